@@ -2,7 +2,12 @@
 
 use clap::{Parser, Subcommand};
 use colored::*;
+use ipc_communication::{IPCMessage, TcpIPCClient};
+use modules::FrecencyStore;
+use search_engine::{FileIndexer, IndexLockedError, PathRemap, ReadOnlyIndex, SearchConfig, SearchEngine};
 use shared_core::ConfigManager;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -33,6 +38,44 @@ enum Commands {
     Import { path: String },
     /// Reset to default configuration
     Reset,
+    /// Inspect or repair the file search index
+    Index {
+        #[command(subcommand)]
+        action: IndexCommands,
+        /// Index directory (defaults to the one used by the files module)
+        #[arg(long, global = true)]
+        path: Option<PathBuf>,
+    },
+    /// Manage the usage history that boosts frequently picked results
+    Usage {
+        #[command(subcommand)]
+        action: UsageCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum UsageCommands {
+    /// Stop boosting a result (e.g. a file picked by mistake)
+    Forget { result_id: String },
+}
+
+#[derive(Subcommand)]
+enum IndexCommands {
+    /// Show document counts, segments and size on disk
+    Stats,
+    /// Verify segment checksums
+    Check,
+    /// Delete the index and index all folders again
+    Rebuild,
+    /// Write a snapshot of the index to an archive
+    Export { archive: PathBuf },
+    /// Replace the index with a snapshot exported on another machine
+    Import {
+        archive: PathBuf,
+        /// Rewrite stored paths starting with FROM to start with TO (repeatable)
+        #[arg(long = "remap", value_name = "FROM=TO")]
+        remaps: Vec<PathRemap>,
+    },
 }
 
 #[tokio::main]
@@ -101,6 +144,23 @@ async fn main() -> anyhow::Result<()> {
             config_manager.save()?;
             println!("{} Configuration reset to defaults", "✅".green());
         }
+        Commands::Index { action, path } => {
+            let index_path = match path {
+                Some(path) => path,
+                None => SearchEngine::default_index_path()?,
+            };
+            if let Err(e) = run_index_command(action, &index_path).await {
+                if let Some(locked) = e.downcast_ref::<IndexLockedError>() {
+                    println!("{} {}", "❌".red(), locked.to_string().red());
+                    println!("{}", "Stop the search daemon before running index commands".dimmed());
+                    std::process::exit(1);
+                }
+                return Err(e);
+            }
+        }
+        Commands::Usage { action: UsageCommands::Forget { result_id } } => {
+            forget_result(&result_id).await?;
+        }
     }
     
     Ok(())
@@ -170,6 +230,123 @@ fn show_config(config_manager: &ConfigManager) {
     println!("{}", "Use 'config-tool --help' for available commands".dimmed());
 }
 
+async fn run_index_command(action: IndexCommands, index_path: &std::path::Path) -> anyhow::Result<()> {
+    // Same folders, scopes and exclusions as the daemon
+    let config = SearchConfig::load_saved()?;
+
+    match action {
+        IndexCommands::Stats => {
+            // Read-only: works while the daemon holds the writer lock
+            let index = ReadOnlyIndex::open(index_path, &config.scopes)?;
+            let stats = index.stats(&config.roots())?;
+
+            println!("\n{}", "🗂️  Search Index".cyan().bold());
+            println!("{}", "═".repeat(40).dimmed());
+            println!("  Path:          {}", index_path.to_string_lossy().dimmed());
+            println!("  Documents:     {}", stats.doc_count.to_string().green());
+            println!("  Deleted:       {}", stats.deleted_doc_count.to_string().dimmed());
+            println!("  Segments:      {}", stats.segment_count.to_string().green());
+            println!("  Size on disk:  {:.1} MB", stats.size_on_disk as f64 / (1024.0 * 1024.0));
+            let last_commit = stats
+                .last_commit
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "never".to_string());
+            println!("  Last commit:   {}", last_commit.green());
+
+            println!("\n{}", "📁 Roots".yellow().bold());
+            for root in &stats.roots {
+                println!("  {}: {}", root.path.to_string_lossy().cyan(), root.doc_count.to_string().green());
+            }
+
+            println!("\n{}", "🔖 Scopes".yellow().bold());
+            for scope in &stats.scopes {
+                println!(
+                    "  {}: {} ({:.1} MB)",
+                    scope.name.cyan(),
+                    scope.doc_count.to_string().green(),
+                    scope.size_on_disk as f64 / (1024.0 * 1024.0)
+                );
+            }
+        }
+        IndexCommands::Check => {
+            let index = ReadOnlyIndex::open(index_path, &config.scopes)?;
+            let report = index.check_integrity()?;
+
+            if report.is_healthy() {
+                println!("{} Search index is healthy", "✅".green());
+            } else {
+                for file in &report.damaged_files {
+                    println!("  {} damaged: {}", "⚠️".yellow(), file.to_string_lossy().red());
+                }
+                for file in &report.missing_files {
+                    println!("  {} missing: {}", "⚠️".yellow(), file.to_string_lossy().red());
+                }
+                println!("{} Search index is damaged, run 'config-tool index rebuild'", "❌".red());
+                std::process::exit(1);
+            }
+        }
+        IndexCommands::Rebuild => {
+            // Recreate instead of opening: a corrupted index may not open at all
+            let engine = Arc::new(SearchEngine::recreate_scoped(index_path, &config.scopes).await?);
+            println!("{} Indexing {} folders...", "🔄".cyan(), config.roots().len());
+            FileIndexer::new(engine.clone(), config).start_indexing().await?;
+
+            let stats = engine.stats(&[])?;
+            println!("{} Index rebuilt with {} documents", "✅".green(), stats.doc_count.to_string().cyan().bold());
+        }
+        IndexCommands::Export { archive } => {
+            let engine = SearchEngine::open_scoped(index_path, &config.scopes).await?;
+            let manifest = engine.export_snapshot(&archive, &config.roots()).await?;
+            let doc_count: u64 = manifest.scopes.iter().map(|scope| scope.doc_count).sum();
+            println!(
+                "{} Exported {} documents to {}",
+                "✅".green(),
+                doc_count.to_string().cyan().bold(),
+                archive.to_string_lossy().green()
+            );
+        }
+        IndexCommands::Import { archive, remaps } => {
+            let (engine, manifest) = SearchEngine::import_snapshot(&archive, index_path, &config.scopes, &remaps).await?;
+            let stats = engine.stats(&[])?;
+            println!("{} Imported snapshot with {} documents", "✅".green(), stats.doc_count.to_string().cyan().bold());
+            for remap in &remaps {
+                println!("  {} {}", "↪".dimmed(), remap.to_string().dimmed());
+            }
+
+            // Pastas da origem que não são indexadas aqui ficam desatualizadas
+            let roots = config.roots();
+            let missing: Vec<_> = manifest.remapped_roots(&remaps).into_iter().filter(|root| !roots.contains(root)).collect();
+            if !missing.is_empty() {
+                println!("\n{}", "⚠️  Folders from the snapshot that are not indexed here:".yellow());
+                for root in &missing {
+                    println!("  {}", root.to_string_lossy().cyan());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn forget_result(result_id: &str) -> anyhow::Result<()> {
+    // A running daemon owns the usage history and would overwrite the file
+    if let Ok(mut client) = TcpIPCClient::new().await {
+        client.send(IPCMessage::ForgetResult { result_id: result_id.to_string() }).await?;
+        println!("{} Asked the daemon to forget '{}'", "✅".green(), result_id.cyan());
+        return Ok(());
+    }
+
+    let mut store = FrecencyStore::load_default();
+    if store.forget(result_id) {
+        store.save()?;
+        println!("{} Forgot usage history of '{}'", "✅".green(), result_id.cyan());
+    } else {
+        println!("{} No usage history for '{}'", "ℹ️".blue(), result_id.cyan());
+    }
+    Ok(())
+}
+
 fn show_module_config(config_manager: &ConfigManager, module_id: &str) {
     if let Some(module_config) = config_manager.get_module_config(module_id) {
         println!("\n{} {}", "📦 Module:".yellow().bold(), module_id.cyan().bold());
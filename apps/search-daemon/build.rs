@@ -1,3 +1,4 @@
 fn main() {
+    #[cfg(feature = "global-shortcuts")]
     tauri_build::build()
-}
\ No newline at end of file
+}
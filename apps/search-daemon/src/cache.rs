@@ -0,0 +1,361 @@
+// Search Result Cache
+// Every keystroke re-runs the module fan-out; this LRU keeps recent result
+// lists, as collected from the modules and before ranking, keyed by
+// (normalized query, module filter, config revision). Entries expire by the
+// shortest TTL among the modules that produced them, and a cached query can
+// answer a longer query that extends it ("budg" → "budget") for the modules
+// that declare substring matching (`SearchModule::matches_by_substring`);
+// the other modules are still searched.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use modules::{SearchQuery, SearchResult};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub query: String,
+    pub module_filter: Option<String>,
+    pub config_revision: u64,
+}
+
+impl CacheKey {
+    pub fn new(query: &SearchQuery, config_revision: u64) -> Self {
+        Self {
+            query: normalize_query(&query.text),
+            module_filter: query.module_filter.clone(),
+            config_revision,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchCacheConfig {
+    pub capacity: usize,
+    /// TTL for modules without an entry in `module_ttls`
+    pub default_ttl: Duration,
+    pub module_ttls: HashMap<String, Duration>,
+    /// Modules whose results for "abc" always include the results for "abcd";
+    /// filled from the modules that declare substring matching
+    pub prefix_reuse_modules: Vec<String>,
+}
+
+impl Default for SearchCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 256,
+            default_ttl: Duration::from_secs(10),
+            module_ttls: HashMap::from([
+                // Clock, dates and calculator answers go stale within a second
+                ("daily".to_string(), Duration::from_secs(1)),
+                ("files".to_string(), Duration::from_secs(30)),
+                ("apps".to_string(), Duration::from_secs(300)),
+            ]),
+            prefix_reuse_modules: Vec::new(),
+        }
+    }
+}
+
+/// Shorter queries are matched exactly rather than by prefix (e.g. by the files
+/// index), so their results don't hold those of a longer query
+const MIN_REUSE_QUERY_CHARS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheLookup {
+    Hit(Vec<SearchResult>),
+    /// Part of the answer filtered from the results of a shorter query
+    PrefixHit(PrefixReuse),
+    Miss,
+}
+
+/// Results of some modules filtered from a cached shorter query
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixReuse {
+    pub results: Vec<SearchResult>,
+    /// Modules fully answered by `results`; only the others need a search
+    pub modules: Vec<String>,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    results: Vec<SearchResult>,
+    expires_at: Instant,
+    /// Modules returning this many results may have been truncated
+    max_results: usize,
+}
+
+pub struct SearchCache {
+    entries: LruCache<CacheKey, CacheEntry>,
+    config: SearchCacheConfig,
+}
+
+impl SearchCache {
+    pub fn new(config: SearchCacheConfig) -> Self {
+        let capacity = NonZeroUsize::new(config.capacity).unwrap_or(NonZeroUsize::MIN);
+        Self { entries: LruCache::new(capacity), config }
+    }
+
+    pub fn lookup(&mut self, query: &SearchQuery, config_revision: u64, now: Instant) -> CacheLookup {
+        let key = CacheKey::new(query, config_revision);
+
+        match self.entries.get(&key) {
+            Some(entry) if entry.expires_at > now => return CacheLookup::Hit(entry.results.clone()),
+            Some(_) => {
+                self.entries.pop(&key);
+            }
+            None => {}
+        }
+
+        if !self.allows_prefix_reuse(key.module_filter.as_deref()) {
+            return CacheLookup::Miss;
+        }
+
+        // Longest cached query that the new one extends
+        let superset = self
+            .entries
+            .iter()
+            .filter(|(cached, entry)| {
+                cached.module_filter == key.module_filter
+                    && cached.config_revision == key.config_revision
+                    && cached.query.chars().count() >= MIN_REUSE_QUERY_CHARS
+                    && key.query.len() > cached.query.len()
+                    && key.query.starts_with(&cached.query)
+                    && entry.expires_at > now
+            })
+            .max_by_key(|(cached, _)| cached.query.len())
+            .map(|(_, entry)| entry.clone());
+
+        let Some(superset) = superset else {
+            return CacheLookup::Miss;
+        };
+        let mut reuse = PrefixReuse { results: Vec::new(), modules: Vec::new(), expires_at: superset.expires_at };
+        let modules = self.config.prefix_reuse_modules.iter().filter(|module| {
+            key.module_filter.as_ref().is_none_or(|filter| filter == *module)
+        });
+        for module in modules {
+            let cached: Vec<&SearchResult> = superset
+                .results
+                .iter()
+                .filter(|result| result.metadata.get("module") == Some(module))
+                .collect();
+            // A truncated list may be missing matches for the longer query
+            if cached.len() >= superset.max_results {
+                continue;
+            }
+            let kept: Vec<SearchResult> = cached
+                .iter()
+                .filter(|result| matches_query(result, &key.query))
+                .map(|result| (*result).clone())
+                .collect();
+            // Nothing left may just mean the local filter is stricter than the module
+            if kept.is_empty() && !cached.is_empty() {
+                continue;
+            }
+            reuse.modules.push(module.clone());
+            reuse.results.extend(kept);
+        }
+
+        if reuse.modules.is_empty() {
+            return CacheLookup::Miss;
+        }
+        CacheLookup::PrefixHit(reuse)
+    }
+
+    pub fn insert(&mut self, query: &SearchQuery, config_revision: u64, results: Vec<SearchResult>, now: Instant) {
+        let ttl = self.ttl_for(query, &results);
+        self.put(query, config_revision, results, now + ttl, now);
+    }
+
+    /// Cache the answer built from `reuse` and a search of the other modules; the
+    /// reused part must not outlive the entry it came from
+    pub fn insert_extended(
+        &mut self,
+        query: &SearchQuery,
+        config_revision: u64,
+        results: Vec<SearchResult>,
+        reuse: &PrefixReuse,
+        now: Instant,
+    ) {
+        let ttl = self.ttl_for(query, &results);
+        self.put(query, config_revision, results, (now + ttl).min(reuse.expires_at), now);
+    }
+
+    pub fn set_prefix_reuse_modules(&mut self, modules: Vec<String>) {
+        self.config.prefix_reuse_modules = modules;
+    }
+
+    /// Shortest TTL among the modules that answered (or were asked, when nothing matched)
+    fn ttl_for(&self, query: &SearchQuery, results: &[SearchResult]) -> Duration {
+        let module_ttl = |module: &str| self.config.module_ttls.get(module).copied().unwrap_or(self.config.default_ttl);
+
+        let from_results = results
+            .iter()
+            .filter_map(|result| result.metadata.get("module"))
+            .map(|module| module_ttl(module))
+            .min();
+        match (from_results, &query.module_filter) {
+            (Some(ttl), _) => ttl,
+            (None, Some(module)) => module_ttl(module),
+            (None, None) => self.config.default_ttl,
+        }
+    }
+
+    fn put(&mut self, query: &SearchQuery, config_revision: u64, results: Vec<SearchResult>, expires_at: Instant, now: Instant) {
+        if expires_at <= now {
+            return;
+        }
+        self.entries.put(
+            CacheKey::new(query, config_revision),
+            CacheEntry { results, expires_at, max_results: query.max_results },
+        );
+    }
+
+    /// A filter on a module whose answers change completely as the query grows
+    /// (like the calculator) leaves nothing to reuse
+    fn allows_prefix_reuse(&self, module_filter: Option<&str>) -> bool {
+        match module_filter {
+            Some(module) => self.config.prefix_reuse_modules.iter().any(|m| m == module),
+            None => !self.config.prefix_reuse_modules.is_empty(),
+        }
+    }
+}
+
+/// Lowercase, trimmed, with runs of whitespace collapsed
+pub fn normalize_query(text: &str) -> String {
+    text.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>().join(" ")
+}
+
+/// Every query word appears in the result's visible text or path
+fn matches_query(result: &SearchResult, query: &str) -> bool {
+    let haystack = format!(
+        "{} {} {}",
+        result.title,
+        result.description,
+        result.metadata.get("path").map(String::as_str).unwrap_or_default()
+    )
+    .to_lowercase();
+    query.split_whitespace().all(|word| haystack.contains(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str, module: Option<&str>) -> SearchQuery {
+        SearchQuery {
+            text: text.to_string(),
+            module_filter: module.map(str::to_string),
+            max_results: 10,
+            timeout_ms: 3000,
+        }
+    }
+
+    fn result(title: &str, module: &str) -> SearchResult {
+        SearchResult {
+            id: title.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+            icon: None,
+            action_type: "open".to_string(),
+            score: 1.0,
+            metadata: HashMap::from([("module".to_string(), module.to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_hit_miss_and_key_normalization() {
+        let mut cache = SearchCache::new(SearchCacheConfig::default());
+        let now = Instant::now();
+        let files = vec![result("budget.md", "files")];
+
+        assert_eq!(cache.lookup(&query("Budget", Some("files")), 1, now), CacheLookup::Miss);
+        cache.insert(&query("Budget", Some("files")), 1, files.clone(), now);
+
+        assert_eq!(cache.lookup(&query("  budget ", Some("files")), 1, now), CacheLookup::Hit(files));
+        assert_eq!(cache.lookup(&query("budget", None), 1, now), CacheLookup::Miss);
+        // A configuration change invalidates older entries
+        assert_eq!(cache.lookup(&query("budget", Some("files")), 2, now), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn test_per_module_ttl() {
+        let mut cache = SearchCache::new(SearchCacheConfig::default());
+        let now = Instant::now();
+        cache.insert(&query("time", None), 1, vec![result("12:00", "daily"), result("timesheet.xlsx", "files")], now);
+        cache.insert(&query("notes", None), 1, vec![result("notes.md", "files")], now);
+
+        let later = now + Duration::from_secs(2);
+        assert_eq!(cache.lookup(&query("time", None), 1, later), CacheLookup::Miss);
+        assert!(matches!(cache.lookup(&query("notes", None), 1, later), CacheLookup::Hit(_)));
+        assert_eq!(cache.lookup(&query("notes", None), 1, now + Duration::from_secs(31)), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn test_prefix_superset_reuse() {
+        let config = SearchCacheConfig { prefix_reuse_modules: vec!["files".to_string()], ..SearchCacheConfig::default() };
+        let mut cache = SearchCache::new(config);
+        let now = Instant::now();
+        let results = vec![result("budget.md", "files"), result("budapest.jpg", "files")];
+        cache.insert(&query("bud", Some("files")), 1, results, now);
+
+        let CacheLookup::PrefixHit(reuse) = cache.lookup(&query("budg", Some("files")), 1, now) else {
+            panic!("expected a prefix hit");
+        };
+        assert_eq!(reuse.results, vec![result("budget.md", "files")]);
+        assert_eq!(reuse.modules, ["files"]);
+        // The answer built from it is cached under the longer query, but no longer than the original
+        cache.insert_extended(&query("budg", Some("files")), 1, reuse.results.clone(), &reuse, now + Duration::from_secs(20));
+        assert!(matches!(cache.lookup(&query("budg", Some("files")), 1, now), CacheLookup::Hit(_)));
+        assert_eq!(cache.lookup(&query("budg", Some("files")), 1, now + Duration::from_secs(31)), CacheLookup::Miss);
+        assert_eq!(cache.lookup(&query("budx", Some("files")), 1, now), CacheLookup::Miss);
+
+        // Not for modules whose answers change as the query grows
+        cache.insert(&query("2+2", Some("daily")), 1, vec![result("2+2", "daily")], now);
+        assert_eq!(cache.lookup(&query("2+2*3", Some("daily")), 1, now), CacheLookup::Miss);
+
+        // Nor for modules that didn't declare substring matching
+        cache.insert(&query("fire", Some("apps")), 1, vec![result("Firefox", "apps")], now);
+        assert_eq!(cache.lookup(&query("firef", Some("apps")), 1, now), CacheLookup::Miss);
+
+        // A truncated list may be missing matches for the longer query
+        let full: Vec<SearchResult> = (0..10).map(|i| result(&format!("report{}.pdf", i), "files")).collect();
+        cache.insert(&query("rep", Some("files")), 1, full, now);
+        assert_eq!(cache.lookup(&query("report", Some("files")), 1, now), CacheLookup::Miss);
+
+        // Single characters aren't matched as prefixes
+        cache.insert(&query("n", Some("files")), 1, vec![result("n.txt", "files")], now);
+        assert_eq!(cache.lookup(&query("no", Some("files")), 1, now), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn test_prefix_reuse_without_module_filter() {
+        let config = SearchCacheConfig {
+            prefix_reuse_modules: vec!["files".to_string(), "apps".to_string()],
+            ..SearchCacheConfig::default()
+        };
+        let mut cache = SearchCache::new(config);
+        let now = Instant::now();
+        let results = vec![result("firewall.conf", "files"), result("Firefox", "apps"), result("fir", "daily")];
+        cache.insert(&query("fir", None), 1, results, now);
+
+        // The daily module is left out and searched again
+        let CacheLookup::PrefixHit(reuse) = cache.lookup(&query("fire", None), 1, now) else {
+            panic!("expected a prefix hit");
+        };
+        assert_eq!(reuse.modules, ["files", "apps"]);
+        assert_eq!(reuse.results, vec![result("firewall.conf", "files"), result("Firefox", "apps")]);
+
+        // So is a module whose list was full
+        let mut results: Vec<SearchResult> = (0..10).map(|i| result(&format!("Firefox {}", i), "apps")).collect();
+        results.push(result("firewall.conf", "files"));
+        cache.insert(&query("fir", None), 2, results, now);
+        let CacheLookup::PrefixHit(reuse) = cache.lookup(&query("fire", None), 2, now) else {
+            panic!("expected a prefix hit");
+        };
+        assert_eq!(reuse.modules, ["files"]);
+        assert_eq!(reuse.results, vec![result("firewall.conf", "files")]);
+    }
+}
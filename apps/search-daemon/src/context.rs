@@ -0,0 +1,185 @@
+// Search Context Detection
+// Builds the ModuleSearchContext handed to modules: user preferences, locale,
+// active module and whatever we can learn about the desktop session.
+
+use std::collections::HashMap;
+use std::process::Command;
+use log::debug;
+
+use modules::{ModuleSearchContext, SearchQuery, SystemInfo};
+use crate::state::DaemonState;
+
+pub fn build_search_context(query: SearchQuery, state: &DaemonState) -> ModuleSearchContext {
+    let active_module = state.current_module.clone();
+
+    let mut user_preferences = HashMap::new();
+    if let Some(config) = active_module.as_deref().and_then(|id| state.get_module_config(id)) {
+        for (key, value) in &config.custom_settings {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            user_preferences.insert(key.clone(), value);
+        }
+    }
+
+    ModuleSearchContext {
+        query,
+        user_preferences,
+        locale: detect_locale(),
+        active_module,
+        // Prefer the snapshot taken when the overlay was toggled: once the overlay
+        // is shown it becomes the foreground window itself.
+        system_info: state.system_info.clone().unwrap_or_default(),
+    }
+}
+
+pub fn detect_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find_map(|value| normalize_locale(&value))
+        .unwrap_or_else(|| "en-US".to_string())
+}
+
+/// Convert a POSIX locale ("pt_BR.UTF-8@euro") into a BCP 47 tag ("pt-BR")
+fn normalize_locale(raw: &str) -> Option<String> {
+    let tag = raw.split(['.', '@']).next().unwrap_or("").trim();
+
+    if tag.is_empty() || tag == "C" || tag == "POSIX" {
+        return None;
+    }
+
+    Some(tag.replace('_', "-"))
+}
+
+pub fn detect_system_info() -> SystemInfo {
+    let info = SystemInfo {
+        platform: std::env::consts::OS.to_string(),
+        current_app: detect_foreground_app(),
+        screen_resolution: detect_screen_resolution().unwrap_or((0, 0)),
+        current_workspace: detect_workspace(),
+    };
+
+    debug!("🖥️  Detected system info: {:?}", info);
+    info
+}
+
+#[cfg(target_os = "linux")]
+fn detect_foreground_app() -> Option<String> {
+    // X11 only: Wayland compositors don't expose the focused window to other clients
+    let pid = run_command("xdotool", &["getactivewindow", "getwindowpid"])?;
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid.trim())).ok()?;
+    let comm = comm.trim();
+
+    if comm.is_empty() {
+        None
+    } else {
+        Some(comm.to_string())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_foreground_app() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn detect_screen_resolution() -> Option<(u32, u32)> {
+    let output = run_command("xrandr", &["--current"])?;
+    parse_xrandr_resolution(&output)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_screen_resolution() -> Option<(u32, u32)> {
+    None
+}
+
+/// Parse the "current W x H" part of the xrandr screen line
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_xrandr_resolution(output: &str) -> Option<(u32, u32)> {
+    let line = output.lines().find(|line| line.starts_with("Screen "))?;
+    let current = line.split(',').find_map(|part| part.trim().strip_prefix("current "))?;
+    let (width, height) = current.split_once(" x ")?;
+
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+#[cfg(target_os = "linux")]
+fn detect_workspace() -> Option<String> {
+    run_command("xdotool", &["get_desktop"])
+        .map(|desktop| desktop.trim().to_string())
+        .filter(|desktop| !desktop.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_workspace() -> Option<String> {
+    None
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn run_command(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ModuleConfig;
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("pt_BR.UTF-8").as_deref(), Some("pt-BR"));
+        assert_eq!(normalize_locale("de_DE@euro").as_deref(), Some("de-DE"));
+        assert_eq!(normalize_locale("en").as_deref(), Some("en"));
+        assert_eq!(normalize_locale("C.UTF-8"), None);
+        assert_eq!(normalize_locale(""), None);
+    }
+
+    #[test]
+    fn test_parse_xrandr_resolution() {
+        let output = "Screen 0: minimum 320 x 200, current 2560 x 1440, maximum 16384 x 16384\n\
+                      eDP-1 connected primary 2560x1440+0+0";
+        assert_eq!(parse_xrandr_resolution(output), Some((2560, 1440)));
+        assert_eq!(parse_xrandr_resolution("garbage"), None);
+    }
+
+    #[test]
+    fn test_build_search_context() {
+        let mut state = DaemonState::new();
+        state.set_current_module("daily".to_string());
+        state.set_module_config("daily".to_string(), ModuleConfig {
+            enabled: true,
+            priority: 1,
+            custom_settings: HashMap::from([
+                ("time_format_24h".to_string(), serde_json::json!(false)),
+                ("theme".to_string(), serde_json::json!("dark")),
+            ]),
+        });
+        state.system_info = Some(SystemInfo {
+            platform: "linux".to_string(),
+            current_app: Some("firefox".to_string()),
+            screen_resolution: (1920, 1080),
+            current_workspace: Some("1".to_string()),
+        });
+
+        let query = SearchQuery {
+            text: "time".to_string(),
+            module_filter: Some("daily".to_string()),
+            max_results: 10,
+            timeout_ms: 3000,
+        };
+        let context = build_search_context(query, &state);
+
+        assert_eq!(context.active_module.as_deref(), Some("daily"));
+        assert_eq!(context.preference("time_format_24h"), Some("false"));
+        assert_eq!(context.preference("theme"), Some("dark"));
+        assert_eq!(context.system_info.current_app.as_deref(), Some("firefox"));
+    }
+}
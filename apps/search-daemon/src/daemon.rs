@@ -1,119 +1,217 @@
 // Search Daemon Core Implementation
+// Builds the daemon context (state, handler registry, TCP IPC server and
+// config) and runs the background services. Frontends (the Tauri global
+// shortcut, or none when headless) get the context and only call into it.
 
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use log::{info, error, warn};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use log::{info, debug, warn};
 
+use crate::daemon_context::DaemonContext;
+use crate::handlers::{HandlerRegistry, OverlayRequest};
 use crate::state::DaemonState;
-use ipc_communication::{IPCServer, IPCMessage};
+use ipc_communication::{IPCMessage, TcpIPCServer};
+use shared_core::R5Config;
+
+/// How long shutdown waits for running searches
+const SHUTDOWN_DRAIN_DEADLINE: Duration = Duration::from_secs(5);
 
 pub struct SearchDaemon {
-    state: Arc<RwLock<DaemonState>>,
-    ipc_server: Option<IPCServer>,
+    context: Arc<DaemonContext>,
+    ipc_server: Arc<TcpIPCServer>,
+    /// Where the state is saved, `None` without a data directory
+    state_path: Option<PathBuf>,
+    /// Shortcut presses (`TriggerShortcut`) and show/hide requests sent over IPC,
+    /// taken by `start`
+    overlay_requests: Mutex<Option<mpsc::UnboundedReceiver<OverlayRequest>>>,
 }
 
 impl SearchDaemon {
-    pub fn new() -> Self {
-        Self {
-            state: Arc::new(RwLock::new(DaemonState::new())),
-            ipc_server: None,
-        }
-    }
-    
-    pub async fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Bind the IPC server (or serve on `listener`, when the service manager
+    /// passed one) and start serving client messages
+    pub async fn initialize(
+        config: R5Config,
+        listener: Option<std::net::TcpListener>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         info!("Initializing search daemon...");
-        
-        // Initialize IPC server
-        let mut ipc_server = IPCServer::new().await?;
-        self.setup_ipc_handlers(&mut ipc_server).await?;
+
+        let state_path = DaemonState::default_path();
+        let state = match state_path.as_deref().and_then(DaemonState::load) {
+            Some(state) => state,
+            None => {
+                // First run: search every enabled module until a client picks one
+                info!("✅ No saved state, searching all enabled modules");
+                DaemonState::new()
+            }
+        };
+        let state = Arc::new(RwLock::new(state));
+
+        let (request_sender, overlay_requests) = mpsc::unbounded_channel();
+        let registry = Arc::new(HandlerRegistry::with_default_handlers(state.clone(), request_sender));
+
+        let mut ipc_server = match listener {
+            Some(listener) => TcpIPCServer::from_std_listener(listener)?,
+            None => TcpIPCServer::new().await?,
+        };
+        ipc_server.set_handler(registry.clone());
         ipc_server.start().await?;
-        
-        self.ipc_server = Some(ipc_server);
-        
-        info!("Search daemon initialized successfully");
-        Ok(())
+        info!("📡 TCP IPC Server started - ready for overlay connections");
+
+        let ipc_server = Arc::new(ipc_server);
+        Ok(Self {
+            context: Arc::new(DaemonContext::new(state, registry, ipc_server.clone(), config)),
+            ipc_server,
+            state_path,
+            overlay_requests: Mutex::new(Some(overlay_requests)),
+        })
     }
-    
+
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Starting search daemon services...");
-        
+
         // Start background tasks
         self.start_background_tasks().await?;
-        
+        debug!("🔧 Serving IPC message kinds: {:?}", self.context.registry.kinds());
+
+        // Shortcut presses forwarded by the desktop environment, show
+        // requests forwarded by a second launch and hide requests, over IPC
+        let requests = self.overlay_requests.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(mut requests) = requests {
+            let ctx = self.context.clone();
+            tokio::spawn(async move {
+                while let Some(request) = requests.recv().await {
+                    match request {
+                        OverlayRequest::Toggle => ctx.toggle_overlay().await,
+                        OverlayRequest::Show(query) => ctx.show_overlay(query).await,
+                        OverlayRequest::Hide => ctx.hide_overlay().await,
+                    }
+                }
+            });
+        }
+
         info!("Search daemon started successfully");
         Ok(())
     }
-    
+
     pub async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Shutting down search daemon...");
-        
+
+        // Tell overlays first so they stop sending queries
+        let status = IPCMessage::DaemonStatus { running: false, pid: Some(std::process::id()) };
+        if let Err(e) = self.context.broadcast(status).await {
+            warn!("⚠️ Failed to announce shutdown: {}", e);
+        }
+        self.ipc_server.stop_accepting();
+
+        // Drain searches, then let the modules commit and release their resources
+        self.context.registry.shutdown(SHUTDOWN_DRAIN_DEADLINE).await;
+
         // Cleanup operations
         self.cleanup().await?;
-        
+
         info!("Search daemon shutdown completed");
         Ok(())
     }
-    
-    pub fn get_state(&self) -> Arc<RwLock<DaemonState>> {
-        self.state.clone()
+
+    /// Resolves once a client asked the daemon to stop over IPC
+    pub async fn stop_requested(&self) {
+        self.context.registry.stop_requested().await
     }
-    
-    async fn setup_ipc_handlers(&self, ipc_server: &mut IPCServer) -> Result<(), Box<dyn std::error::Error>> {
-        use crate::handlers::*;
-        
-        // Register all handlers
-        ipc_server.register_handler("ping", PingHandler::new());
-        ipc_server.register_handler("toggle_overlay", OverlayHandler::new());
-        ipc_server.register_handler("show_overlay", OverlayHandler::new());
-        ipc_server.register_handler("hide_overlay", OverlayHandler::new());
-        ipc_server.register_handler("search_query", SearchHandler::new(self.state.clone()));
-        ipc_server.register_handler("clear_results", SearchHandler::new(self.state.clone()));
-        ipc_server.register_handler("update_module", ModuleHandler::new(self.state.clone()));
-        ipc_server.register_handler("get_current_module", ModuleHandler::new(self.state.clone()));
-        ipc_server.register_handler("daemon_status", DaemonControlHandler::new(self.state.clone()));
-        ipc_server.register_handler("stop_daemon", DaemonControlHandler::new(self.state.clone()));
-        
-        info!("IPC handlers registered successfully");
-        Ok(())
+
+    pub fn context(&self) -> Arc<DaemonContext> {
+        self.context.clone()
     }
-    
+
     async fn start_background_tasks(&self) -> Result<(), Box<dyn std::error::Error>> {
         // Start stats monitoring task
-        let state_clone = self.state.clone();
+        let state_clone = self.context.state.clone();
+        let state_path = self.state_path.clone();
         tokio::spawn(async move {
-            Self::stats_monitoring_task(state_clone).await;
+            Self::stats_monitoring_task(state_clone, state_path).await;
         });
-        
+
         // Start cleanup task
-        let state_clone = self.state.clone();
+        let state_clone = self.context.state.clone();
         tokio::spawn(async move {
             Self::cleanup_task(state_clone).await;
         });
-        
+
+        // Indexing progress broadcaster, at most one update per interval
+        let mut progress = self.context.state.read().await.indexing.subscribe();
+        let ctx = self.context.clone();
+        tokio::spawn(async move {
+            let interval = tokio::time::Duration::from_millis(500);
+
+            while progress.changed().await.is_ok() {
+                let current = progress.borrow_and_update().clone();
+                let message = IPCMessage::IndexingProgress {
+                    files_seen: current.files_seen,
+                    files_indexed: current.files_indexed,
+                    files_skipped: current.files_skipped,
+                    done: current.done,
+                };
+                if let Err(e) = ctx.broadcast(message).await {
+                    debug!("📭 Indexing progress not delivered: {}", e);
+                }
+                if !current.done {
+                    tokio::time::sleep(interval).await;
+                }
+            }
+        });
+
         info!("Background tasks started");
         Ok(())
     }
-    
-    async fn stats_monitoring_task(state: Arc<RwLock<DaemonState>>) {
+
+    async fn stats_monitoring_task(state: Arc<RwLock<DaemonState>>, state_path: Option<PathBuf>) {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-        
+        // The first tick completes immediately
+        interval.tick().await;
+
         loop {
             interval.tick().await;
-            
-            // Update memory usage stats
-            if let Ok(memory_usage) = Self::get_memory_usage() {
+
+            let persisted = {
                 let mut state_guard = state.write().await;
-                state_guard.update_stats(memory_usage);
+                state_guard.stats.uptime_seconds += 60;
+
+                // Update memory usage stats
+                if let Ok(memory_usage) = Self::get_memory_usage() {
+                    state_guard.update_stats(memory_usage);
+                }
+
+                // Log periodic stats
+                if state_guard.stats.uptime_seconds % 300 == 0 { // Every 5 minutes
+                    info!("📊 Daemon Stats - Uptime: {}s, Shortcuts: {}, Searches: {}, Cache hit rate: {:.0}%",
+                          state_guard.stats.uptime_seconds,
+                          state_guard.stats.shortcuts_triggered,
+                          state_guard.stats.searches_performed,
+                          state_guard.stats.cache_hit_rate() * 100.0);
+                }
+
+                state_guard.persisted()
+            };
+
+            // Checkpoint, so a crash loses at most a minute of stats; written
+            // after the lock is released so searches don't wait on the disk
+            if let Some(path) = state_path.clone() {
+                match tokio::task::spawn_blocking(move || persisted.save(&path)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => warn!("⚠️ Failed to save daemon state: {}", e),
+                    Err(e) => warn!("⚠️ Daemon state checkpoint panicked: {}", e),
+                }
             }
         }
     }
-    
+
     async fn cleanup_task(state: Arc<RwLock<DaemonState>>) {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // 5 minutes
-        
+
         loop {
             interval.tick().await;
-            
+
             // Cleanup expired search sessions
             {
                 let mut state_guard = state.write().await;
@@ -123,7 +221,7 @@ impl SearchDaemon {
                         .duration_since(std::time::UNIX_EPOCH)
                         .unwrap_or_default()
                         .as_millis() as u64;
-                    
+
                     let elapsed = now.saturating_sub(state_guard.stats.last_activity);
                     if elapsed > 300000 { // 5 minutes
                         warn!("Cleaning up expired search session: {}", session_id);
@@ -133,7 +231,7 @@ impl SearchDaemon {
             }
         }
     }
-    
+
     fn get_memory_usage() -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
         // Get current process memory usage
         #[cfg(unix)]
@@ -149,26 +247,30 @@ impl SearchDaemon {
                 }
             }
         }
-        
-        #[cfg(windows)]
-        {
-            // TODO: Implement Windows memory usage detection
-            return Ok(0);
-        }
-        
+
+        // TODO: Implement Windows memory usage detection
         Ok(0)
     }
-    
+
     async fn cleanup(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Save current state if needed
-        let state = self.state.read().await;
+        let (persisted, stats) = {
+            let state = self.context.state.read().await;
+            (state.persisted(), state.stats.clone())
+        };
+        if let Some(path) = &self.state_path {
+            let target = path.clone();
+            tokio::task::spawn_blocking(move || persisted.save(&target)).await??;
+            info!("💾 Daemon state saved to {:?}", path);
+        }
+
         info!("Final daemon stats:");
-        info!("  Shortcuts triggered: {}", state.stats.shortcuts_triggered);
-        info!("  Searches performed: {}", state.stats.searches_performed);
-        info!("  Memory usage: {} KB", state.stats.memory_usage_kb);
-        
+        info!("  Shortcuts triggered: {}", stats.shortcuts_triggered);
+        info!("  Searches performed: {}", stats.searches_performed);
+        info!("  Cache hit rate: {:.0}%", stats.cache_hit_rate() * 100.0);
+        info!("  Memory usage: {} KB", stats.memory_usage_kb);
+
         // Additional cleanup operations can be added here
-        
+
         Ok(())
     }
 }
@@ -177,4 +279,4 @@ impl Drop for SearchDaemon {
     fn drop(&mut self) {
         info!("Search daemon instance dropped");
     }
-}
\ No newline at end of file
+}
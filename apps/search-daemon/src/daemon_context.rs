@@ -0,0 +1,257 @@
+// Daemon Context
+// Everything the shortcut frontends and background services share: the
+// daemon state, the IPC handler registry, the transport that reaches the
+// connected overlays and the loaded configuration.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use log::{info, error, debug, warn};
+
+use crate::context;
+use crate::handlers::HandlerRegistry;
+use crate::state::DaemonState;
+use ipc_communication::{Broadcaster, IPCMessage};
+use shared_core::R5Config;
+
+pub struct DaemonContext {
+    pub state: Arc<RwLock<DaemonState>>,
+    pub registry: Arc<HandlerRegistry>,
+    pub broadcaster: Arc<dyn Broadcaster>,
+    pub config: R5Config,
+}
+
+impl DaemonContext {
+    pub fn new(
+        state: Arc<RwLock<DaemonState>>,
+        registry: Arc<HandlerRegistry>,
+        broadcaster: Arc<dyn Broadcaster>,
+        config: R5Config,
+    ) -> Self {
+        Self { state, registry, broadcaster, config }
+    }
+
+    pub async fn broadcast(&self, message: IPCMessage) -> Result<(), String> {
+        debug!("📤 Broadcasting IPC message: {:?}", message);
+        self.broadcaster.broadcast(message).await.map_err(|e| e.to_string())
+    }
+
+    /// Show or hide the overlay, spawning it if no overlay is connected
+    pub async fn toggle_overlay(&self) {
+        info!("🔥 Shortcut triggered!");
+
+        if self.state.read().await.is_processing_shortcut() {
+            debug!("Shortcut already processing, ignoring");
+            return;
+        }
+
+        // Snapshot the desktop before the overlay takes focus
+        let system_info = tokio::task::spawn_blocking(context::detect_system_info).await.ok();
+        let overlay_connected = self.broadcaster.client_count().await > 0;
+
+        // Update daemon statistics
+        {
+            let mut state = self.state.write().await;
+            state.set_processing_shortcut(true);
+            state.system_info = system_info;
+            // A freshly spawned overlay is always shown
+            let visible = !overlay_connected || !state.overlay_visible;
+            state.set_overlay_visible(visible);
+            state.stats.last_activity = shared_core::utils::current_timestamp_ms();
+            debug!("📊 Shortcut count: {}", state.stats.shortcuts_triggered);
+        }
+
+        if overlay_connected {
+            info!("🔄 Sending ToggleOverlay directly to overlay via IPC");
+            match self.broadcast(IPCMessage::ToggleOverlay).await {
+                Ok(_) => info!("📡 Toggle overlay broadcasted to all connected clients"),
+                Err(e) => error!("❌ Failed to broadcast toggle overlay: {}", e),
+            }
+        } else if self.launch_overlay().await {
+            if let Err(e) = self.broadcast(IPCMessage::ShowOverlay { query: None }).await {
+                error!("❌ Failed to communicate with spawned overlay: {}", e);
+            }
+        }
+
+        self.state.write().await.set_processing_shortcut(false);
+    }
+
+    /// Show the overlay with `query` typed in, spawning it if no overlay is connected
+    pub async fn show_overlay(&self, query: Option<String>) {
+        info!("🪟 Show overlay requested: {:?}", query);
+
+        if self.broadcaster.client_count().await == 0 && !self.launch_overlay().await {
+            return;
+        }
+
+        self.state.write().await.set_overlay_visible(true);
+        if let Err(e) = self.broadcast(IPCMessage::ShowOverlay { query }).await {
+            error!("❌ Failed to broadcast show overlay: {}", e);
+        }
+    }
+
+    /// Hide the overlay and resume background indexing
+    pub async fn hide_overlay(&self) {
+        info!("🙈 Hide overlay requested");
+
+        self.state.write().await.set_overlay_visible(false);
+        if self.broadcaster.client_count().await == 0 {
+            return;
+        }
+        if let Err(e) = self.broadcast(IPCMessage::HideOverlay).await {
+            error!("❌ Failed to broadcast hide overlay: {}", e);
+        }
+    }
+
+    /// Start the overlay, unless one is already running but not connected yet,
+    /// and give it time to connect
+    async fn launch_overlay(&self) -> bool {
+        if let Some(pid) = shared_core::running_instance(shared_core::OVERLAY_INSTANCE) {
+            info!("⏳ Overlay already running (PID {}), waiting for it to connect", pid);
+        } else {
+            warn!("⚠️ No overlay connected, attempting to spawn overlay");
+            match spawn_overlay_process(self.config.ui.overlay_path.as_deref()) {
+                Ok(_) => info!("🚀 Overlay process spawned successfully"),
+                Err(e) => {
+                    error!("❌ Failed to spawn overlay process: {}", e);
+                    return false;
+                }
+            }
+        }
+
+        // Wait a bit for overlay to start, then try again
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        true
+    }
+}
+
+/// The configured overlay executable, or `real-overlay` installed next to the
+/// daemon; the working directory is `/` under systemd, so it can't be used
+fn overlay_executable(configured: Option<&Path>) -> Result<PathBuf, String> {
+    if let Some(path) = configured {
+        return Ok(path.to_path_buf());
+    }
+
+    let daemon = std::env::current_exe()
+        .map_err(|e| format!("Failed to locate the daemon executable: {}", e))?;
+    let dir = daemon.parent()
+        .ok_or_else(|| format!("Daemon executable {} has no parent directory", daemon.display()))?;
+    Ok(dir.join(format!("real-overlay{}", std::env::consts::EXE_SUFFIX)))
+}
+
+fn spawn_overlay_process(configured: Option<&Path>) -> Result<(), String> {
+    info!("🚀 Spawning overlay process...");
+
+    let overlay_path = overlay_executable(configured)?;
+
+    if !overlay_path.exists() {
+        return Err(format!("Overlay binary not found at: {}", overlay_path.display()));
+    }
+
+    let child = std::process::Command::new(&overlay_path)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn overlay process: {}", e))?;
+
+    info!("🎯 Overlay process spawned with PID: {}", child.id());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ipc_communication::IPCResult;
+    use std::sync::Mutex;
+
+    /// Records broadcasts instead of writing to sockets
+    struct FakeBroadcaster {
+        clients: usize,
+        sent: Mutex<Vec<IPCMessage>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Broadcaster for FakeBroadcaster {
+        async fn broadcast(&self, message: IPCMessage) -> IPCResult<()> {
+            self.sent.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        async fn client_count(&self) -> usize {
+            self.clients
+        }
+    }
+
+    #[tokio::test]
+    async fn test_toggle_reaches_connected_overlays() {
+        let fake = Arc::new(FakeBroadcaster { clients: 1, sent: Mutex::new(Vec::new()) });
+        let ctx = DaemonContext::new(
+            Arc::new(RwLock::new(DaemonState::new())),
+            Arc::new(HandlerRegistry::new()),
+            fake.clone(),
+            R5Config::default(),
+        );
+
+        ctx.toggle_overlay().await;
+        {
+            let state = ctx.state.read().await;
+            assert!(state.overlay_visible);
+            assert_eq!(state.stats.shortcuts_triggered, 1);
+            assert!(!state.is_processing_shortcut());
+        }
+        ctx.toggle_overlay().await;
+        assert!(!ctx.state.read().await.overlay_visible);
+
+        let sent = fake.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|message| matches!(message, IPCMessage::ToggleOverlay)));
+    }
+
+    #[tokio::test]
+    async fn test_show_reaches_connected_overlays() {
+        let fake = Arc::new(FakeBroadcaster { clients: 1, sent: Mutex::new(Vec::new()) });
+        let ctx = DaemonContext::new(
+            Arc::new(RwLock::new(DaemonState::new())),
+            Arc::new(HandlerRegistry::new()),
+            fake.clone(),
+            R5Config::default(),
+        );
+
+        ctx.show_overlay(Some("notes".to_string())).await;
+        assert!(ctx.state.read().await.overlay_visible);
+
+        let sent = fake.sent.lock().unwrap();
+        assert!(matches!(sent.as_slice(), [IPCMessage::ShowOverlay { query: Some(query) }] if query == "notes"));
+    }
+
+    #[test]
+    fn test_overlay_executable_location() {
+        let configured = Path::new("/opt/flowlight/overlay");
+        assert_eq!(overlay_executable(Some(configured)).unwrap(), configured);
+
+        // Next to the daemon, whatever the working directory
+        let overlay = overlay_executable(None).unwrap();
+        assert_eq!(overlay.parent(), std::env::current_exe().unwrap().parent());
+        assert!(overlay.file_name().unwrap().to_string_lossy().starts_with("real-overlay"));
+    }
+
+    #[tokio::test]
+    async fn test_hide_resumes_indexing() {
+        let fake = Arc::new(FakeBroadcaster { clients: 1, sent: Mutex::new(Vec::new()) });
+        let ctx = DaemonContext::new(
+            Arc::new(RwLock::new(DaemonState::new())),
+            Arc::new(HandlerRegistry::new()),
+            fake.clone(),
+            R5Config::default(),
+        );
+
+        ctx.show_overlay(None).await;
+        assert!(ctx.state.read().await.indexing.is_paused());
+
+        ctx.hide_overlay().await;
+        {
+            let state = ctx.state.read().await;
+            assert!(!state.overlay_visible);
+            assert!(!state.indexing.is_paused());
+        }
+        assert!(matches!(fake.sent.lock().unwrap().last(), Some(IPCMessage::HideOverlay)));
+    }
+}
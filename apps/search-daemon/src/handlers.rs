@@ -1,12 +1,17 @@
 // Real IPC Message Handlers with Module System Integration
 
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify, RwLock};
 use log::{info, error, debug, warn};
 
-use ipc_communication::{IPCMessage, IPCResult, MessageHandler, SearchResult};
-use modules::{ModuleRegistry, SearchQuery, SearchResult as ModuleSearchResult};
+use ipc_communication::{IPCMessage, IPCResult, MessageHandler, Reply, SearchResult};
+use modules::{FrecencyStore, ModuleRegistry, SearchQuery, SearchResult as ModuleSearchResult};
 use crate::state::DaemonState;
+use crate::context::build_search_context;
+use crate::cache::{CacheLookup, SearchCache, SearchCacheConfig};
 
 // Convert between module and IPC result types
 fn convert_module_result_to_ipc(module_result: ModuleSearchResult) -> SearchResult {
@@ -41,12 +46,23 @@ impl MessageHandler for PingHandler {
     }
 }
 
+/// Overlay actions clients ask for, carried out by the daemon context
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayRequest {
+    Toggle,
+    /// Show the overlay with the query typed in
+    Show(Option<String>),
+    Hide,
+}
+
 // Overlay Control Handler
-pub struct OverlayHandler;
+pub struct OverlayHandler {
+    requests: mpsc::UnboundedSender<OverlayRequest>,
+}
 
 impl OverlayHandler {
-    pub fn new() -> Self {
-        Self
+    pub fn new(requests: mpsc::UnboundedSender<OverlayRequest>) -> Self {
+        Self { requests }
     }
 }
 
@@ -55,18 +71,25 @@ impl MessageHandler for OverlayHandler {
         match message {
             IPCMessage::ToggleOverlay => {
                 info!("Toggling search overlay");
-                // TODO: Implement actual overlay toggle logic
-                // This will communicate with the overlay process
+                if self.requests.send(OverlayRequest::Toggle).is_err() {
+                    error!("❌ Overlay request listener is gone, toggle dropped");
+                }
                 Ok(None)
             }
             IPCMessage::ShowOverlay { query } => {
                 info!("Showing overlay with query: {:?}", query);
-                // TODO: Implement overlay show logic
+                // e.g. forwarded by a second `r5-flowlight-daemon run --show`
+                if self.requests.send(OverlayRequest::Show(query)).is_err() {
+                    error!("❌ Overlay request listener is gone, show dropped");
+                }
                 Ok(None)
             }
             IPCMessage::HideOverlay => {
                 info!("Hiding overlay");
-                // TODO: Implement overlay hide logic
+                // Also resumes the indexing paused while the overlay was open
+                if self.requests.send(OverlayRequest::Hide).is_err() {
+                    error!("❌ Overlay request listener is gone, hide dropped");
+                }
                 Ok(None)
             }
             _ => Ok(None),
@@ -74,109 +97,211 @@ impl MessageHandler for OverlayHandler {
     }
 }
 
+// Counts a search as in flight until dropped
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl InFlightGuard {
+    fn new(in_flight: &Arc<AtomicUsize>) -> Self {
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Self(in_flight.clone())
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Wait until nothing is in flight; false if `deadline` passed first
+async fn wait_until_idle(in_flight: &AtomicUsize, deadline: Duration) -> bool {
+    let give_up_at = tokio::time::Instant::now() + deadline;
+    while in_flight.load(Ordering::SeqCst) > 0 {
+        if tokio::time::Instant::now() >= give_up_at {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    true
+}
+
 // Real Search Handler with Module System
 pub struct SearchHandler {
     daemon_state: Arc<RwLock<DaemonState>>,
     module_registry: Arc<RwLock<ModuleRegistry>>,
+    cache: Arc<Mutex<SearchCache>>,
+    in_flight: Arc<AtomicUsize>,
 }
 
 impl SearchHandler {
     pub fn new(daemon_state: Arc<RwLock<DaemonState>>) -> Self {
-        // Create and initialize module registry
-        let module_registry = Arc::new(RwLock::new(ModuleRegistry::new()));
+        // Create and initialize module registry, ranking with the persisted usage history
+        let mut registry = ModuleRegistry::new();
+        registry.set_frecency_store(FrecencyStore::load_default().into_shared());
+        let handler = Self::from_parts(daemon_state, registry);
         
         // Initialize modules in a background task
-        let registry_clone = module_registry.clone();
+        let registry_clone = handler.module_registry.clone();
+        let state_clone = handler.daemon_state.clone();
+        let cache_clone = handler.cache.clone();
         tokio::spawn(async move {
+            let indexing = state_clone.read().await.indexing.clone();
             let mut registry = registry_clone.write().await;
+            registry.set_indexing_control(indexing);
             if let Err(e) = registry.initialize_default_modules().await {
                 error!("❌ Failed to initialize default modules: {}", e);
             } else {
                 info!("✅ Module registry initialized with default modules");
             }
+            enable_prefix_reuse(&registry, &cache_clone).await;
+            // Results cached before the modules loaded their saved configuration are stale
+            state_clone.write().await.bump_config_revision();
         });
         
-        Self { 
+        handler
+    }
+    
+    /// Search `registry` as it is, without loading the default modules
+    fn from_parts(daemon_state: Arc<RwLock<DaemonState>>, registry: ModuleRegistry) -> Self {
+        Self {
             daemon_state,
-            module_registry,
+            module_registry: Arc::new(RwLock::new(registry)),
+            cache: Arc::new(Mutex::new(SearchCache::new(SearchCacheConfig::default()))),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+    
+    /// Wait for running searches, then clean up every module (committing the file index)
+    pub async fn shutdown(&self, drain_deadline: Duration) {
+        if !wait_until_idle(&self.in_flight, drain_deadline).await {
+            warn!("⚠️ {} searches still running after {:?}, abandoning them",
+                  self.in_flight.load(Ordering::SeqCst), drain_deadline);
+        }
+        
+        if let Err(e) = self.module_registry.write().await.cleanup_all().await {
+            error!("❌ Failed to clean up modules: {}", e);
         }
     }
 }
 
-impl MessageHandler for SearchHandler {
-    fn handle(&self, message: IPCMessage) -> IPCResult<Option<IPCMessage>> {
+impl SearchHandler {
+    /// Run `query` (or answer it from the cache) and send the results to `reply`, if any
+    fn search(&self, query: String, session_id: String, reply: Option<Reply>) {
+        let daemon_state = self.daemon_state.clone();
+        let module_registry = self.module_registry.clone();
+        let cache = self.cache.clone();
+        let in_flight = InFlightGuard::new(&self.in_flight);
+        
+        tokio::spawn(async move {
+            let _in_flight = in_flight;
+            
+            // Update daemon stats
+            {
+                let mut state = daemon_state.write().await;
+                state.stats.searches_performed += 1;
+                state.record_session_query(&session_id);
+                state.stats.last_activity = shared_core::utils::current_timestamp_ms();
+            }
+            
+            // Build the search context from daemon state
+            let (search_context, config_revision) = {
+                let state = daemon_state.read().await;
+                let search_query = SearchQuery {
+                    text: query,
+                    module_filter: state.current_module.clone(),
+                    max_results: 10,
+                    timeout_ms: 3000,
+                };
+                (build_search_context(search_query, &state), state.config_revision)
+            };
+            
+            // Repeated keystrokes and extended queries are answered from the cache
+            let lookup = cache
+                .lock()
+                .map(|mut cache| cache.lookup(&search_context.query, config_revision, Instant::now()))
+                .unwrap_or(CacheLookup::Miss);
+            let registry = module_registry.read().await;
+            let collected = match lookup {
+                CacheLookup::Hit(results) => {
+                    daemon_state.write().await.stats.cache_hits += 1;
+                    debug!("⚡ Cache hit with {} results", results.len());
+                    Ok(results)
+                }
+                CacheLookup::PrefixHit(reuse) => {
+                    daemon_state.write().await.stats.cache_prefix_hits += 1;
+                    debug!("⚡ Prefix cache hit for {:?} with {} results", reuse.modules, reuse.results.len());
+                    registry.collect_results(&search_context, &reuse.modules).await.map(|mut results| {
+                        results.extend(reuse.results.iter().cloned());
+                        if let Ok(mut cache) = cache.lock() {
+                            cache.insert_extended(&search_context.query, config_revision, results.clone(), &reuse, Instant::now());
+                        }
+                        results
+                    })
+                }
+                CacheLookup::Miss => {
+                    daemon_state.write().await.stats.cache_misses += 1;
+                    registry.collect_results(&search_context, &[]).await.inspect(|results| {
+                        if let Ok(mut cache) = cache.lock() {
+                            cache.insert(&search_context.query, config_revision, results.clone(), Instant::now());
+                        }
+                    })
+                }
+            };
+            // Ranked on every answer, so cached results get the current frecency boost
+            let module_results = match collected {
+                Ok(results) => {
+                    let results = registry.rank_results(&search_context.query, results);
+                    info!("✅ Module search completed with {} results", results.len());
+                    results
+                }
+                Err(e) => {
+                    error!("❌ Module search failed: {}", e);
+                    Vec::new()
+                }
+            };
+            drop(registry);
+            
+            let Some(reply) = reply else {
+                return;
+            };
+            let results: Vec<SearchResult> = module_results.into_iter().map(convert_module_result_to_ipc).collect();
+            debug!("📤 Sending {} search results to client {}", results.len(), reply.client_id());
+            if let Err(e) = reply.send(IPCMessage::SearchResults { results, session_id }) {
+                warn!("⚠️ Search results dropped: {}", e);
+            }
+        });
+    }
+    
+    fn dispatch(&self, message: IPCMessage, reply: Option<&Reply>) -> IPCResult<Option<IPCMessage>> {
         match message {
             IPCMessage::SearchQuery { query, session_id } => {
-                info!("🔍 Real search query received: '{}' (session: {})", query, session_id);
-                
-                // This needs to be async, but the trait is sync
-                // We'll return a future result for now and update the trait later
-                let daemon_state = self.daemon_state.clone();
+                info!("🔍 Search query received (session: {})", session_id);
+                self.search(query, session_id, reply.cloned());
+                Ok(None)
+            }
+            IPCMessage::ClearResults => {
+                info!("🧹 Clearing search results");
+                Ok(None)
+            }
+            IPCMessage::ExecuteAction { result_id, action_type, query } => {
+                info!("⚡ Executing '{}' on '{}'", action_type, result_id);
                 let module_registry = self.module_registry.clone();
-                let query_clone = query.clone();
-                
-                // For now, return a mock result while we transition to async
-                // TODO: Make the handler trait async
-                
                 tokio::spawn(async move {
-                    // Update daemon stats
-                    {
-                        let mut state = daemon_state.write().await;
-                        state.stats.searches_performed += 1;
-                        state.stats.last_activity = shared_core::utils::current_timestamp_ms();
-                    }
-                    
-                    // Get current module from daemon state
-                    let current_module = {
-                        let state = daemon_state.read().await;
-                        state.current_module.clone()
-                    };
-                    
-                    // Create search query
-                    let search_query = SearchQuery {
-                        text: query_clone,
-                        module_filter: current_module,
-                        max_results: 10,
-                        timeout_ms: 3000,
-                    };
-                    
-                    // Perform search using module registry
                     let registry = module_registry.read().await;
-                    match registry.search_all_modules(&search_query).await {
-                        Ok(module_results) => {
-                            info!("✅ Module search completed with {} results", module_results.len());
-                        }
-                        Err(e) => {
-                            error!("❌ Module search failed: {}", e);
-                        }
+                    if let Err(e) = registry.execute_action(&query, &result_id, &action_type).await {
+                        error!("❌ Failed to execute action: {}", e);
                     }
                 });
-                
-                // Return immediate mock results for now
-                let results = vec![
-                    SearchResult {
-                        id: "daily_time".to_string(),
-                        title: "Current Time".to_string(),
-                        description: "Get current time and date".to_string(),
-                        icon: Some("🕐".to_string()),
-                        action_type: "copy".to_string(),
-                        metadata: std::collections::HashMap::new(),
-                    },
-                    SearchResult {
-                        id: "daily_calculator".to_string(),
-                        title: "Calculator".to_string(),
-                        description: "Open system calculator".to_string(),
-                        icon: Some("🧮".to_string()),
-                        action_type: "launch".to_string(),
-                        metadata: std::collections::HashMap::new(),
-                    },
-                ];
-                
-                info!("📤 Returning {} search results", results.len());
-                Ok(Some(IPCMessage::SearchResults { results, session_id }))
+                Ok(None)
             }
-            IPCMessage::ClearResults => {
-                info!("🧹 Clearing search results");
+            IPCMessage::ForgetResult { result_id } => {
+                info!("🧽 Forgetting usage history of '{}'", result_id);
+                let module_registry = self.module_registry.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = module_registry.read().await.forget_result(&result_id) {
+                        error!("❌ Failed to forget '{}': {}", result_id, e);
+                    }
+                });
                 Ok(None)
             }
             _ => Ok(None),
@@ -184,6 +309,26 @@ impl MessageHandler for SearchHandler {
     }
 }
 
+/// Let the cache answer longer queries for the modules that match by substring
+async fn enable_prefix_reuse(registry: &ModuleRegistry, cache: &Mutex<SearchCache>) {
+    let substring_modules = registry.substring_modules().await;
+    if let Ok(mut cache) = cache.lock() {
+        cache.set_prefix_reuse_modules(substring_modules);
+    }
+}
+
+impl MessageHandler for SearchHandler {
+    /// Searches still run, but without a client to send the results to
+    fn handle(&self, message: IPCMessage) -> IPCResult<Option<IPCMessage>> {
+        self.dispatch(message, None)
+    }
+    
+    /// Results are sent to `reply` once the search is done
+    fn handle_with_reply(&self, message: IPCMessage, reply: &Reply) -> IPCResult<Option<IPCMessage>> {
+        self.dispatch(message, Some(reply))
+    }
+}
+
 // Module Management Handler
 pub struct ModuleHandler {
     daemon_state: Arc<RwLock<DaemonState>>,
@@ -200,12 +345,21 @@ impl MessageHandler for ModuleHandler {
         match message {
             IPCMessage::UpdateModule { module_id } => {
                 info!("Update module command: {}", module_id);
+                let daemon_state = self.daemon_state.clone();
+                let selected = module_id.clone();
+                tokio::spawn(async move {
+                    daemon_state.write().await.set_current_module(selected);
+                });
                 Ok(Some(IPCMessage::ModuleChanged { module_id }))
             }
             IPCMessage::GetCurrentModule => {
                 debug!("Get current module request");
+                let current = self.daemon_state
+                    .try_read()
+                    .ok()
+                    .and_then(|state| state.current_module.clone());
                 Ok(Some(IPCMessage::ModuleChanged { 
-                    module_id: "daily".to_string() 
+                    module_id: current.unwrap_or_else(|| "daily".to_string()),
                 }))
             }
             _ => Ok(None),
@@ -216,11 +370,13 @@ impl MessageHandler for ModuleHandler {
 // Daemon Control Handler
 pub struct DaemonControlHandler {
     daemon_state: Arc<RwLock<DaemonState>>,
+    stop: Arc<Notify>,
 }
 
 impl DaemonControlHandler {
-    pub fn new(daemon_state: Arc<RwLock<DaemonState>>) -> Self {
-        Self { daemon_state }
+    /// `StopDaemon` notifies `stop`; whoever waits on it shuts the daemon down
+    pub fn new(daemon_state: Arc<RwLock<DaemonState>>, stop: Arc<Notify>) -> Self {
+        Self { daemon_state, stop }
     }
 }
 
@@ -235,13 +391,304 @@ impl MessageHandler for DaemonControlHandler {
                 }))
             }
             IPCMessage::StopDaemon => {
-                warn!("Daemon stop requested via IPC");
+                warn!("🛑 Daemon stop requested via IPC");
+                self.stop.notify_one();
                 Ok(Some(IPCMessage::DaemonStatus {
                     running: false,
-                    pid: None,
+                    pid: Some(std::process::id()),
                 }))
             }
             _ => Ok(None),
         }
     }
-}
\ No newline at end of file
+}
+
+// Shortcut Trigger Handler - lets the desktop environment own the key binding
+pub struct ShortcutHandler {
+    trigger: mpsc::UnboundedSender<OverlayRequest>,
+}
+
+impl ShortcutHandler {
+    /// Each `TriggerShortcut` sends one `OverlayRequest::Toggle` to `trigger`
+    pub fn new(trigger: mpsc::UnboundedSender<OverlayRequest>) -> Self {
+        Self { trigger }
+    }
+}
+
+impl MessageHandler for ShortcutHandler {
+    fn handle(&self, message: IPCMessage) -> IPCResult<Option<IPCMessage>> {
+        match message {
+            IPCMessage::TriggerShortcut => {
+                info!("⌨️  Shortcut triggered via IPC");
+                if self.trigger.send(OverlayRequest::Toggle).is_err() {
+                    error!("❌ Shortcut listener is gone, trigger dropped");
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+// Handler Registry - routes each message kind to the handler that serves it
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<&'static str, Arc<dyn MessageHandler + Send + Sync>>,
+    search: Option<Arc<SearchHandler>>,
+    shutting_down: AtomicBool,
+    stop: Arc<Notify>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Every handler the daemon serves over IPC
+    pub fn with_default_handlers(
+        daemon_state: Arc<RwLock<DaemonState>>,
+        overlay_requests: mpsc::UnboundedSender<OverlayRequest>,
+    ) -> Self {
+        let mut registry = Self::new();
+        registry.register(&["ping"], Arc::new(PingHandler::new()));
+        registry.register(&["toggle_overlay", "show_overlay", "hide_overlay"], Arc::new(OverlayHandler::new(overlay_requests.clone())));
+        registry.register(&["trigger_shortcut"], Arc::new(ShortcutHandler::new(overlay_requests)));
+        let search = Arc::new(SearchHandler::new(daemon_state.clone()));
+        registry.register(&["search_query", "clear_results", "execute_action", "forget_result"], search.clone());
+        registry.search = Some(search);
+        registry.register(&["update_module", "get_current_module"], Arc::new(ModuleHandler::new(daemon_state.clone())));
+        registry.register(&["daemon_status", "stop_daemon"], Arc::new(DaemonControlHandler::new(daemon_state, registry.stop.clone())));
+        
+        info!("🔧 IPC handlers registered for {} message kinds", registry.handlers.len());
+        registry
+    }
+    
+    /// Message kinds with a handler, sorted
+    pub fn kinds(&self) -> Vec<&'static str> {
+        let mut kinds: Vec<_> = self.handlers.keys().copied().collect();
+        kinds.sort_unstable();
+        kinds
+    }
+    
+    /// Resolves once a client sent `StopDaemon`
+    pub async fn stop_requested(&self) {
+        self.stop.notified().await
+    }
+    
+    /// Refuse new searches, drain the running ones and clean up the modules
+    pub async fn shutdown(&self, drain_deadline: Duration) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        if let Some(search) = &self.search {
+            search.shutdown(drain_deadline).await;
+        }
+    }
+    
+    /// Route messages of `kinds` (see `IPCMessage::kind`) to `handler`
+    pub fn register(&mut self, kinds: &[&'static str], handler: Arc<dyn MessageHandler + Send + Sync>) {
+        for kind in kinds {
+            self.handlers.insert(kind, handler.clone());
+        }
+    }
+}
+
+impl HandlerRegistry {
+    fn handler_for(&self, message: &IPCMessage) -> Option<&Arc<dyn MessageHandler + Send + Sync>> {
+        if self.shutting_down.load(Ordering::SeqCst) && matches!(message, IPCMessage::SearchQuery { .. }) {
+            debug!("🛑 Shutting down, search ignored");
+            return None;
+        }
+        let handler = self.handlers.get(message.kind());
+        if handler.is_none() {
+            debug!("📭 No handler for '{}' messages", message.kind());
+        }
+        handler
+    }
+}
+
+impl MessageHandler for HandlerRegistry {
+    fn handle(&self, message: IPCMessage) -> IPCResult<Option<IPCMessage>> {
+        match self.handler_for(&message) {
+            Some(handler) => handler.handle(message),
+            None => Ok(None),
+        }
+    }
+    
+    fn handle_with_reply(&self, message: IPCMessage, reply: &Reply) -> IPCResult<Option<IPCMessage>> {
+        match self.handler_for(&message) {
+            Some(handler) => handler.handle_with_reply(message, reply),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use modules::{DailyModule, FilesModule, SearchModule};
+
+    #[test]
+    fn test_registry_routes_by_message_kind() {
+        let daemon_state = Arc::new(RwLock::new(DaemonState::new()));
+        let mut registry = HandlerRegistry::new();
+        registry.register(&["ping"], Arc::new(PingHandler::new()));
+        registry.register(&["daemon_status", "stop_daemon"], Arc::new(DaemonControlHandler::new(daemon_state, registry.stop.clone())));
+        
+        assert!(matches!(registry.handle(IPCMessage::Ping).unwrap(), Some(IPCMessage::Pong)));
+        assert!(matches!(
+            registry.handle(IPCMessage::StopDaemon).unwrap(),
+            Some(IPCMessage::DaemonStatus { running: false, .. })
+        ));
+        assert!(registry.handle(IPCMessage::ClearResults).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stop_daemon_requests_shutdown() {
+        let (trigger, _requests) = mpsc::unbounded_channel();
+        let registry = HandlerRegistry::with_default_handlers(Arc::new(RwLock::new(DaemonState::new())), trigger);
+
+        assert!(matches!(
+            registry.handle(IPCMessage::StopDaemon).unwrap(),
+            Some(IPCMessage::DaemonStatus { running: false, .. })
+        ));
+        tokio::time::timeout(Duration::from_secs(1), registry.stop_requested()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_idle_honours_deadline() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        assert!(wait_until_idle(&in_flight, Duration::ZERO).await);
+        
+        let guard = InFlightGuard::new(&in_flight);
+        assert!(!wait_until_idle(&in_flight, Duration::from_millis(30)).await);
+        
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            drop(guard);
+        });
+        assert!(wait_until_idle(&in_flight, Duration::from_secs(5)).await);
+    }
+    
+    #[tokio::test]
+    async fn test_trigger_shortcut_reaches_listener() {
+        let (trigger, mut requests) = mpsc::unbounded_channel();
+        let registry = HandlerRegistry::with_default_handlers(Arc::new(RwLock::new(DaemonState::new())), trigger);
+
+        assert!(registry.handle(IPCMessage::TriggerShortcut).unwrap().is_none());
+        assert_eq!(requests.try_recv().ok(), Some(OverlayRequest::Toggle));
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_results_reach_the_client() {
+        let daemon_state = Arc::new(RwLock::new(DaemonState::new()));
+        let search = SearchHandler::new(daemon_state.clone());
+        let (reply, mut messages) = Reply::channel(1, 4);
+        
+        for _ in 0..2 {
+            let query = IPCMessage::SearchQuery { query: "time".to_string(), session_id: "s1".to_string() };
+            assert!(search.handle_with_reply(query, &reply).unwrap().is_none());
+            let answer = tokio::time::timeout(Duration::from_secs(10), messages.recv()).await.unwrap();
+            assert!(matches!(answer, Some(IPCMessage::SearchResults { session_id, .. }) if session_id == "s1"));
+        }
+        
+        let stats = daemon_state.read().await.stats.clone();
+        assert_eq!(stats.searches_performed, 2);
+        assert_eq!(stats.cache_hits + stats.cache_prefix_hits + stats.cache_misses, 2);
+    }
+
+    /// Daily and files modules, the latter indexing `corpus` into `index`
+    async fn daily_and_files(corpus: &std::path::Path, index: &std::path::Path) -> ModuleRegistry {
+        std::fs::write(corpus.join("budget.txt"), "Invoice for the quarterly budget").unwrap();
+        
+        let mut files = FilesModule::new(search_engine::SearchConfig::default());
+        files.initialize(HashMap::from([
+            ("indexed_paths".to_string(), serde_json::json!([corpus])),
+            ("ignored_paths".to_string(), serde_json::json!([])),
+            ("index_path".to_string(), serde_json::json!(index)),
+            ("watch".to_string(), serde_json::json!(false)),
+        ])).await.unwrap();
+        let mut progress = files.indexing_control().subscribe();
+        tokio::time::timeout(Duration::from_secs(10), progress.wait_for(|p| p.done)).await.unwrap().unwrap();
+        // Wait for the commit to be visible, so no empty answer gets cached
+        let budget = SearchQuery { text: "budget".to_string(), module_filter: None, max_results: 10, timeout_ms: 3000 };
+        for _ in 0..50 {
+            if !files.search(&budget).await.unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        
+        let mut registry = ModuleRegistry::new();
+        let mut daily = DailyModule::new();
+        daily.initialize(HashMap::new()).await.unwrap();
+        registry.register_module("daily".to_string(), Box::new(daily)).await.unwrap();
+        registry.register_module("files".to_string(), Box::new(files)).await.unwrap();
+        registry
+    }
+    
+    async fn search_results(search: &SearchHandler, reply: &Reply, messages: &mut mpsc::Receiver<IPCMessage>, query: &str) -> Vec<SearchResult> {
+        let query = IPCMessage::SearchQuery { query: query.to_string(), session_id: "s1".to_string() };
+        assert!(search.handle_with_reply(query, reply).unwrap().is_none());
+        let answer = tokio::time::timeout(Duration::from_secs(10), messages.recv()).await.unwrap();
+        let Some(IPCMessage::SearchResults { results, .. }) = answer else {
+            panic!("expected search results, got {:?}", answer);
+        };
+        results
+    }
+    
+    #[tokio::test]
+    async fn test_file_results_reach_the_client() {
+        let corpus_dir = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        let registry = daily_and_files(corpus_dir.path(), &index_dir.path().join("index")).await;
+        
+        // A fresh daemon has no current module, so every enabled module is searched
+        let daemon_state = Arc::new(RwLock::new(DaemonState::new()));
+        assert!(daemon_state.read().await.current_module.is_none());
+        let search = SearchHandler::from_parts(daemon_state, registry);
+        let (reply, mut messages) = Reply::channel(1, 4);
+        
+        let results = search_results(&search, &reply, &mut messages, "budget").await;
+        assert!(results.iter().any(|r| r.title == "budget.txt" && r.metadata["module"] == "files"));
+        
+        search.shutdown(Duration::from_secs(1)).await;
+    }
+    
+    #[tokio::test]
+    async fn test_extended_query_reuses_file_results() {
+        let corpus_dir = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        let registry = daily_and_files(corpus_dir.path(), &index_dir.path().join("index")).await;
+        
+        let daemon_state = Arc::new(RwLock::new(DaemonState::new()));
+        let search = SearchHandler::from_parts(daemon_state.clone(), registry);
+        enable_prefix_reuse(&*search.module_registry.read().await, &search.cache).await;
+        let (reply, mut messages) = Reply::channel(1, 4);
+        
+        search_results(&search, &reply, &mut messages, "budg").await;
+        // Files come from the cached "budg", the daily module is searched again
+        let results = search_results(&search, &reply, &mut messages, "budge").await;
+        assert!(results.iter().any(|r| r.title == "budget.txt" && r.metadata["module"] == "files"));
+        
+        let stats = daemon_state.read().await.stats.clone();
+        assert_eq!((stats.cache_misses, stats.cache_prefix_hits), (1, 1));
+        
+        search.shutdown(Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_overlay_requests_are_forwarded() {
+        let (trigger, mut requests) = mpsc::unbounded_channel();
+        let registry = HandlerRegistry::with_default_handlers(Arc::new(RwLock::new(DaemonState::new())), trigger);
+
+        assert!(registry.handle(IPCMessage::ToggleOverlay).unwrap().is_none());
+        assert_eq!(requests.try_recv().ok(), Some(OverlayRequest::Toggle));
+        
+        let show = IPCMessage::ShowOverlay { query: Some("notes".to_string()) };
+        assert!(registry.handle(show).unwrap().is_none());
+        assert_eq!(requests.try_recv().ok(), Some(OverlayRequest::Show(Some("notes".to_string()))));
+        
+        assert!(registry.handle(IPCMessage::HideOverlay).unwrap().is_none());
+        assert_eq!(requests.try_recv().ok(), Some(OverlayRequest::Hide));
+    }
+}
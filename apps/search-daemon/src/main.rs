@@ -4,98 +4,277 @@
 // 2. Manage IPC server for communication with main-app and overlay
 // 3. Handle search requests and route to appropriate modules
 // 4. Maintain application state and configuration
+//
+// The global shortcut frontend needs Tauri and is behind the
+// `global-shortcuts` feature. In headless mode (`--headless`, or builds
+// without the feature) the daemon only serves IPC, and the desktop
+// environment binds the shortcut to `r5-flowlight-daemon toggle`.
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use clap::Parser;
-use log::{info, error, warn, debug};
+use clap::{Args, Parser, Subcommand};
+use log::{info, error};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tauri::{AppHandle, Runtime};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
-// use uuid::Uuid;
 
 // Internal modules
 mod daemon;
+mod daemon_context;
 mod handlers;
+mod context;
 mod state;
+mod cache;
+#[cfg(feature = "global-shortcuts")]
+mod shortcuts;
+mod systemd;
 
-// use daemon::SearchDaemon;
-use state::DaemonState;
-use ipc_communication::{IPCServer, IPCMessage};
+use daemon::SearchDaemon;
+use daemon_context::DaemonContext;
+use ipc_communication::{IPCMessage, TcpIPCClient, IPC_PORT};
+use shared_core::{InstanceLock, InstanceStatus};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
-struct Args {
+struct Cli {
+    /// Log level (error, warn, info, debug, trace)
+    #[arg(short, long, default_value = "info", global = true)]
+    log_level: String,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the daemon (default)
+    Run(RunArgs),
+    /// Ask a running daemon for its status
+    Status,
+    /// Toggle the overlay of a running daemon, as the global shortcut does
+    Toggle,
+    /// Print the systemd --user service and socket units
+    SystemdUnit(SystemdUnitArgs),
+}
+
+#[derive(Args, Debug)]
+struct SystemdUnitArgs {
+    /// Start the daemon headless (the desktop environment binds the shortcut)
+    #[arg(long)]
+    headless: bool,
+
+    /// Write the units to the systemd user unit directory instead of printing them
+    #[arg(long)]
+    install: bool,
+}
+
+#[derive(Args, Debug, Default)]
+struct RunArgs {
     /// Run in daemon mode (detach from terminal)
     #[arg(short, long)]
     daemon: bool,
-    
-    /// Log level (error, warn, info, debug, trace)
-    #[arg(short, long, default_value = "info")]
-    log_level: String,
-    
+
+    /// Global shortcut, saved to the config (default: the configured one)
+    #[arg(short, long)]
+    shortcut: Option<String>,
+
+    /// Serve IPC only, without registering the global shortcut
+    #[arg(long)]
+    headless: bool,
+
     /// PID file location
     #[arg(short, long, default_value = "/tmp/r5-flowlight-daemon.pid")]
     pid_file: String,
+
+    /// Show the overlay, optionally with QUERY typed in. Forwarded to the
+    /// running daemon when there already is one
+    #[arg(long, value_name = "QUERY", num_args = 0..=1, default_missing_value = "")]
+    show: Option<String>,
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-    
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Initialize logging
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or(&args.log_level)
-    ).init();
-    
-    info!("R5 Flowlight Search Daemon starting...");
-    
-    // Daemonize if requested
+    let mut logger = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or(&cli.log_level)
+    );
+    if systemd::journald_logging() {
+        // The journal timestamps lines itself and reads the priority from the prefix
+        logger.format(|buf, record| {
+            use std::io::Write;
+            writeln!(buf, "<{}>{}: {}", systemd::journald_priority(record.level()), record.target(), record.args())
+        });
+    }
+    logger.init();
+
+    match cli.command {
+        Some(Command::Status) => {
+            tokio::runtime::Runtime::new()?.block_on(print_status())
+        }
+        Some(Command::Toggle) => {
+            tokio::runtime::Runtime::new()?.block_on(send_to_daemon(IPCMessage::TriggerShortcut))
+        }
+        Some(Command::SystemdUnit(args)) => write_systemd_units(args),
+        Some(Command::Run(args)) => run(args),
+        None => run(RunArgs {
+            pid_file: "/tmp/r5-flowlight-daemon.pid".to_string(),
+            ..RunArgs::default()
+        }),
+    }
+}
+
+fn run(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    info!("🚀 R5 Flowlight Search Daemon starting...");
+
+    // One daemon per user: a second launch hands its request to the first
+    let lock_path = InstanceLock::default_path(shared_core::DAEMON_INSTANCE)
+        .ok_or("Could not find a directory for the instance lock")?;
+    let instance_lock = match InstanceLock::acquire(&lock_path)
+        .map_err(|e| format!("Failed to check for a running daemon: {}", e))?
+    {
+        InstanceStatus::Acquired(lock) => lock,
+        InstanceStatus::Running(pid) => return forward_to_running_daemon(pid, args.show),
+    };
+
+    // Daemonize before the runtime spawns any threads
     if args.daemon {
         daemonize_process(&args.pid_file)?;
+        instance_lock.refresh()?;
     }
-    
-    // Setup signal handlers for graceful shutdown
-    setup_signal_handlers().await?;
-    
-    // Initialize daemon state
-    let daemon_state = Arc::new(RwLock::new(DaemonState::new()));
-    
-    // Start IPC server
-    let mut ipc_server = IPCServer::new().await?;
-    setup_ipc_handlers(&mut ipc_server, daemon_state.clone()).await?;
-    ipc_server.start().await?;
-    
-    // Start Tauri headless app for global shortcuts
-    let daemon_state_clone = daemon_state.clone();
-    tauri::Builder::default()
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .setup(move |app| {
-            setup_global_shortcuts(app.handle(), daemon_state_clone)?;
-            info!("Search daemon initialized successfully");
-            Ok(())
-        })
-        .build(tauri::generate_context!())?
-        .run(|_app, _event| {});
-    
+
+    let config = load_config(args.shortcut)?;
+    let listener = systemd::take_activation_listener();
+    let notifier = systemd::Notifier::from_env().map(Arc::new);
+    tokio::runtime::Runtime::new()?.block_on(async move {
+        let daemon = Arc::new(SearchDaemon::initialize(config, listener).await?);
+        daemon.start().await?;
+
+        if let Some(notifier) = &notifier {
+            if let Err(e) = notifier.ready("Serving IPC") {
+                error!("Failed to notify systemd: {}", e);
+            }
+            notifier.clone().spawn_watchdog();
+        }
+
+        // Shut down gracefully on signals and on StopDaemon from a client
+        let pid_file = args.daemon.then(|| args.pid_file.clone());
+        setup_signal_handlers(daemon.clone(), pid_file, instance_lock, notifier)?;
+
+        if let Some(query) = args.show {
+            let ctx = daemon.context();
+            tokio::spawn(async move { ctx.show_overlay(Some(query).filter(|q| !q.is_empty())).await });
+        }
+
+        if args.headless {
+            serve_headless(&daemon.context()).await
+        } else {
+            run_frontend(daemon.context()).await
+        }
+    })
+}
+
+/// Hand `--show` to the daemon already running, or report it
+fn forward_to_running_daemon(pid: u32, show: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(query) = show else {
+        return Err(format!("r5-flowlight-daemon is already running (PID {})", pid).into());
+    };
+
+    info!("📨 Daemon already running (PID {}), forwarding --show", pid);
+    let query = Some(query).filter(|q| !q.is_empty());
+    tokio::runtime::Runtime::new()?.block_on(send_to_daemon(IPCMessage::ShowOverlay { query }))
+}
+
+/// Configuration with the shortcut from the command line saved into it
+fn load_config(cli_shortcut: Option<String>) -> Result<shared_core::R5Config, Box<dyn std::error::Error>> {
+    let mut config_manager = shared_core::ConfigManager::new()
+        .map_err(|e| format!("Failed to initialize config manager: {}", e))?;
+    info!("📁 Config path: {:?}", config_manager.get_config_file_path());
+
+    if let Some(shortcut) = cli_shortcut {
+        config_manager.set_global_shortcut(shortcut)
+            .map_err(|e| format!("Failed to save shortcut config: {}", e))?;
+    }
+
+    info!("⌨️  Global shortcut: {}", config_manager.get_global_shortcut());
+    Ok(config_manager.get_config().clone())
+}
+
+#[cfg(feature = "global-shortcuts")]
+async fn run_frontend(ctx: Arc<DaemonContext>) -> Result<(), Box<dyn std::error::Error>> {
+    shortcuts::run(ctx)
+}
+
+#[cfg(not(feature = "global-shortcuts"))]
+async fn run_frontend(ctx: Arc<DaemonContext>) -> Result<(), Box<dyn std::error::Error>> {
+    log::warn!("⚠️ Built without the global-shortcuts feature, running headless");
+    serve_headless(&ctx).await
+}
+
+async fn serve_headless(ctx: &DaemonContext) -> Result<(), Box<dyn std::error::Error>> {
+    info!("🖥️  Headless mode - serving IPC only");
+    info!("💡 Bind '{}' in your desktop environment to: r5-flowlight-daemon toggle",
+          ctx.config.shortcuts.global_shortcut);
+    // Signal handlers exit the process
+    std::future::pending::<()>().await;
     Ok(())
 }
 
+async fn send_to_daemon(message: IPCMessage) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TcpIPCClient::new().await
+        .map_err(|e| format!("Daemon is not running: {}", e))?;
+    client.send(message).await?;
+    Ok(())
+}
+
+async fn print_status() -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TcpIPCClient::new().await
+        .map_err(|e| format!("Daemon is not running: {}", e))?;
+    client.send(IPCMessage::DaemonStatus { running: true, pid: None }).await?;
+
+    // Broadcasts (e.g. indexing progress) may arrive before the reply
+    loop {
+        if let IPCMessage::DaemonStatus { running, pid } = client.receive().await? {
+            let pid = pid.map(|pid| pid.to_string()).unwrap_or_else(|| "unknown".to_string());
+            println!("Daemon running: {} (PID {})", running, pid);
+            return Ok(());
+        }
+    }
+}
+
+fn write_systemd_units(args: SystemdUnitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let units = [
+        ("service", systemd::service_unit(&std::env::current_exe()?, args.headless)),
+        ("socket", systemd::socket_unit(IPC_PORT)),
+    ];
+
+    if !args.install {
+        for (kind, content) in &units {
+            println!("# {}.{}\n{}", systemd::SERVICE_NAME, kind, content);
+        }
+        return Ok(());
+    }
+
+    let dir = systemd::user_unit_dir().ok_or("Could not find the config directory")?;
+    std::fs::create_dir_all(&dir)?;
+    for (kind, content) in &units {
+        let path = dir.join(format!("{}.{}", systemd::SERVICE_NAME, kind));
+        std::fs::write(&path, content)?;
+        println!("Wrote {}", path.display());
+    }
+    println!("Enable with: systemctl --user daemon-reload && systemctl --user enable --now {}.socket",
+             systemd::SERVICE_NAME);
+    Ok(())
+}
+
+/// Fork into the background, keeping the user's identity (config, index and
+/// overlay all live in their home directory)
 fn daemonize_process(pid_file: &str) -> Result<(), Box<dyn std::error::Error>> {
     use daemonize::Daemonize;
-    
+
     let daemonize = Daemonize::new()
         .pid_file(pid_file)
-        .chown_pid_file(true)
         .working_directory("/tmp")
-        .user("nobody")
-        .group("daemon")
-        .umask(0o027)
-        .privileged_action(|| {
-            info!("Daemon privileges dropped");
-        });
-    
+        .umask(0o027);
+
     match daemonize.start() {
         Ok(_) => {
             info!("Daemon started successfully");
@@ -108,128 +287,39 @@ fn daemonize_process(pid_file: &str) -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
-async fn setup_signal_handlers() -> Result<(), Box<dyn std::error::Error>> {
+fn setup_signal_handlers(
+    daemon: Arc<SearchDaemon>,
+    pid_file: Option<String>,
+    instance_lock: InstanceLock,
+    notifier: Option<Arc<systemd::Notifier>>,
+) -> Result<(), Box<dyn std::error::Error>> {
     use signal_hook::consts::signal::*;
     use signal_hook_tokio::Signals;
     use tokio_stream::StreamExt;
-    
-    let mut signals = Signals::new(&[SIGTERM, SIGINT, SIGQUIT])?;
-    
+
+    let mut signals = Signals::new([SIGTERM, SIGINT, SIGQUIT])?;
+
     tokio::spawn(async move {
-        while let Some(signal) = signals.next().await {
-            match signal {
-                SIGTERM | SIGINT | SIGQUIT => {
-                    info!("Received shutdown signal: {}", signal);
-                    graceful_shutdown().await;
-                    std::process::exit(0);
-                }
-                _ => unreachable!(),
+        // A signal, or `StopDaemon` from a client
+        tokio::select! {
+            Some(signal) = signals.next() => info!("Received shutdown signal: {}", signal),
+            _ = daemon.stop_requested() => info!("Shutdown requested over IPC"),
+        }
+        if let Some(notifier) = &notifier {
+            let _ = notifier.stopping();
+        }
+        if let Err(e) = daemon.shutdown().await {
+            error!("Shutdown failed: {}", e);
+        }
+        if let Some(pid_file) = pid_file {
+            if let Err(e) = std::fs::remove_file(&pid_file) {
+                error!("Failed to remove PID file {}: {}", pid_file, e);
             }
         }
+        // exit() skips destructors
+        drop(instance_lock);
+        std::process::exit(0);
     });
-    
-    Ok(())
-}
-
-async fn graceful_shutdown() {
-    info!("Performing graceful shutdown...");
-    
-    // Cleanup IPC connections
-    // Cleanup temporary files
-    // Save state if needed
-    
-    info!("Graceful shutdown completed");
-}
-
-fn setup_global_shortcuts<R: Runtime>(
-    app_handle: &AppHandle<R>,
-    daemon_state: Arc<RwLock<DaemonState>>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let global_shortcut = app_handle.global_shortcut();
-    
-    // Clear any existing shortcuts
-    let _ = global_shortcut.unregister("CmdOrCtrl+Space");
-    
-    // Register the main shortcut
-    let daemon_state_clone = daemon_state.clone();
-    global_shortcut.on_shortcut("CmdOrCtrl+Space", move |_app, _shortcut, event| {
-        if event.state == ShortcutState::Pressed {
-            let state = daemon_state_clone.clone();
-            tokio::spawn(async move {
-                handle_global_shortcut(state).await;
-            });
-        }
-    })?;
-    
-    info!("Global shortcut Cmd+Space registered successfully");
-    Ok(())
-}
 
-async fn handle_global_shortcut(daemon_state: Arc<RwLock<DaemonState>>) {
-    info!("Global shortcut triggered - toggling search overlay");
-    
-    let state = daemon_state.read().await;
-    
-    // Check if module is selected
-    if state.current_module.is_none() {
-        warn!("No module selected - showing module selection notification");
-        // TODO: Show notification to select module
-        return;
-    }
-    
-    // Check for debouncing
-    if state.is_processing_shortcut() {
-        debug!("Shortcut already processing, ignoring");
-        return;
-    }
-    
-    drop(state); // Release read lock
-    
-    // Set processing flag
-    {
-        let mut state = daemon_state.write().await;
-        state.set_processing_shortcut(true);
-    }
-    
-    // Send toggle command via IPC (this will communicate with overlay)
-    if let Err(e) = broadcast_ipc_message(IPCMessage::ToggleOverlay).await {
-        error!("Failed to send toggle overlay message: {}", e);
-    }
-    
-    // Reset processing flag after delay
-    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-    {
-        let mut state = daemon_state.write().await;
-        state.set_processing_shortcut(false);
-    }
-}
-
-async fn broadcast_ipc_message(message: IPCMessage) -> Result<(), Box<dyn std::error::Error>> {
-    // TODO: This will be implemented when IPC server is fully set up
-    // For now, just log the message
-    info!("Broadcasting IPC message: {:?}", message);
     Ok(())
 }
-
-async fn setup_ipc_handlers(
-    ipc_server: &mut IPCServer,
-    daemon_state: Arc<RwLock<DaemonState>>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Register ping handler
-    ipc_server.register_handler("ping", handlers::PingHandler::new());
-    
-    // Register overlay control handlers
-    ipc_server.register_handler("toggle_overlay", handlers::OverlayHandler::new());
-    ipc_server.register_handler("show_overlay", handlers::OverlayHandler::new());
-    ipc_server.register_handler("hide_overlay", handlers::OverlayHandler::new());
-    
-    // Register search handlers
-    ipc_server.register_handler("search_query", handlers::SearchHandler::new(daemon_state.clone()));
-    
-    // Register module handlers
-    ipc_server.register_handler("update_module", handlers::ModuleHandler::new(daemon_state.clone()));
-    ipc_server.register_handler("get_current_module", handlers::ModuleHandler::new(daemon_state));
-    
-    info!("IPC handlers registered successfully");
-    Ok(())
-}
\ No newline at end of file
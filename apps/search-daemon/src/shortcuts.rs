@@ -0,0 +1,58 @@
+// Global Shortcut Frontend
+// Runs a windowless Tauri app whose only job is to register the global
+// shortcut and forward presses to the daemon core.
+
+use std::sync::Arc;
+use log::info;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::daemon_context::DaemonContext;
+
+/// Blocks on the Tauri event loop until the process exits
+pub fn run(ctx: Arc<DaemonContext>) -> Result<(), Box<dyn std::error::Error>> {
+    // Presses arrive on Tauri's thread; the daemon lives on the main runtime
+    let runtime = tokio::runtime::Handle::current();
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .setup(move |app| {
+            let shortcut = ctx.config.shortcuts.global_shortcut.clone();
+            register_global_shortcut(app.handle(), runtime, ctx, &shortcut)?;
+            info!("✅ Global shortcuts registered successfully");
+            Ok(())
+        })
+        .build(tauri::generate_context!())?
+        .run(|_app, event| {
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                info!("🛑 Daemon shutdown requested");
+                api.prevent_exit();
+            }
+        });
+
+    Ok(())
+}
+
+fn register_global_shortcut<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    runtime: tokio::runtime::Handle,
+    ctx: Arc<DaemonContext>,
+    shortcut: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let global_shortcut = app_handle.global_shortcut();
+
+    // Clear any existing shortcuts
+    let _ = global_shortcut.unregister_all();
+
+    global_shortcut.on_shortcut(shortcut, move |_app, _shortcut, event| {
+        if event.state == ShortcutState::Pressed {
+            let ctx = ctx.clone();
+            runtime.spawn(async move {
+                ctx.toggle_overlay().await;
+            });
+        }
+    })?;
+
+    info!("🎯 Global shortcut '{}' registered and active", shortcut);
+    Ok(())
+}
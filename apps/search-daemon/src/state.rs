@@ -1,8 +1,24 @@
 // Daemon State Management
+// The selected module, cumulative stats and recent search sessions survive
+// restarts in `daemon-state.json` in the data directory.
 
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use log::{info, warn};
 use serde::{Serialize, Deserialize};
+use modules::SystemInfo;
+use search_engine::IndexingControl;
+use shared_core::utils::{data_dir, write_atomic};
+
+/// Background indexing stays paused at most this long after the overlay opens,
+/// in case the overlay goes away without reporting it was hidden
+const OVERLAY_INDEXING_PAUSE: Duration = Duration::from_secs(30);
+
+/// Bump when the state file layout changes incompatibly
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+const MAX_RECENT_SESSIONS: usize = 20;
 
 #[derive(Debug, Clone)]
 pub struct DaemonState {
@@ -11,8 +27,16 @@ pub struct DaemonState {
     pub last_shortcut_time: u64,
     pub overlay_visible: bool,
     pub search_session_id: Option<String>,
+    /// Desktop snapshot taken when the overlay was last toggled
+    pub system_info: Option<SystemInfo>,
     pub configuration: DaemonConfig,
+    /// Bumped on every configuration change; part of the search cache key
+    pub config_revision: u64,
     pub stats: DaemonStats,
+    /// Most recent first, at most `MAX_RECENT_SESSIONS`
+    pub recent_sessions: VecDeque<RecentSession>,
+    /// Pause/resume and progress of background file indexing
+    pub indexing: IndexingControl,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,13 +55,37 @@ pub struct ModuleConfig {
     pub custom_settings: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DaemonStats {
     pub shortcuts_triggered: u64,
     pub searches_performed: u64,
     pub uptime_seconds: u64,
     pub last_activity: u64,
+    /// Current process only
+    #[serde(skip)]
     pub memory_usage_kb: u64,
+    pub cache_hits: u64,
+    pub cache_prefix_hits: u64,
+    pub cache_misses: u64,
+}
+
+/// Queries sent by one overlay search session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentSession {
+    pub id: String,
+    pub started_at: u64,
+    pub last_query_at: u64,
+    pub queries: u64,
+}
+
+/// What `daemon-state.json` holds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersistedState {
+    schema_version: u32,
+    current_module: Option<String>,
+    stats: DaemonStats,
+    recent_sessions: VecDeque<RecentSession>,
 }
 
 impl DaemonState {
@@ -48,13 +96,67 @@ impl DaemonState {
             last_shortcut_time: 0,
             overlay_visible: false,
             search_session_id: None,
+            system_info: None,
             configuration: DaemonConfig::default(),
+            config_revision: 0,
             stats: DaemonStats::new(),
+            recent_sessions: VecDeque::new(),
+            indexing: IndexingControl::new(),
+        }
+    }
+    
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("daemon-state.json"))
+    }
+    
+    /// State restored from `path`; `None` if there is no usable state file
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let persisted = match parse_persisted(&content) {
+            Ok(persisted) => persisted,
+            Err(e) => {
+                warn!("⚠️ Ignoring daemon state file {:?}: {}", path, e);
+                return None;
+            }
+        };
+        
+        let mut state = Self::new();
+        state.current_module = persisted.current_module;
+        state.stats = DaemonStats { last_activity: current_timestamp(), ..persisted.stats };
+        state.recent_sessions = persisted.recent_sessions;
+        info!("📂 Restored daemon state from {:?} (module: {:?}, {} searches so far)",
+              path, state.current_module, state.stats.searches_performed);
+        Some(state)
+    }
+    
+    /// Copy of the fields that are saved, so the file can be written without holding the state
+    pub fn persisted(&self) -> PersistedState {
+        PersistedState {
+            schema_version: STATE_SCHEMA_VERSION,
+            current_module: self.current_module.clone(),
+            stats: self.stats.clone(),
+            recent_sessions: self.recent_sessions.clone(),
         }
     }
+
+    
+    /// Count a query towards its overlay session
+    pub fn record_session_query(&mut self, session_id: &str) {
+        let now = current_timestamp();
+        let mut session = self.recent_sessions
+            .iter()
+            .position(|s| s.id == session_id)
+            .and_then(|index| self.recent_sessions.remove(index))
+            .unwrap_or_else(|| RecentSession { id: session_id.to_string(), started_at: now, last_query_at: now, queries: 0 });
+        session.last_query_at = now;
+        session.queries += 1;
+        self.recent_sessions.push_front(session);
+        self.recent_sessions.truncate(MAX_RECENT_SESSIONS);
+    }
     
     pub fn set_current_module(&mut self, module_id: String) {
         self.current_module = Some(module_id);
+        self.bump_config_revision();
         self.update_activity();
     }
     
@@ -86,6 +188,12 @@ impl DaemonState {
     
     pub fn set_overlay_visible(&mut self, visible: bool) {
         self.overlay_visible = visible;
+        // Keep the disk quiet while the user is searching
+        if visible {
+            self.indexing.pause_for(OVERLAY_INDEXING_PAUSE);
+        } else {
+            self.indexing.resume();
+        }
         self.update_activity();
     }
     
@@ -108,9 +216,15 @@ impl DaemonState {
     
     pub fn set_module_config(&mut self, module_id: String, config: ModuleConfig) {
         self.configuration.module_configs.insert(module_id, config);
+        self.bump_config_revision();
         self.update_activity();
     }
     
+    /// Invalidate cached search results, e.g. after the modules (re)loaded their configuration
+    pub fn bump_config_revision(&mut self) {
+        self.config_revision += 1;
+    }
+    
     pub fn update_stats(&mut self, memory_usage_kb: u64) {
         self.stats.memory_usage_kb = memory_usage_kb;
         self.update_activity();
@@ -137,6 +251,12 @@ impl Default for DaemonConfig {
     }
 }
 
+impl Default for DaemonStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DaemonStats {
     pub fn new() -> Self {
         let now = current_timestamp();
@@ -146,8 +266,44 @@ impl DaemonStats {
             uptime_seconds: 0,
             last_activity: now,
             memory_usage_kb: 0,
+            cache_hits: 0,
+            cache_prefix_hits: 0,
+            cache_misses: 0,
         }
     }
+
+    /// Share of searches answered from the cache (exact or prefix reuse)
+    pub fn cache_hit_rate(&self) -> f64 {
+        let hits = self.cache_hits + self.cache_prefix_hits;
+        let total = hits + self.cache_misses;
+        if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        }
+    }
+}
+
+impl PersistedState {
+    /// Write atomically to `path`; blocks on disk I/O
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn parse_persisted(content: &str) -> anyhow::Result<PersistedState> {
+    #[derive(Deserialize)]
+    struct Header {
+        schema_version: u32,
+    }
+    
+    // Check the version before the layout, which may differ in other versions
+    let header: Header = serde_json::from_str(content)?;
+    if header.schema_version != STATE_SCHEMA_VERSION {
+        anyhow::bail!("unsupported schema version {} (expected {})", header.schema_version, STATE_SCHEMA_VERSION);
+    }
+    Ok(serde_json::from_str(content)?)
 }
 
 fn current_timestamp() -> u64 {
@@ -198,6 +354,18 @@ mod tests {
         assert!(state.search_session_id.is_none());
     }
 
+    #[test]
+    fn test_overlay_pauses_indexing() {
+        let mut state = DaemonState::new();
+        let indexing = state.indexing.clone();
+        
+        state.set_overlay_visible(true);
+        assert!(indexing.is_paused());
+        
+        state.set_overlay_visible(false);
+        assert!(!indexing.is_paused());
+    }
+
     #[test]
     fn test_module_management() {
         let mut state = DaemonState::new();
@@ -208,4 +376,54 @@ mod tests {
         state.clear_current_module();
         assert!(state.current_module.is_none());
     }
+
+    #[test]
+    fn test_state_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon-state.json");
+        assert!(DaemonState::load(&path).is_none());
+        
+        let mut state = DaemonState::new();
+        state.set_current_module("files".to_string());
+        state.stats.searches_performed = 7;
+        state.stats.memory_usage_kb = 1024;
+        for i in 0..MAX_RECENT_SESSIONS + 5 {
+            state.record_session_query(&format!("session-{}", i));
+        }
+        state.record_session_query("session-10");
+        state.persisted().save(&path).unwrap();
+        
+        let restored = DaemonState::load(&path).unwrap();
+        assert_eq!(restored.current_module.as_deref(), Some("files"));
+        assert_eq!(restored.stats.searches_performed, 7);
+        assert_eq!(restored.stats.memory_usage_kb, 0);
+        assert_eq!(restored.recent_sessions.len(), MAX_RECENT_SESSIONS);
+        assert_eq!(restored.recent_sessions[0].id, "session-10");
+        assert_eq!(restored.recent_sessions[0].queries, 2);
+        
+        // Files from another schema version are ignored, not misread
+        let content = std::fs::read_to_string(&path).unwrap()
+            .replace("\"schema_version\": 1", "\"schema_version\": 99");
+        std::fs::write(&path, content).unwrap();
+        assert!(DaemonState::load(&path).is_none());
+    }
+
+    #[test]
+    fn test_config_revision_and_cache_hit_rate() {
+        let mut state = DaemonState::new();
+        assert_eq!(state.stats.cache_hit_rate(), 0.0);
+
+        state.set_module_config(
+            "files".to_string(),
+            ModuleConfig { enabled: true, priority: 0, custom_settings: HashMap::new() },
+        );
+        assert_eq!(state.config_revision, 1);
+        state.set_current_module("apps".to_string());
+        assert_eq!(state.config_revision, 2);
+
+        state.stats.cache_hits = 2;
+        state.stats.cache_prefix_hits = 1;
+        state.stats.cache_misses = 1;
+        assert_eq!(state.stats.cache_hit_rate(), 0.75);
+    }
 }
\ No newline at end of file
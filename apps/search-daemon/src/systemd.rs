@@ -0,0 +1,229 @@
+// systemd --user Integration
+// Readiness/watchdog notifications over $NOTIFY_SOCKET, socket activation
+// through $LISTEN_FDS and the unit files `r5-flowlight-daemon systemd-unit`
+// prints. Implemented directly on the documented protocols, so nothing
+// here needs libsystemd and it all degrades to no-ops outside systemd.
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::time::Duration;
+use log::{info, debug, warn};
+
+/// First file descriptor passed by socket activation (SD_LISTEN_FDS_START)
+const LISTEN_FDS_START: i32 = 3;
+
+pub const SERVICE_NAME: &str = "r5-flowlight-daemon";
+
+/// Sends state changes to the service manager
+pub struct Notifier {
+    socket_path: String,
+}
+
+impl Notifier {
+    /// `None` unless started by systemd with `Type=notify`
+    pub fn from_env() -> Option<Self> {
+        std::env::var("NOTIFY_SOCKET").ok().filter(|path| !path.is_empty()).map(Self::new)
+    }
+
+    /// `socket_path` starting with '@' names an abstract socket
+    pub fn new(socket_path: impl Into<String>) -> Self {
+        Self { socket_path: socket_path.into() }
+    }
+
+    /// Send newline-separated `KEY=VALUE` assignments
+    pub fn notify(&self, state: &str) -> io::Result<()> {
+        let socket = UnixDatagram::unbound()?;
+        match self.socket_path.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+                socket.send_to_addr(state.as_bytes(), &addr)?;
+            }
+            _ => {
+                socket.send_to(state.as_bytes(), &self.socket_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn ready(&self, status: &str) -> io::Result<()> {
+        self.notify(&format!("READY=1\nSTATUS={}\nMAINPID={}", status, std::process::id()))
+    }
+
+    pub fn stopping(&self) -> io::Result<()> {
+        self.notify("STOPPING=1\nSTATUS=Shutting down")
+    }
+
+    pub fn watchdog(&self) -> io::Result<()> {
+        self.notify("WATCHDOG=1")
+    }
+
+    /// Ping the watchdog at half the interval systemd expects, if it asked for one
+    pub fn spawn_watchdog(self: std::sync::Arc<Self>) {
+        let Some(interval) = watchdog_interval(
+            std::env::var("WATCHDOG_USEC").ok().as_deref(),
+            std::env::var("WATCHDOG_PID").ok().as_deref(),
+        ) else {
+            return;
+        };
+
+        info!("🐕 systemd watchdog enabled, pinging every {:?}", interval / 2);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval / 2);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.watchdog() {
+                    warn!("⚠️ Failed to ping systemd watchdog: {}", e);
+                }
+            }
+        });
+    }
+}
+
+/// Watchdog timeout from `WATCHDOG_USEC`, if it is meant for this process
+fn watchdog_interval(usec: Option<&str>, pid: Option<&str>) -> Option<Duration> {
+    if let Some(pid) = pid {
+        if pid.parse::<u32>().ok()? != std::process::id() {
+            return None;
+        }
+    }
+    let usec = usec?.parse::<u64>().ok().filter(|&usec| usec > 0)?;
+    Some(Duration::from_micros(usec))
+}
+
+/// The IPC listening socket passed by `r5-flowlight-daemon.socket`, if any
+///
+/// The environment is cleared so child processes (like the overlay) don't
+/// take the socket for theirs.
+pub fn take_activation_listener() -> Option<std::net::TcpListener> {
+    let pid = std::env::var("LISTEN_PID").ok();
+    let fds = std::env::var("LISTEN_FDS").ok();
+    std::env::remove_var("LISTEN_PID");
+    std::env::remove_var("LISTEN_FDS");
+    std::env::remove_var("LISTEN_FDNAMES");
+
+    if pid?.parse::<u32>().ok()? != std::process::id() {
+        return None;
+    }
+    let count = fds?.parse::<i32>().ok()?;
+    if count < 1 {
+        return None;
+    }
+    if count > 1 {
+        warn!("⚠️ {} sockets passed by systemd, only the first is used", count);
+    }
+
+    use std::os::unix::io::FromRawFd;
+    // SAFETY: systemd hands this descriptor to us and nothing else owns it
+    let listener = unsafe { std::net::TcpListener::from_raw_fd(LISTEN_FDS_START) };
+    if let Err(e) = listener.set_nonblocking(true) {
+        warn!("⚠️ Unusable socket from systemd: {}", e);
+        return None;
+    }
+    debug!("🔌 Using socket passed by systemd");
+    Some(listener)
+}
+
+/// Stderr is connected to the journal
+pub fn journald_logging() -> bool {
+    std::env::var_os("JOURNAL_STREAM").is_some()
+}
+
+/// syslog priority prefix understood by journald (sd-daemon.h)
+pub fn journald_priority(level: log::Level) -> u8 {
+    match level {
+        log::Level::Error => 3,
+        log::Level::Warn => 4,
+        log::Level::Info => 6,
+        log::Level::Debug | log::Level::Trace => 7,
+    }
+}
+
+/// `r5-flowlight-daemon.service`
+pub fn service_unit(exec: &std::path::Path, headless: bool) -> String {
+    let mode = if headless { " --headless" } else { "" };
+    format!(
+        "[Unit]
+Description=R5 Flowlight search daemon
+After=graphical-session.target
+
+[Service]
+Type=notify
+ExecStart={} run{}
+Restart=on-failure
+WatchdogSec=30
+# The overlay needs the user's display
+PassEnvironment=DISPLAY WAYLAND_DISPLAY XDG_RUNTIME_DIR
+
+[Install]
+WantedBy=default.target
+",
+        exec.display(),
+        mode
+    )
+}
+
+/// `r5-flowlight-daemon.socket`, starting the daemon on the first IPC connection
+pub fn socket_unit(port: u16) -> String {
+    format!(
+        "[Unit]
+Description=R5 Flowlight search daemon IPC socket
+
+[Socket]
+ListenStream=127.0.0.1:{}
+
+[Install]
+WantedBy=sockets.target
+",
+        port
+    )
+}
+
+/// Where `systemctl --user` looks for units
+pub fn user_unit_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("systemd").join("user"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_reaches_stub_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let stub = UnixDatagram::bind(&path).unwrap();
+
+        let notifier = Notifier::new(path.to_string_lossy().to_string());
+        notifier.ready("Serving IPC").unwrap();
+        notifier.watchdog().unwrap();
+
+        let mut buf = [0u8; 256];
+        let n = stub.recv(&mut buf).unwrap();
+        let ready = std::str::from_utf8(&buf[..n]).unwrap();
+        assert!(ready.starts_with("READY=1\nSTATUS=Serving IPC\n"), "{}", ready);
+        let n = stub.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"WATCHDOG=1");
+    }
+
+    #[test]
+    fn test_watchdog_interval() {
+        let own_pid = std::process::id().to_string();
+        assert_eq!(watchdog_interval(Some("30000000"), None), Some(Duration::from_secs(30)));
+        assert_eq!(watchdog_interval(Some("30000000"), Some(&own_pid)), Some(Duration::from_secs(30)));
+        // Meant for another process, or not requested
+        assert_eq!(watchdog_interval(Some("30000000"), Some("1")), None);
+        assert_eq!(watchdog_interval(Some("0"), None), None);
+        assert_eq!(watchdog_interval(None, None), None);
+    }
+
+    #[test]
+    fn test_unit_files() {
+        let service = service_unit(std::path::Path::new("/usr/bin/r5-flowlight-daemon"), true);
+        assert!(service.contains("Type=notify"));
+        assert!(service.contains("ExecStart=/usr/bin/r5-flowlight-daemon run --headless\n"));
+        assert!(socket_unit(19755).contains("ListenStream=127.0.0.1:19755"));
+    }
+}
@@ -3,7 +3,7 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use log::{info, error, warn, debug};
+use log::{info, error, debug};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tauri::{AppHandle, Manager, WebviewWindow, Emitter};
@@ -12,7 +12,18 @@ use serde::{Deserialize, Serialize};
 #[cfg(target_os = "macos")]
 use core_graphics::display::{CGDisplay, CGPoint};
 
+use clap::Parser;
 use ipc_communication::{IPCClient, IPCMessage, TcpIPCClient};
+use shared_core::{InstanceLock, InstanceStatus};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Query to show the overlay with. Forwarded (through the daemon) to the
+    /// running overlay when there already is one
+    #[arg(long, value_name = "QUERY")]
+    show: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealOverlayState {
@@ -159,19 +170,20 @@ async fn search_real_query(
 async fn execute_action(
     result_id: String,
     state: tauri::State<'_, Arc<RwLock<RealOverlayState>>>,
+    ipc_client: tauri::State<'_, Arc<RwLock<TcpIPCClient>>>,
 ) -> Result<(), String> {
     let overlay_state = state.read().await;
     
     if let Some(result) = overlay_state.search_results.iter().find(|r| r.id == result_id) {
         info!("⚡ Executing action for: {} ({})", result.title, result.action_type);
-        // TODO: Implement actual file/app opening, launching, copying
-        match result.action_type.as_str() {
-            "open" => { info!("📂 Opening: {}", result.title); }
-            "launch" => { info!("🚀 Launching: {}", result.title); }
-            "copy" => { info!("📋 Copying: {}", result.title); }
-            _ => { warn!("❓ Unknown action type: {}", result.action_type); }
-        }
-        Ok(())
+        // The daemon runs the action and records the pick for this query
+        let message = IPCMessage::ExecuteAction {
+            result_id: result.id.clone(),
+            action_type: result.action_type.clone(),
+            query: overlay_state.current_query.clone(),
+        };
+        ipc_client.write().await.send(message).await
+            .map_err(|e| format!("Failed to send action to daemon: {}", e))
     } else {
         Err(format!("Result with id '{}' not found", result_id))
     }
@@ -292,10 +304,28 @@ fn get_cursor_position() -> Option<(f64, f64)> {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
     env_logger::init();
     
     info!("🚀 R5 Flowlight REAL Search Overlay starting...");
     
+    // One overlay per user: the daemon checks this lock before spawning one,
+    // and a second launch asks the daemon to show the running overlay instead
+    let lock_path = InstanceLock::default_path(shared_core::OVERLAY_INSTANCE)
+        .ok_or("Could not find a directory for the instance lock")?;
+    let _instance_lock = match InstanceLock::acquire(&lock_path)
+        .map_err(|e| format!("Failed to check for a running overlay: {}", e))?
+    {
+        InstanceStatus::Acquired(lock) => lock,
+        InstanceStatus::Running(pid) => {
+            info!("🪟 Overlay already running (PID {}), asking the daemon to show it", pid);
+            let mut client = TcpIPCClient::new().await
+                .map_err(|e| format!("Daemon is not running: {}", e))?;
+            client.send(IPCMessage::ShowOverlay { query: args.show }).await?;
+            return Ok(());
+        }
+    };
+    
     let overlay_state = Arc::new(RwLock::new(RealOverlayState::default()));
     
     // --- IPC Connection with Retry Logic ---
@@ -324,6 +354,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
     
+    // Shown through the daemon, like a forwarded --show
+    if let Some(query) = args.show {
+        tcp_ipc_client.write().await.send(IPCMessage::ShowOverlay { query: Some(query) }).await?;
+    }
+    
     tauri::Builder::default()
         .manage(overlay_state.clone())
         .manage(tcp_ipc_client.clone())
@@ -12,11 +12,17 @@ pub enum IPCMessage {
     ToggleOverlay,
     ShowOverlay { query: Option<String> },
     HideOverlay,
+    /// Same as pressing the global shortcut, for desktops that bind it themselves
+    TriggerShortcut,
     
     // Search operations
     SearchQuery { query: String, session_id: String },
     SearchResults { results: Vec<SearchResult>, session_id: String },
     ClearResults,
+    /// Run a result's action; `query` is what was typed when it was picked
+    ExecuteAction { result_id: String, action_type: String, query: String },
+    /// Drop a result's usage history so it stops being boosted
+    ForgetResult { result_id: String },
     
     // Configuration
     UpdateModule { module_id: String },
@@ -27,12 +33,39 @@ pub enum IPCMessage {
     StartDaemon,
     StopDaemon,
     DaemonStatus { running: bool, pid: Option<u32> },
+    IndexingProgress { files_seen: u64, files_indexed: u64, files_skipped: u64, done: bool },
     
     // Handshake
     Ping,
     Pong,
 }
 
+impl IPCMessage {
+    /// Snake-case name of the variant, used to route messages to handlers
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IPCMessage::ToggleOverlay => "toggle_overlay",
+            IPCMessage::ShowOverlay { .. } => "show_overlay",
+            IPCMessage::HideOverlay => "hide_overlay",
+            IPCMessage::TriggerShortcut => "trigger_shortcut",
+            IPCMessage::SearchQuery { .. } => "search_query",
+            IPCMessage::SearchResults { .. } => "search_results",
+            IPCMessage::ClearResults => "clear_results",
+            IPCMessage::ExecuteAction { .. } => "execute_action",
+            IPCMessage::ForgetResult { .. } => "forget_result",
+            IPCMessage::UpdateModule { .. } => "update_module",
+            IPCMessage::GetCurrentModule => "get_current_module",
+            IPCMessage::ModuleChanged { .. } => "module_changed",
+            IPCMessage::StartDaemon => "start_daemon",
+            IPCMessage::StopDaemon => "stop_daemon",
+            IPCMessage::DaemonStatus { .. } => "daemon_status",
+            IPCMessage::IndexingProgress { .. } => "indexing_progress",
+            IPCMessage::Ping => "ping",
+            IPCMessage::Pong => "pong",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: String,
@@ -59,6 +92,51 @@ pub enum IPCError {
 
 pub type IPCResult<T> = Result<T, IPCError>;
 
+/// Sends messages to every connected client; implemented by the transports
+/// and by fakes in tests
+#[async_trait::async_trait]
+pub trait Broadcaster: Send + Sync {
+    async fn broadcast(&self, message: IPCMessage) -> IPCResult<()>;
+    async fn client_count(&self) -> usize;
+}
+
+/// Way back to the client a message came from, usable after the handler
+/// returned; messages are queued for the client's writer
+#[derive(Debug, Clone)]
+pub struct Reply {
+    client_id: u64,
+    queue: mpsc::Sender<IPCMessage>,
+}
+
+impl Reply {
+    /// Reply handle for `client_id` and the queue its messages are read from
+    pub fn channel(client_id: u64, capacity: usize) -> (Self, mpsc::Receiver<IPCMessage>) {
+        let (queue, messages) = mpsc::channel(capacity);
+        (Self { client_id, queue }, messages)
+    }
+
+    pub fn client_id(&self) -> u64 {
+        self.client_id
+    }
+
+    /// Queue `message` without waiting; fails if the client is gone or not reading
+    pub fn send(&self, message: IPCMessage) -> IPCResult<()> {
+        self.queue.try_send(message).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => {
+                IPCError::SendFailed(format!("Client {} is not reading", self.client_id))
+            }
+            mpsc::error::TrySendError::Closed(_) => {
+                IPCError::SendFailed(format!("Client {} disconnected", self.client_id))
+            }
+        })
+    }
+
+    /// Resolves once the client's writer stopped
+    pub async fn closed(&self) {
+        self.queue.closed().await
+    }
+}
+
 // Cross-platform IPC implementation
 // TODO: Fix async lifetime issues
 // pub mod channel;
@@ -73,7 +151,7 @@ pub mod tcp_ipc; // TCP-based cross-process IPC
 // pub use client::*;
 pub use simple::{test_basic_ipc, MessageHandler};
 pub use real_simple::{IPCServer, IPCClient, debug_message_bus};
-pub use tcp_ipc::{TcpIPCServer, TcpIPCClient};
+pub use tcp_ipc::{TcpIPCServer, TcpIPCClient, IPC_PORT};
 
 // Constants
 pub const IPC_PIPE_NAME: &str = "r5_flowlight_ipc";
@@ -98,4 +176,11 @@ mod tests {
             _ => panic!("Deserialization failed"),
         }
     }
+
+    #[test]
+    fn test_message_kind() {
+        assert_eq!(IPCMessage::Ping.kind(), "ping");
+        assert_eq!(IPCMessage::ShowOverlay { query: None }.kind(), "show_overlay");
+        assert_eq!(IPCMessage::DaemonStatus { running: true, pid: None }.kind(), "daemon_status");
+    }
 }
\ No newline at end of file
@@ -116,6 +116,7 @@ impl IPCServer {
             IPCMessage::StartDaemon => "start_daemon".to_string(),
             IPCMessage::StopDaemon => "stop_daemon".to_string(),
             IPCMessage::DaemonStatus { .. } => "daemon_status".to_string(),
+            IPCMessage::IndexingProgress { .. } => "indexing_progress".to_string(),
             IPCMessage::Ping => "ping".to_string(),
             IPCMessage::Pong => "pong".to_string(),
             IPCMessage::ClearResults => "clear_results".to_string(),
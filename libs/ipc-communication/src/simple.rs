@@ -1,6 +1,6 @@
 // Simplified IPC for testing - without complex async handling
 
-use crate::{IPCMessage, IPCResult, IPCError};
+use crate::{IPCMessage, IPCResult, IPCError, Reply};
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
@@ -102,6 +102,13 @@ pub fn test_basic_ipc() -> IPCResult<()> {
 // Integration-ready IPC implementations
 pub trait MessageHandler {
     fn handle(&self, message: IPCMessage) -> IPCResult<Option<IPCMessage>>;
+
+    /// Like `handle`, for handlers that answer later through `reply`;
+    /// the returned message is still sent first
+    fn handle_with_reply(&self, message: IPCMessage, reply: &Reply) -> IPCResult<Option<IPCMessage>> {
+        let _ = reply;
+        self.handle(message)
+    }
 }
 
 pub struct IPCServer {
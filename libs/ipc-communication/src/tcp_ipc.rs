@@ -1,133 +1,434 @@
 // TCP-based IPC for real cross-process communication
+// Messages are newline-delimited JSON in both directions
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use log::{debug, error, info, warn};
 use serde_json;
-use crate::{IPCMessage, IPCResult, IPCError};
+use crate::{Broadcaster, IPCMessage, IPCResult, IPCError, MessageHandler, Reply};
 
-const IPC_PORT: u16 = 19755;
+/// Port the daemon listens on (localhost only)
+pub const IPC_PORT: u16 = 19755;
+
+/// Messages waiting for one client before new ones are dropped
+const CLIENT_QUEUE_CAPACITY: usize = 64;
+
+/// A client that takes longer than this to accept one message is disconnected
+const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+type SharedHandler = Arc<dyn MessageHandler + Send + Sync>;
+type Clients = Arc<RwLock<Vec<ConnectedClient>>>;
+
+struct ConnectedClient {
+    id: u64,
+    queue: Reply,
+}
 
 pub struct TcpIPCServer {
     listener: Option<TcpListener>,
-    clients: Arc<RwLock<Vec<TcpStream>>>,
+    clients: Clients,
+    handler: Option<SharedHandler>,
+    write_timeout: Duration,
+    accept_task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl TcpIPCServer {
     pub async fn new() -> IPCResult<Self> {
-        let listener = TcpListener::bind(format!("127.0.0.1:{}", IPC_PORT)).await
+        Self::bind(&format!("127.0.0.1:{}", IPC_PORT)).await
+    }
+
+    pub async fn bind(addr: &str) -> IPCResult<Self> {
+        let listener = TcpListener::bind(addr).await
             .map_err(|e| IPCError::ConnectionFailed(format!("Failed to bind TCP server: {}", e)))?;
-        
-        println!("🔌 TCP IPC Server listening on port {}", IPC_PORT);
-        
-        Ok(Self {
+
+        info!("🔌 TCP IPC Server listening on {}", addr);
+        Ok(Self::with_listener(listener))
+    }
+
+    /// Serve on an already bound socket, e.g. one passed by the service manager
+    pub fn from_std_listener(listener: std::net::TcpListener) -> IPCResult<Self> {
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        info!("🔌 TCP IPC Server using inherited socket {:?}", listener.local_addr().ok());
+        Ok(Self::with_listener(listener))
+    }
+
+    fn with_listener(listener: TcpListener) -> Self {
+        Self {
             listener: Some(listener),
             clients: Arc::new(RwLock::new(Vec::new())),
-        })
+            handler: None,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            accept_task: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        self.listener.as_ref().and_then(|listener| listener.local_addr().ok())
+    }
+
+    /// Handler for messages sent by clients; its reply goes back to the sender only
+    pub fn set_handler(&mut self, handler: SharedHandler) {
+        self.handler = Some(handler);
+    }
+
+    /// How long a client may take to accept one message before it is disconnected
+    pub fn set_write_timeout(&mut self, write_timeout: Duration) {
+        self.write_timeout = write_timeout;
     }
-    
+
     pub async fn start(&mut self) -> IPCResult<()> {
         if let Some(listener) = self.listener.take() {
             let clients = self.clients.clone();
-            
-            tokio::spawn(async move {
+            let handler = self.handler.clone();
+            let write_timeout = self.write_timeout;
+            let next_id = Arc::new(AtomicU64::new(1));
+
+            let accept_task = tokio::spawn(async move {
                 loop {
                     match listener.accept().await {
                         Ok((stream, addr)) => {
-                            println!("🔌 New client connected: {}", addr);
-                            let mut clients_lock = clients.write().await;
-                            clients_lock.push(stream);
+                            let id = next_id.fetch_add(1, Ordering::Relaxed);
+                            info!("🔌 New client {} connected: {}", id, addr);
+                            let (reader, writer) = stream.into_split();
+                            let (queue, messages) = Reply::channel(id, CLIENT_QUEUE_CAPACITY);
+                            clients.write().await.push(ConnectedClient { id, queue: queue.clone() });
+                            tokio::spawn(write_client(id, writer, messages, write_timeout, clients.clone()));
+                            tokio::spawn(serve_client(id, reader, queue, clients.clone(), handler.clone()));
                         }
                         Err(e) => {
-                            println!("❌ Failed to accept connection: {}", e);
+                            error!("❌ Failed to accept connection: {}", e);
                         }
                     }
                 }
             });
+            *self.accept_task.lock().unwrap_or_else(|e| e.into_inner()) = Some(accept_task);
         }
-        
+
         Ok(())
     }
-    
+
+    /// Close the listening socket; connected clients keep being served
+    pub fn stop_accepting(&self) {
+        if let Some(accept_task) = self.accept_task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            accept_task.abort();
+            info!("🔌 TCP IPC Server stopped accepting connections");
+        }
+    }
+
+    /// Queue `message` for every client; never waits on a slow one
     pub async fn broadcast(&self, message: IPCMessage) -> IPCResult<()> {
-        let message_json = serde_json::to_string(&message)
-            .map_err(|e| IPCError::SerializationError(e))?;
-        
-        let mut clients_lock = self.clients.write().await;
-        let mut to_remove = Vec::new();
-        
-        for (i, stream) in clients_lock.iter_mut().enumerate() {
-            match stream.write_all(format!("{}\n", message_json).as_bytes()).await {
-                Ok(_) => {
-                    println!("📤 Message sent to client {}", i);
-                }
-                Err(_) => {
-                    println!("❌ Failed to send to client {}, marking for removal", i);
-                    to_remove.push(i);
+        let mut gone = Vec::new();
+        for client in self.clients.read().await.iter() {
+            match client.queue.send(message.clone()) {
+                Ok(()) => debug!("📤 Message queued for client {}", client.id),
+                Err(e) => {
+                    // A full queue means a stuck writer, which its timeout disconnects
+                    warn!("⚠️ Message to client {} dropped: {}", client.id, e);
+                    gone.push(client.id);
                 }
             }
         }
-        
-        // Remove disconnected clients
-        for &i in to_remove.iter().rev() {
-            clients_lock.remove(i);
+
+        if !gone.is_empty() {
+            self.clients.write().await.retain(|client| !gone.contains(&client.id));
         }
-        
         Ok(())
     }
+
+    pub async fn client_count(&self) -> usize {
+        self.clients.read().await.len()
+    }
+}
+
+#[async_trait::async_trait]
+impl Broadcaster for TcpIPCServer {
+    async fn broadcast(&self, message: IPCMessage) -> IPCResult<()> {
+        TcpIPCServer::broadcast(self, message).await
+    }
+
+    async fn client_count(&self) -> usize {
+        TcpIPCServer::client_count(self).await
+    }
+}
+
+/// Writes one client's queued messages, disconnecting it when a write fails
+/// or takes longer than `write_timeout`
+async fn write_client(
+    id: u64,
+    mut writer: OwnedWriteHalf,
+    mut messages: mpsc::Receiver<IPCMessage>,
+    write_timeout: Duration,
+    clients: Clients,
+) {
+    while let Some(message) = messages.recv().await {
+        let message_json = match serde_json::to_string(&message) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("❌ Failed to serialize message for client {}: {}", id, e);
+                continue;
+            }
+        };
+        let line = format!("{}\n", message_json);
+        match tokio::time::timeout(write_timeout, writer.write_all(line.as_bytes())).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                warn!("⚠️ Failed to send to client {}: {}", id, e);
+                break;
+            }
+            Err(_) => {
+                warn!("⚠️ Client {} did not read for {:?}, disconnecting", id, write_timeout);
+                break;
+            }
+        }
+    }
+
+    // Closing the queue also stops the client's reader
+    messages.close();
+    clients.write().await.retain(|client| client.id != id);
+}
+
+/// Reads one client's messages until it disconnects, answering through the handler
+async fn serve_client(
+    id: u64,
+    reader: OwnedReadHalf,
+    reply: Reply,
+    clients: Clients,
+    handler: Option<SharedHandler>,
+) {
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let next_line = tokio::select! {
+            next_line = lines.next_line() => next_line,
+            _ = reply.closed() => break,
+        };
+        let line = match next_line {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("⚠️ Failed to read from client {}: {}", id, e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message: IPCMessage = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("⚠️ Invalid message from client {}: {}", id, e);
+                continue;
+            }
+        };
+        debug!("📨 TCP Server received {} from client {}", message.kind(), id);
+
+        let Some(handler) = &handler else {
+            continue;
+        };
+        match handler.handle_with_reply(message, &reply) {
+            Ok(Some(answer)) => {
+                if let Err(e) = reply.send(answer) {
+                    warn!("⚠️ Reply dropped: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => error!("❌ Handler failed for client {}: {}", id, e),
+        }
+    }
+
+    info!("🔌 Client {} disconnected", id);
+    clients.write().await.retain(|client| client.id != id);
 }
 
 pub struct TcpIPCClient {
-    stream: Option<TcpStream>,
+    reader: Option<BufReader<OwnedReadHalf>>,
+    writer: Option<OwnedWriteHalf>,
 }
 
 impl TcpIPCClient {
     pub async fn new() -> IPCResult<Self> {
-        let stream = TcpStream::connect(format!("127.0.0.1:{}", IPC_PORT)).await
+        Self::connect(&format!("127.0.0.1:{}", IPC_PORT)).await
+    }
+
+    pub async fn connect(addr: &str) -> IPCResult<Self> {
+        let stream = TcpStream::connect(addr).await
             .map_err(|e| IPCError::ConnectionFailed(format!("Failed to connect to TCP server: {}", e)))?;
-        
-        println!("🔌 TCP IPC Client connected");
-        
+
+        info!("🔌 TCP IPC Client connected");
+
+        let (reader, writer) = stream.into_split();
         Ok(Self {
-            stream: Some(stream),
+            reader: Some(BufReader::new(reader)),
+            writer: Some(writer),
         })
     }
-    
+
     pub async fn send(&mut self, message: IPCMessage) -> IPCResult<()> {
-        if let Some(stream) = &mut self.stream {
+        if let Some(writer) = &mut self.writer {
             let message_json = serde_json::to_string(&message)
-                .map_err(|e| IPCError::SerializationError(e))?;
-            
-            stream.write_all(format!("{}\n", message_json).as_bytes()).await
+                .map_err(IPCError::SerializationError)?;
+
+            writer.write_all(format!("{}\n", message_json).as_bytes()).await
                 .map_err(|e| IPCError::SendFailed(format!("Failed to send message: {}", e)))?;
-            
-            println!("📤 TCP Client sent: {:?}", message);
+
+            debug!("📤 TCP Client sent {}", message.kind());
             Ok(())
         } else {
             Err(IPCError::SendFailed("No connection available".to_string()))
         }
     }
-    
+
     pub async fn receive(&mut self) -> IPCResult<IPCMessage> {
-        if let Some(stream) = &mut self.stream {
-            let mut buffer = vec![0; 1024];
-            let n = stream.read(&mut buffer).await
+        if let Some(reader) = &mut self.reader {
+            let mut line = String::new();
+            let n = reader.read_line(&mut line).await
                 .map_err(|e| IPCError::ReceiveFailed(format!("Failed to read from stream: {}", e)))?;
-            
+
             if n == 0 {
                 return Err(IPCError::ReceiveFailed("Connection closed".to_string()));
             }
-            
-            let message_str = String::from_utf8_lossy(&buffer[..n]);
-            let message_str = message_str.trim();
-            
-            let message: IPCMessage = serde_json::from_str(message_str)
-                .map_err(|e| IPCError::SerializationError(e))?;
-            
-            println!("📨 TCP Client received: {:?}", message);
+
+            let message: IPCMessage = serde_json::from_str(line.trim())
+                .map_err(IPCError::SerializationError)?;
+
+            debug!("📨 TCP Client received {}", message.kind());
             Ok(message)
         } else {
             Err(IPCError::ReceiveFailed("No connection available".to_string()))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoPing;
+
+    impl MessageHandler for EchoPing {
+        fn handle(&self, message: IPCMessage) -> IPCResult<Option<IPCMessage>> {
+            Ok(match message {
+                IPCMessage::Ping => Some(IPCMessage::Pong),
+                _ => None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_dispatches_client_messages() {
+        let mut server = TcpIPCServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        server.set_handler(Arc::new(EchoPing));
+        server.start().await.unwrap();
+
+        let mut client = TcpIPCClient::connect(&addr).await.unwrap();
+        client.send(IPCMessage::HideOverlay).await.unwrap();
+        client.send(IPCMessage::Ping).await.unwrap();
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::Pong));
+
+        assert_eq!(server.client_count().await, 1);
+        server.broadcast(IPCMessage::ToggleOverlay).await.unwrap();
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::ToggleOverlay));
+    }
+
+    struct ReplyLater;
+
+    impl MessageHandler for ReplyLater {
+        fn handle(&self, _message: IPCMessage) -> IPCResult<Option<IPCMessage>> {
+            Ok(None)
+        }
+
+        fn handle_with_reply(&self, message: IPCMessage, reply: &Reply) -> IPCResult<Option<IPCMessage>> {
+            let reply = reply.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                reply.send(IPCMessage::ClearResults).unwrap();
+            });
+            Ok(match message {
+                IPCMessage::Ping => Some(IPCMessage::Pong),
+                _ => None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_replies_after_returning() {
+        let mut server = TcpIPCServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        server.set_handler(Arc::new(ReplyLater));
+        server.start().await.unwrap();
+
+        let mut client = TcpIPCClient::connect(&addr).await.unwrap();
+        client.send(IPCMessage::Ping).await.unwrap();
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::Pong));
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::ClearResults));
+    }
+
+    #[tokio::test]
+    async fn test_client_that_stops_reading_is_dropped() {
+        let mut server = TcpIPCServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        server.set_write_timeout(Duration::from_millis(100));
+        server.start().await.unwrap();
+
+        // Connected but never reads
+        let _stuck = TcpStream::connect(&addr).await.unwrap();
+        let mut client = TcpIPCClient::connect(&addr).await.unwrap();
+        while server.client_count().await < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        let big = IPCMessage::ShowOverlay { query: Some("x".repeat(1 << 20)) };
+        for _ in 0..200 {
+            let started = std::time::Instant::now();
+            server.broadcast(big.clone()).await.unwrap();
+            assert!(started.elapsed() < Duration::from_millis(500), "broadcast waited on a slow client");
+            assert!(matches!(client.receive().await.unwrap(), IPCMessage::ShowOverlay { .. }));
+            if server.client_count().await == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(server.client_count().await, 1);
+
+        server.broadcast(IPCMessage::HideOverlay).await.unwrap();
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::HideOverlay));
+    }
+
+    #[tokio::test]
+    async fn test_server_from_inherited_socket() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let mut server = TcpIPCServer::from_std_listener(listener).unwrap();
+        server.set_handler(Arc::new(EchoPing));
+        server.start().await.unwrap();
+
+        let mut client = TcpIPCClient::connect(&addr).await.unwrap();
+        client.send(IPCMessage::Ping).await.unwrap();
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::Pong));
+    }
+
+    #[tokio::test]
+    async fn test_stop_accepting_keeps_connected_clients() {
+        let mut server = TcpIPCServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        server.start().await.unwrap();
+        let mut client = TcpIPCClient::connect(&addr).await.unwrap();
+        while server.client_count().await == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        server.stop_accepting();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(TcpIPCClient::connect(&addr).await.is_err());
+
+        server.broadcast(IPCMessage::HideOverlay).await.unwrap();
+        assert!(matches!(client.receive().await.unwrap(), IPCMessage::HideOverlay));
+    }
+}
@@ -0,0 +1,295 @@
+// Apps module - Launch installed applications from freedesktop .desktop entries
+
+use crate::traits::*;
+use crate::base::BaseSearchModule;
+use crate::desktop_entry::{self, DesktopEntry};
+use async_trait::async_trait;
+use fuzzy_matcher::FuzzyMatcher;
+use log::{info, debug};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+pub const APPS_MODULE_ID: &str = "apps";
+
+pub struct AppsModule {
+    base: BaseSearchModule,
+    entries: Vec<DesktopEntry>,
+}
+
+impl AppsModule {
+    pub fn new() -> Self {
+        let info = ModuleInfo {
+            id: APPS_MODULE_ID.to_string(),
+            name: "Applications".to_string(),
+            description: "Search and launch installed applications".to_string(),
+            version: "1.0.0".to_string(),
+            author: "R5 Flowlight".to_string(),
+            enabled: true,
+            keywords: vec![
+                "apps".to_string(),
+                "applications".to_string(),
+                "launch".to_string(),
+            ],
+        };
+
+        Self {
+            base: BaseSearchModule::new(info),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[DesktopEntry] {
+        &self.entries
+    }
+
+    /// Rescan the application directories ("application_dirs" and "locale" settings override the XDG defaults)
+    async fn reload(&mut self) -> anyhow::Result<()> {
+        let dirs: Vec<PathBuf> = match self.base.settings.get("application_dirs") {
+            Some(value) => serde_json::from_value(value.clone())?,
+            None => desktop_entry::application_dirs(),
+        };
+        let locale = self
+            .base
+            .settings
+            .get("locale")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(desktop_entry::current_locale);
+
+        self.entries = tokio::task::spawn_blocking(move || desktop_entry::discover(&dirs, &locale)).await?;
+        Ok(())
+    }
+
+    /// Name matches weigh most, then generic name and comment, then keywords
+    fn score(&self, entry: &DesktopEntry, query: &str) -> Option<i64> {
+        let matcher = &self.base.matcher;
+        let secondary = entry.generic_name.iter().chain(entry.comment.iter());
+
+        matcher
+            .fuzzy_match(&entry.name, query)
+            .or_else(|| secondary.filter_map(|text| matcher.fuzzy_match(text, query)).max().map(|s| s / 2))
+            .or_else(|| {
+                entry
+                    .keywords
+                    .iter()
+                    .filter_map(|keyword| matcher.fuzzy_match(keyword, query))
+                    .max()
+                    .map(|s| s / 3)
+            })
+    }
+
+    /// Best matches for `text`, with scores in `[0, 1]`
+    pub fn ranked(&self, text: &str, limit: usize) -> Vec<(&DesktopEntry, f32)> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(&DesktopEntry, i64)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| self.score(entry, text).map(|score| (entry, score)))
+            .collect();
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+        scored.truncate(limit);
+
+        scored
+            .into_iter()
+            .map(|(entry, score)| (entry, (score as f32 / 1000.0).clamp(0.0, 1.0)))
+            .collect()
+    }
+
+    fn to_search_result(entry: &DesktopEntry, score: f32) -> SearchResult {
+        let mut metadata = HashMap::new();
+        metadata.insert("desktop_file".to_string(), entry.path.display().to_string());
+        metadata.insert("exec".to_string(), entry.exec.clone());
+        metadata.insert("actions".to_string(), "launch".to_string());
+        if let Some(icon) = &entry.icon {
+            metadata.insert("icon_name".to_string(), icon.clone());
+        }
+
+        SearchResult {
+            id: entry.id.clone(),
+            title: entry.name.clone(),
+            description: entry
+                .generic_name
+                .clone()
+                .or_else(|| entry.comment.clone())
+                .unwrap_or_else(|| entry.exec.clone()),
+            icon: Some("🚀".to_string()),
+            action_type: "launch".to_string(),
+            score,
+            metadata,
+        }
+    }
+}
+
+impl Default for AppsModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SearchModule for AppsModule {
+    fn info(&self) -> ModuleInfo {
+        self.base.info()
+    }
+
+    async fn initialize(&mut self, config: HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+        self.base.initialize(config).await?;
+        self.reload().await?;
+
+        info!("✅ Apps module initialized with {} applications", self.entries.len());
+        Ok(())
+    }
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>> {
+        let results: Vec<SearchResult> = self
+            .ranked(&query.text, query.max_results)
+            .into_iter()
+            .map(|(entry, score)| Self::to_search_result(entry, score))
+            .collect();
+
+        debug!("🔍 Apps search for '{}' returned {} results", query.text.trim(), results.len());
+        Ok(results)
+    }
+
+    /// Fuzzy matching is by subsequence, so a longer query never matches an app the shorter one missed
+    fn matches_by_substring(&self) -> bool {
+        true
+    }
+
+    async fn execute_action(&self, result_id: &str, action_type: &str) -> anyhow::Result<()> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.id == result_id)
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a known application", result_id))?;
+
+        match action_type {
+            "launch" | "open" => launch(entry),
+            _ => Err(anyhow::anyhow!("Unsupported action type: {}", action_type)),
+        }
+    }
+
+    async fn health_check(&self) -> anyhow::Result<bool> {
+        Ok(self.base.initialized)
+    }
+
+    fn get_settings_schema(&self) -> HashMap<String, serde_json::Value> {
+        let mut schema = self.base.get_settings_schema();
+
+        schema.insert("application_dirs".to_string(), serde_json::json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Directories with .desktop files, in precedence order"
+        }));
+
+        schema.insert("locale".to_string(), serde_json::json!({
+            "type": "string",
+            "description": "Locale for application names, e.g. pt_BR"
+        }));
+
+        schema
+    }
+
+    async fn update_settings(&mut self, settings: HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+        self.base.update_settings(settings).await?;
+        self.reload().await?;
+
+        info!("⚙️  Apps module settings updated");
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> anyhow::Result<()> {
+        self.entries.clear();
+        self.base.cleanup().await?;
+
+        info!("🧹 Apps module cleaned up");
+        Ok(())
+    }
+}
+
+fn launch(entry: &DesktopEntry) -> anyhow::Result<()> {
+    let mut command_line = entry.command_line(&[])?;
+
+    // Terminal=true programs need a terminal emulator around them
+    if entry.terminal {
+        let terminal = std::env::var("TERMINAL").unwrap_or_else(|_| "x-terminal-emulator".to_string());
+        command_line.splice(0..0, [terminal, "-e".to_string()]);
+    }
+
+    let mut command = Command::new(&command_line[0]);
+    command
+        .args(&command_line[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(dir) = &entry.working_dir {
+        command.current_dir(dir);
+    }
+
+    // Own process group, so the app outlives the launcher
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to launch '{}': {}", entry.name, e))?;
+
+    info!("🚀 Launched {} ({})", entry.name, command_line.join(" "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            text: text.to_string(),
+            module_filter: None,
+            max_results: 10,
+            timeout_ms: 3000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apps_module_searches_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::write(
+            dir.join("org.example.Editor.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Editor\nName[pt_BR]=Editor de Texto\nKeywords=notes;txt;\nExec=editor %F\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("calc.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Calculator\nGenericName=Arithmetic\nExec=calc\n",
+        )
+        .unwrap();
+
+        let mut module = AppsModule::new();
+        module.initialize(HashMap::from([
+            ("application_dirs".to_string(), serde_json::json!([dir])),
+            ("locale".to_string(), serde_json::json!("pt_BR.UTF-8")),
+        ])).await.unwrap();
+        assert_eq!(module.entries().len(), 2);
+
+        let results = module.search(&query("texto")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "org.example.Editor.desktop");
+        assert_eq!(results[0].title, "Editor de Texto");
+
+        // Keywords and generic names match too
+        assert_eq!(module.search(&query("notes")).await.unwrap()[0].id, "org.example.Editor.desktop");
+        assert_eq!(module.search(&query("arithm")).await.unwrap()[0].id, "calc.desktop");
+
+        assert!(module.execute_action("missing.desktop", "launch").await.is_err());
+    }
+}
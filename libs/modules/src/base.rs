@@ -25,33 +25,27 @@ impl BaseSearchModule {
     }
     
     pub fn fuzzy_search(&self, query: &str, items: &[SearchResult]) -> Vec<SearchResult> {
-        let mut scored_results: Vec<(SearchResult, i64)> = items
+        let mut scored_results: Vec<(SearchResult, f32)> = items
             .iter()
             .filter_map(|item| {
-                // Try matching title first
-                if let Some(score) = self.matcher.fuzzy_match(&item.title, query) {
-                    return Some((item.clone(), score));
-                }
+                // Try matching title first, then description with lower priority
+                let score = self.matcher.fuzzy_match(&item.title, query)
+                    .or_else(|| self.matcher.fuzzy_match(&item.description, query).map(|score| score / 2))?;
                 
-                // Then try description
-                if let Some(score) = self.matcher.fuzzy_match(&item.description, query) {
-                    return Some((item.clone(), score / 2)); // Lower priority for description matches
-                }
-                
-                None
+                // Normalize score to 0.0-1.0 range
+                Some((item.clone(), (score as f32 / 1000.0).clamp(0.0, 1.0)))
             })
             .collect();
         
         // Sort by score (descending)
-        scored_results.sort_by(|a, b| b.1.cmp(&a.1));
+        scored_results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
         
         // Update scores and return results
         scored_results
             .into_iter()
             .enumerate()
             .map(|(index, (mut result, score))| {
-                // Normalize score to 0.0-1.0 range
-                result.score = (score as f32 / 1000.0).min(1.0).max(0.0);
+                result.score = score;
                 
                 // Apply small penalty for position
                 result.score *= 1.0 - (index as f32 * 0.01);
@@ -0,0 +1,480 @@
+// Freedesktop .desktop entries - discovery, parsing and Exec expansion
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DesktopEntry {
+    /// Desktop file ID, e.g. "org.gnome.Terminal.desktop"
+    pub id: String,
+    pub path: PathBuf,
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub comment: Option<String>,
+    pub keywords: Vec<String>,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    /// Working directory for the launched program
+    pub working_dir: Option<PathBuf>,
+}
+
+/// Result of parsing one file: an application, or an entry that hides others with the same ID
+enum ParsedEntry {
+    Application(Box<DesktopEntry>),
+    Hidden,
+}
+
+/// `applications` directories in precedence order: the user's data dir first, then `XDG_DATA_DIRS`
+pub fn application_dirs() -> Vec<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').filter(|d| !d.is_empty()).map(PathBuf::from))
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// Locale used for `Name[xx_YY]` lookups, from the usual POSIX variables
+pub fn current_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_else(|| "C".to_string())
+}
+
+/// Load every visible application; an ID found in an earlier directory shadows later ones
+pub fn discover(dirs: &[PathBuf], locale: &str) -> Vec<DesktopEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for dir in dirs {
+        let mut files = Vec::new();
+        collect_desktop_files(dir, dir, &mut files);
+
+        for (id, path) in files {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("⚠️  Could not read {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            if let Some(ParsedEntry::Application(entry)) = parse_entry(&id, &path, &content, locale) {
+                entries.push(*entry);
+            }
+        }
+    }
+
+    debug!("🗂️  Discovered {} applications", entries.len());
+    entries
+}
+
+fn collect_desktop_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut paths: Vec<PathBuf> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_desktop_files(root, &path, files);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("desktop") {
+            // Subdirectories become part of the ID: kde/konsole.desktop -> kde-konsole.desktop
+            if let Ok(relative) = path.strip_prefix(root) {
+                let id = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("-");
+                files.push((id, path));
+            }
+        }
+    }
+}
+
+impl DesktopEntry {
+    /// Parse the `[Desktop Entry]` group; `None` for non-applications and hidden entries
+    pub fn parse(id: &str, path: &Path, content: &str, locale: &str) -> Option<Self> {
+        match parse_entry(id, path, content, locale)? {
+            ParsedEntry::Application(entry) => Some(*entry),
+            ParsedEntry::Hidden => None,
+        }
+    }
+
+    /// Command line for launching the entry, with field codes expanded
+    pub fn command_line(&self, files: &[&str]) -> anyhow::Result<Vec<String>> {
+        expand_exec(self, files)
+    }
+}
+
+fn parse_entry(id: &str, path: &Path, content: &str, locale: &str) -> Option<ParsedEntry> {
+    let mut in_main_group = false;
+    let mut pairs: Vec<(&str, &str)> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            continue;
+        }
+        if in_main_group {
+            if let Some((key, value)) = line.split_once('=') {
+                pairs.push((key.trim(), value.trim()));
+            }
+        }
+    }
+
+    let raw = |key: &str| pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+    let localized = |key: &str| {
+        locale_variants(locale)
+            .iter()
+            .find_map(|variant| raw(&format!("{}[{}]", key, variant)))
+            .or_else(|| raw(key))
+            .map(unescape_value)
+    };
+    let flag = |key: &str| raw(key).map(|v| v == "true").unwrap_or(false);
+
+    if raw("Type") != Some("Application") || flag("Hidden") || flag("NoDisplay") {
+        return Some(ParsedEntry::Hidden);
+    }
+
+    let entry = DesktopEntry {
+        id: id.to_string(),
+        path: path.to_path_buf(),
+        name: localized("Name")?,
+        generic_name: localized("GenericName"),
+        comment: localized("Comment"),
+        keywords: localized("Keywords").map(|k| split_list(&k)).unwrap_or_default(),
+        exec: raw("Exec").map(unescape_value)?,
+        icon: raw("Icon").map(unescape_value),
+        terminal: flag("Terminal"),
+        working_dir: raw("Path").filter(|p| !p.is_empty()).map(PathBuf::from),
+    };
+    Some(ParsedEntry::Application(Box::new(entry)))
+}
+
+/// Locale suffixes to try, most specific first: lang_COUNTRY@MODIFIER, lang_COUNTRY, lang@MODIFIER, lang
+fn locale_variants(locale: &str) -> Vec<String> {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or("").replace('-', "_");
+    let (lang, country) = match rest.split_once('_') {
+        Some((lang, country)) => (lang.to_string(), Some(country.to_string())),
+        None => (rest.clone(), None),
+    };
+
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut variants = Vec::new();
+    if let (Some(country), Some(modifier)) = (&country, modifier) {
+        variants.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = &country {
+        variants.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        variants.push(format!("{}@{}", lang, modifier));
+    }
+    variants.push(lang);
+    variants
+}
+
+/// Undo the escapes allowed in string values: \s \n \t \r \\
+fn unescape_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            // Keep unknown escapes (e.g. "\;" in lists) for the next stage
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Split a `;` separated list, honouring `\;`
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.clone().next() == Some(';') => {
+                chars.next();
+                current.push(';');
+            }
+            ';' => items.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Split Exec into arguments following the quoting rules of the spec
+fn split_exec(exec: &str) -> anyhow::Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => current.push(escaped),
+                            None => anyhow::bail!("Unterminated escape in Exec: {}", exec),
+                        },
+                        Some(other) => current.push(other),
+                        None => anyhow::bail!("Unterminated quote in Exec: {}", exec),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+fn expand_exec(entry: &DesktopEntry, files: &[&str]) -> anyhow::Result<Vec<String>> {
+    let mut command = Vec::new();
+
+    for arg in split_exec(&entry.exec)? {
+        // List codes are only valid as a whole argument and expand to several arguments
+        match arg.as_str() {
+            "%F" | "%U" => {
+                command.extend(files.iter().map(|f| f.to_string()));
+                continue;
+            }
+            "%i" => {
+                if let Some(icon) = &entry.icon {
+                    command.push("--icon".to_string());
+                    command.push(icon.clone());
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut expanded = String::new();
+        let mut had_code = false;
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some('f') | Some('u') => {
+                    had_code = true;
+                    if let Some(file) = files.first() {
+                        expanded.push_str(file);
+                    }
+                }
+                Some('c') => expanded.push_str(&entry.name),
+                Some('k') => expanded.push_str(&entry.path.to_string_lossy()),
+                // Deprecated (%d %D %n %N %v %m) or misplaced codes expand to nothing
+                Some(_) => had_code = true,
+                None => anyhow::bail!("Dangling '%' in Exec: {}", entry.exec),
+            }
+        }
+
+        // An argument that was only a field code with nothing to insert is dropped
+        if !(had_code && expanded.is_empty()) {
+            command.push(expanded);
+        }
+    }
+
+    if command.is_empty() {
+        anyhow::bail!("Empty Exec for {}", entry.id);
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIREFOX: &str = "\
+[Desktop Entry]
+Type=Application
+Name=Firefox
+Name[pt_BR]=Navegador Firefox
+Name[pt]=Firefox (pt)
+GenericName=Web Browser
+GenericName[pt_BR]=Navegador Web
+Keywords=Internet;WWW;Browser\\;Web;
+Exec=firefox %u
+Icon=firefox
+
+[Desktop Action new-window]
+Name=New Window
+Exec=firefox --new-window %u
+";
+
+    fn entry(exec: &str) -> DesktopEntry {
+        DesktopEntry {
+            id: "editor.desktop".to_string(),
+            path: PathBuf::from("/usr/share/applications/editor.desktop"),
+            name: "Text Editor".to_string(),
+            generic_name: None,
+            comment: None,
+            keywords: Vec::new(),
+            exec: exec.to_string(),
+            icon: Some("accessories-text-editor".to_string()),
+            terminal: false,
+            working_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_localized_keys() {
+        let path = Path::new("/usr/share/applications/firefox.desktop");
+
+        let entry = DesktopEntry::parse("firefox.desktop", path, FIREFOX, "pt_BR.UTF-8").unwrap();
+        assert_eq!(entry.name, "Navegador Firefox");
+        assert_eq!(entry.generic_name.as_deref(), Some("Navegador Web"));
+        assert_eq!(entry.keywords, vec!["Internet", "WWW", "Browser;Web"]);
+        // Keys from other groups are ignored
+        assert_eq!(entry.exec, "firefox %u");
+
+        let entry = DesktopEntry::parse("firefox.desktop", path, FIREFOX, "pt_PT").unwrap();
+        assert_eq!(entry.name, "Firefox (pt)");
+
+        let entry = DesktopEntry::parse("firefox.desktop", path, FIREFOX, "C").unwrap();
+        assert_eq!(entry.name, "Firefox");
+        assert_eq!(entry.generic_name.as_deref(), Some("Web Browser"));
+    }
+
+    #[test]
+    fn test_parse_skips_hidden_and_non_applications() {
+        let path = Path::new("/tmp/x.desktop");
+        let hidden = "[Desktop Entry]\nType=Application\nName=X\nExec=x\nNoDisplay=true\n";
+        let link = "[Desktop Entry]\nType=Link\nName=X\nURL=https://example.com\n";
+        let no_exec = "[Desktop Entry]\nType=Application\nName=X\n";
+
+        assert!(DesktopEntry::parse("x.desktop", path, hidden, "C").is_none());
+        assert!(DesktopEntry::parse("x.desktop", path, link, "C").is_none());
+        assert!(DesktopEntry::parse("x.desktop", path, no_exec, "C").is_none());
+    }
+
+    #[test]
+    fn test_expand_exec_field_codes() {
+        let files = ["/tmp/a b.txt", "/tmp/c.txt"];
+
+        assert_eq!(entry("gedit %U").command_line(&files).unwrap(), vec!["gedit", "/tmp/a b.txt", "/tmp/c.txt"]);
+        assert_eq!(entry("gedit %f").command_line(&[]).unwrap(), vec!["gedit"]);
+        assert_eq!(entry("gedit --file=%f").command_line(&files).unwrap(), vec!["gedit", "--file=/tmp/a b.txt"]);
+        assert_eq!(
+            entry("gedit %i --title=%c %k").command_line(&[]).unwrap(),
+            vec![
+                "gedit",
+                "--icon",
+                "accessories-text-editor",
+                "--title=Text Editor",
+                "/usr/share/applications/editor.desktop",
+            ]
+        );
+        assert_eq!(entry("printf 100%% %d").command_line(&[]).unwrap(), vec!["printf", "100%"]);
+    }
+
+    #[test]
+    fn test_expand_exec_quoting() {
+        assert_eq!(
+            entry(r#""/opt/My App/bin/app" --name "say \"hi\"" %u"#).command_line(&["x"]).unwrap(),
+            vec!["/opt/My App/bin/app", "--name", "say \"hi\"", "x"]
+        );
+        // "\\\\" in the file is "\\" after value unescaping, i.e. one literal backslash
+        let path = Path::new("/tmp/x.desktop");
+        let content = "[Desktop Entry]\nType=Application\nName=X\nExec=sh -c \"echo \\\\\\\\$HOME\"\n";
+        let parsed = DesktopEntry::parse("x.desktop", path, content, "C").unwrap();
+        assert_eq!(parsed.command_line(&[]).unwrap(), vec!["sh", "-c", "echo \\$HOME"]);
+
+        assert!(entry("app \"unterminated").command_line(&[]).is_err());
+    }
+
+    #[test]
+    fn test_discover_respects_precedence() {
+        let root = tempfile::tempdir().unwrap();
+        let user = root.path().join("user/applications");
+        let system = root.path().join("system/applications");
+        std::fs::create_dir_all(user.join("kde")).unwrap();
+        std::fs::create_dir_all(&system).unwrap();
+
+        let app = |name: &str| format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n", name, name.to_lowercase());
+        std::fs::write(system.join("editor.desktop"), app("System Editor")).unwrap();
+        std::fs::write(user.join("editor.desktop"), app("User Editor")).unwrap();
+        std::fs::write(system.join("ads.desktop"), app("Ads")).unwrap();
+        std::fs::write(user.join("ads.desktop"), "[Desktop Entry]\nType=Application\nName=Ads\nExec=ads\nHidden=true\n").unwrap();
+        std::fs::write(user.join("kde/konsole.desktop"), app("Konsole")).unwrap();
+
+        let entries = discover(&[user, system], "C");
+        let mut names: Vec<(&str, &str)> = entries.iter().map(|e| (e.id.as_str(), e.name.as_str())).collect();
+        names.sort();
+        assert_eq!(names, vec![("editor.desktop", "User Editor"), ("kde-konsole.desktop", "Konsole")]);
+    }
+}
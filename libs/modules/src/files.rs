@@ -0,0 +1,539 @@
+// Files module - File search backed by the persistent Tantivy index
+
+use crate::traits::*;
+use crate::base::BaseSearchModule;
+use async_trait::async_trait;
+use log::{info, warn, debug};
+use search_engine::{
+    FileIndexer, FileWatcher, IndexScheduler, IndexingControl, SchedulerConfig, SearchConfig, SearchEngine,
+    WatcherHandle,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+pub const FILES_MODULE_ID: &str = "files";
+
+/// Actions offered for every file result
+pub const FILE_ACTIONS: &[&str] = &["open", "reveal", "copy_path"];
+
+pub struct FilesModule {
+    base: BaseSearchModule,
+    config: SearchConfig,
+    engine: Option<Arc<SearchEngine>>,
+    indexing_task: Option<JoinHandle<()>>,
+    indexing: IndexingControl,
+    watcher: Option<WatcherHandle>,
+}
+
+impl FilesModule {
+    pub fn new(config: SearchConfig) -> Self {
+        let info = ModuleInfo {
+            id: FILES_MODULE_ID.to_string(),
+            name: "Files".to_string(),
+            description: "Search file names and contents in indexed folders".to_string(),
+            version: "1.0.0".to_string(),
+            author: "R5 Flowlight".to_string(),
+            enabled: true,
+            keywords: vec![
+                "files".to_string(),
+                "documents".to_string(),
+                "folders".to_string(),
+            ],
+        };
+
+        Self {
+            base: BaseSearchModule::new(info),
+            config,
+            engine: None,
+            indexing_task: None,
+            indexing: IndexingControl::new(),
+            watcher: None,
+        }
+    }
+
+    /// Share pause/resume and progress of background indexing. Must be called before `initialize`
+    pub fn set_indexing_control(&mut self, control: IndexingControl) {
+        self.indexing = control;
+    }
+
+    pub fn indexing_control(&self) -> &IndexingControl {
+        &self.indexing
+    }
+
+    /// Worker count and read throttling for background indexing, from the search config
+    pub fn scheduler_config(&self) -> SchedulerConfig {
+        SchedulerConfig::from_search_config(&self.config)
+    }
+
+    fn engine(&self) -> anyhow::Result<&Arc<SearchEngine>> {
+        self.engine.as_ref().ok_or_else(|| anyhow::anyhow!("Files module is not initialized"))
+    }
+
+    /// Settings override the matching `SearchConfig` fields ("indexed_paths", "max_file_size_mb", ...)
+    fn apply_settings(&mut self, settings: &HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+        let mut merged = serde_json::to_value(&self.config)?;
+        if let Some(fields) = merged.as_object_mut() {
+            for (key, value) in settings {
+                if fields.contains_key(key) {
+                    fields.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        self.config = serde_json::from_value(merged)?;
+        Ok(())
+    }
+
+    fn setting_bool(&self, key: &str, default: bool) -> bool {
+        self.base.settings.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
+    }
+
+    fn to_search_result(&self, result: search_engine::SearchResult) -> SearchResult {
+        let path = Path::new(&result.path);
+        let directory = path.parent().map(|p| p.display().to_string()).unwrap_or_default();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("path".to_string(), result.path.clone());
+        metadata.insert("directory".to_string(), directory.clone());
+        metadata.insert("actions".to_string(), FILE_ACTIONS.join(","));
+        metadata.insert("scope".to_string(), result.scope.clone());
+        if let Some(snippet) = &result.snippet {
+            metadata.insert("snippet".to_string(), snippet.clone());
+            if let Ok(highlights) = serde_json::to_string(&result.highlights) {
+                metadata.insert("highlights".to_string(), highlights);
+            }
+        }
+
+        // BM25 scores are unbounded; squash them into the 0-1 range used by other modules
+        let score = result.score.unwrap_or(0.0).max(0.0);
+
+        SearchResult {
+            id: result.path.clone(),
+            title: result.title,
+            description: result.snippet.unwrap_or(directory),
+            icon: Some(file_icon(path).to_string()),
+            action_type: "open".to_string(),
+            score: score / (score + 1.0),
+            metadata,
+        }
+    }
+}
+
+#[async_trait]
+impl SearchModule for FilesModule {
+    fn info(&self) -> ModuleInfo {
+        self.base.info()
+    }
+
+    async fn initialize(&mut self, config: HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+        self.apply_settings(&config)?;
+        self.base.initialize(config).await?;
+
+        let engine = match self.base.settings.get("index_path").and_then(|v| v.as_str()) {
+            Some(index_path) => SearchEngine::open_scoped(Path::new(index_path), &self.config.scopes).await?,
+            None => SearchEngine::open_scoped(&SearchEngine::default_index_path()?, &self.config.scopes).await?,
+        };
+        let engine = match search_engine::semantic::embedder_from_config(&self.config) {
+            Some(embedder) => engine.with_embedder(embedder, self.config.semantic_weight)?,
+            None => engine,
+        };
+        let engine = Arc::new(engine);
+        let indexer = FileIndexer::new(engine.clone(), self.config.clone());
+
+        if self.setting_bool("watch", true) {
+            match FileWatcher::new(indexer.clone()).start() {
+                Ok(handle) => self.watcher = Some(handle),
+                Err(e) => warn!("⚠️  File watcher unavailable, index will only refresh on restart: {}", e),
+            }
+        }
+
+        // Rescanning is cheap for unchanged files, but still too slow to block startup
+        let scheduler = IndexScheduler::new(indexer, self.scheduler_config(), self.indexing.clone());
+        self.indexing_task = Some(tokio::spawn(async move {
+            if let Err(e) = scheduler.run().await {
+                warn!("⚠️  File indexing failed: {}", e);
+            }
+        }));
+
+        self.engine = Some(engine);
+        info!("✅ Files module initialized for {} folders", self.config.indexed_paths.len());
+        Ok(())
+    }
+
+    async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>> {
+        // An empty query would match nothing useful in a file index
+        if query.text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let results = self.engine()?.search(&query.text, query.max_results).await?;
+        let results: Vec<SearchResult> = results.into_iter().map(|r| self.to_search_result(r)).collect();
+
+        debug!("🔍 Files search for '{}' returned {} results", query.text, results.len());
+        Ok(results)
+    }
+
+    /// Terms match by prefix; with semantic search on, similar files join as the query grows
+    fn matches_by_substring(&self) -> bool {
+        self.engine.as_ref().is_some_and(|engine| engine.embedder().is_none())
+    }
+
+    async fn execute_action(&self, result_id: &str, action_type: &str) -> anyhow::Result<()> {
+        // Only act on paths this index produced; the registry tries every module in turn
+        if self.engine()?.get_document(result_id)?.is_none() {
+            return Err(anyhow::anyhow!("'{}' is not an indexed file", result_id));
+        }
+
+        let path = PathBuf::from(result_id);
+        info!("⚡ Executing file action '{}' for '{}'", action_type, path.display());
+
+        match action_type {
+            "open" => open_path(&path),
+            "reveal" => reveal_path(&path),
+            "copy_path" => copy_to_clipboard(&path.to_string_lossy()),
+            _ => Err(anyhow::anyhow!("Unsupported action type: {}", action_type)),
+        }
+    }
+
+    async fn health_check(&self) -> anyhow::Result<bool> {
+        Ok(self.base.initialized && self.engine.is_some())
+    }
+
+    fn get_settings_schema(&self) -> HashMap<String, serde_json::Value> {
+        let mut schema = self.base.get_settings_schema();
+
+        schema.insert("indexed_paths".to_string(), serde_json::json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Folders to index"
+        }));
+
+        schema.insert("scopes".to_string(), serde_json::json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "roots": { "type": "array", "items": { "type": "string" } },
+                    "extensions": { "type": "array", "items": { "type": "string" } },
+                    "retention_days": { "type": "integer" }
+                },
+                "required": ["name", "roots"]
+            },
+            "description": "Named search scopes with their own folders and index, searchable with scope:name"
+        }));
+
+        schema.insert("semantic_search".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": false,
+            "description": "Rank results by meaning as well as keywords using document embeddings"
+        }));
+
+        schema.insert("embedding_command".to_string(), serde_json::json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Local embedding model command: kept running, reads one JSON string per line on stdin and answers each with a JSON array of floats"
+        }));
+
+        schema.insert("embedding_dimensions".to_string(), serde_json::json!({
+            "type": "integer",
+            "default": 384,
+            "description": "Number of dimensions produced by the embedding model"
+        }));
+
+        schema.insert("semantic_weight".to_string(), serde_json::json!({
+            "type": "number",
+            "default": 0.4,
+            "description": "How much semantic similarity counts against keyword score (0-1)"
+        }));
+
+        schema.insert("max_file_size_mb".to_string(), serde_json::json!({
+            "type": "number",
+            "default": 50,
+            "description": "Files larger than this are indexed by name only"
+        }));
+
+        schema.insert("excluded_patterns".to_string(), serde_json::json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Gitignore-style patterns to skip, e.g. *.log or build/"
+        }));
+
+        schema.insert("respect_ignore_files".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": true,
+            "description": "Skip files listed in .gitignore and .ignore files"
+        }));
+
+        schema.insert("include_hidden".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": false,
+            "description": "Index hidden files and folders"
+        }));
+
+        schema.insert("index_archive_listings".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": false,
+            "description": "Index the names of files inside zip archives"
+        }));
+
+        schema.insert("indexing_workers".to_string(), serde_json::json!({
+            "type": "integer",
+            "default": 4,
+            "description": "Files indexed in parallel during background scans"
+        }));
+
+        schema.insert("background_throttling".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": true,
+            "description": "Limit disk reads during background scans"
+        }));
+
+        schema.insert("throttle_bytes_per_sec".to_string(), serde_json::json!({
+            "type": "integer",
+            "default": 8388608,
+            "description": "Maximum read rate while throttled, in bytes per second"
+        }));
+
+        schema.insert("watch".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": true,
+            "description": "Keep the index up to date as files change"
+        }));
+
+        schema.insert("index_path".to_string(), serde_json::json!({
+            "type": "string",
+            "description": "Directory where the index is stored"
+        }));
+
+        schema
+    }
+
+    async fn update_settings(&mut self, settings: HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+        self.apply_settings(&settings)?;
+        self.base.update_settings(settings).await?;
+
+        info!("⚙️  Files module settings updated, changes apply on the next restart");
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> anyhow::Result<()> {
+        if let Some(task) = self.indexing_task.take() {
+            task.abort();
+        }
+        self.watcher = None;
+        if let Some(engine) = self.engine.take() {
+            engine.commit_changes().await?;
+        }
+        self.base.cleanup().await?;
+
+        info!("🧹 Files module cleaned up");
+        Ok(())
+    }
+}
+
+fn file_icon(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "pdf" => "📕",
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp" => "🖼️",
+        "mp3" | "wav" | "flac" | "ogg" | "m4a" => "🎵",
+        "mp4" | "mkv" | "mov" | "avi" | "webm" => "🎬",
+        "zip" | "tar" | "gz" | "7z" | "rar" => "📦",
+        "xls" | "xlsx" | "csv" | "ods" => "📊",
+        "ppt" | "pptx" | "odp" => "📽️",
+        "rs" | "js" | "ts" | "py" | "go" | "java" | "c" | "cpp" | "h" | "sh" => "💻",
+        _ => "📄",
+    }
+}
+
+fn spawn(program: &str, args: &[&str]) -> anyhow::Result<()> {
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run '{}': {}", program, e))?;
+    Ok(())
+}
+
+fn open_path(path: &Path) -> anyhow::Result<()> {
+    let path = path.to_string_lossy();
+
+    if cfg!(target_os = "macos") {
+        spawn("open", &[&path])
+    } else if cfg!(target_os = "windows") {
+        spawn("cmd", &["/C", "start", "", &path])
+    } else {
+        spawn("xdg-open", &[&path])
+    }
+}
+
+fn reveal_path(path: &Path) -> anyhow::Result<()> {
+    let path_str = path.to_string_lossy();
+
+    if cfg!(target_os = "macos") {
+        spawn("open", &["-R", &path_str])
+    } else if cfg!(target_os = "windows") {
+        spawn("explorer", &[&format!("/select,{}", path_str)])
+    } else {
+        // Ask the file manager to select the file; fall back to opening its folder
+        let uri = format!("array:string:{}", file_uri(path));
+        let shown = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+                &uri,
+                "string:",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        if shown {
+            Ok(())
+        } else {
+            let parent = path.parent().unwrap_or(path);
+            spawn("xdg-open", &[&parent.to_string_lossy()])
+        }
+    }
+}
+
+fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let candidates: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if cfg!(target_os = "windows") {
+        &[("clip", &[])]
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        &[("wl-copy", &[]), ("xclip", &["-selection", "clipboard"])]
+    } else {
+        &[("xclip", &["-selection", "clipboard"]), ("xsel", &["--clipboard", "--input"])]
+    };
+
+    for (program, args) in candidates {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            info!("📋 Copied to clipboard: {}", text);
+            return Ok(());
+        }
+    }
+
+    Err(anyhow::anyhow!("No clipboard tool available"))
+}
+
+/// `file://` URI with everything outside the unreserved set percent-encoded
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery {
+            text: text.to_string(),
+            module_filter: None,
+            max_results: 10,
+            timeout_ms: 3000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_module_searches_index() {
+        let corpus_dir = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        let (corpus, index) = (corpus_dir.path(), index_dir.path().join("index"));
+        std::fs::write(corpus.join("invoice.txt"), "Invoice for the quarterly budget").unwrap();
+
+        let mut module = FilesModule::new(SearchConfig::default());
+        module.initialize(HashMap::from([
+            ("indexed_paths".to_string(), serde_json::json!([corpus])),
+            ("ignored_paths".to_string(), serde_json::json!([])),
+            ("index_path".to_string(), serde_json::json!(index)),
+            ("watch".to_string(), serde_json::json!(false)),
+        ])).await.unwrap();
+        assert!(module.health_check().await.unwrap());
+        assert!(module.matches_by_substring());
+
+        let mut results = Vec::new();
+        let mut progress = module.indexing_control().subscribe();
+        tokio::time::timeout(std::time::Duration::from_secs(10), progress.wait_for(|p| p.done)).await.unwrap().unwrap();
+        assert_eq!(module.indexing_control().progress().files_indexed, 1);
+        for _ in 0..50 {
+            results = module.search(&query("budget")).await.unwrap();
+            if !results.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.title, "invoice.txt");
+        assert_eq!(result.id, corpus.join("invoice.txt").to_string_lossy());
+        assert!(result.score > 0.0 && result.score < 1.0);
+        assert!(result.metadata["snippet"].contains("budget"));
+
+        // Ids that did not come from this index are left to other modules
+        assert!(module.execute_action("calculator", "open").await.is_err());
+        assert!(module.execute_action(&result.id, "delete").await.is_err());
+        assert!(module.search(&query("")).await.unwrap().is_empty());
+
+        module.cleanup().await.unwrap();
+    }
+
+    #[test]
+    fn test_file_uri_escapes_path() {
+        assert_eq!(file_uri(Path::new("/home/me/My Notes #1.txt")), "file:///home/me/My%20Notes%20%231.txt");
+    }
+
+    #[test]
+    fn test_saved_performance_settings_reach_scheduler() {
+        // As written by the settings UI: `[search]` next to the other app sections
+        let saved = SearchConfig { indexing_workers: 2, background_throttling: false, ..SearchConfig::default() };
+        let mut app_config = toml::Table::new();
+        app_config.insert("general".to_string(), toml::Value::Table(toml::Table::new()));
+        app_config.insert("search".to_string(), toml::Value::try_from(&saved).unwrap());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, toml::to_string(&app_config).unwrap()).unwrap();
+
+        let module = FilesModule::new(SearchConfig::load_from(&path).unwrap());
+        assert_eq!(module.scheduler_config(), SchedulerConfig { workers: 2, max_bytes_per_sec: None });
+    }
+}
@@ -0,0 +1,322 @@
+// Frecency store - learns which results users actually pick for a query
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use shared_core::utils::{current_timestamp_s, data_dir, write_atomic};
+
+pub type SharedFrecencyStore = Arc<RwLock<FrecencyStore>>;
+
+const MAX_PREFIX_CHARS: usize = 32;
+const MAX_TIMESTAMPS_PER_ENTRY: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrecencyConfig {
+    /// Age (seconds) at which a selection counts half as much
+    pub half_life_secs: u64,
+    /// Maximum number of (query prefix, result) pairs kept
+    pub max_entries: usize,
+    /// Maximum score added to a result by frecency
+    pub max_boost: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub query_prefix: String,
+    pub result_id: String,
+    /// Selection timestamps in seconds, oldest first
+    pub timestamps: Vec<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UsageFile {
+    entries: Vec<UsageEntry>,
+}
+
+pub struct FrecencyStore {
+    path: Option<PathBuf>,
+    config: FrecencyConfig,
+    entries: Vec<UsageEntry>,
+}
+
+impl Default for FrecencyConfig {
+    fn default() -> Self {
+        Self {
+            half_life_secs: 14 * 24 * 60 * 60,
+            max_entries: 2000,
+            max_boost: 0.3,
+        }
+    }
+}
+
+impl UsageEntry {
+    fn decayed_weight(&self, now: u64, half_life_secs: u64) -> f32 {
+        let half_life = half_life_secs.max(1) as f32;
+        self.timestamps
+            .iter()
+            .map(|&t| 0.5f32.powf(now.saturating_sub(t) as f32 / half_life))
+            .sum()
+    }
+}
+
+impl FrecencyStore {
+    /// In-memory store, nothing is persisted
+    pub fn new(config: FrecencyConfig) -> Self {
+        Self {
+            path: None,
+            config,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Load the store from `path`, starting empty if the file is missing or unreadable
+    pub fn load(path: PathBuf, config: FrecencyConfig) -> Self {
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<UsageFile>(&content) {
+                Ok(file) => file.entries,
+                Err(e) => {
+                    warn!("⚠️  Ignoring corrupt usage file {:?}: {}", path, e);
+                    Vec::new()
+                }
+            },
+            Err(_) => Vec::new(),
+        };
+
+        info!("📈 Loaded {} usage entries from {:?}", entries.len(), path);
+        let mut store = Self {
+            path: Some(path),
+            config,
+            entries,
+        };
+        store.enforce_size_cap(current_timestamp_s());
+        store
+    }
+
+    /// Load the store from the default location in the data directory
+    pub fn load_default() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::load(path, FrecencyConfig::default()),
+            None => Self::new(FrecencyConfig::default()),
+        }
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("usage.json"))
+    }
+
+    pub fn into_shared(self) -> SharedFrecencyStore {
+        Arc::new(RwLock::new(self))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record that `result_id` was chosen after typing `query`
+    pub fn record(&mut self, query: &str, result_id: &str) {
+        self.record_at(query, result_id, current_timestamp_s());
+    }
+
+    pub fn record_at(&mut self, query: &str, result_id: &str, now: u64) {
+        let query_prefix = normalize_query(query);
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.query_prefix == query_prefix && e.result_id == result_id)
+        {
+            Some(entry) => {
+                entry.timestamps.push(now);
+                if entry.timestamps.len() > MAX_TIMESTAMPS_PER_ENTRY {
+                    let excess = entry.timestamps.len() - MAX_TIMESTAMPS_PER_ENTRY;
+                    entry.timestamps.drain(..excess);
+                }
+            }
+            None => self.entries.push(UsageEntry {
+                query_prefix,
+                result_id: result_id.to_string(),
+                timestamps: vec![now],
+            }),
+        }
+
+        self.enforce_size_cap(now);
+        debug!("📈 Recorded selection of '{}' for query '{}'", result_id, query);
+    }
+
+    /// Remove every usage entry for a result
+    pub fn forget(&mut self, result_id: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.result_id != result_id);
+        let removed = self.entries.len() != before;
+
+        if removed {
+            info!("🧽 Forgot usage history for '{}'", result_id);
+        }
+        removed
+    }
+
+    /// Score boost in `[0, max_boost]` for `result_id` given the current query
+    pub fn boost(&self, query: &str, result_id: &str) -> f32 {
+        self.boost_at(query, result_id, current_timestamp_s())
+    }
+
+    pub fn boost_at(&self, query: &str, result_id: &str, now: u64) -> f32 {
+        let query = normalize_query(query);
+
+        let weight: f32 = self
+            .entries
+            .iter()
+            .filter(|e| e.result_id == result_id)
+            .map(|e| prefix_affinity(&query, &e.query_prefix) * e.decayed_weight(now, self.config.half_life_secs))
+            .sum();
+
+        // Saturate so a handful of recent picks gets close to the max boost
+        self.config.max_boost * (1.0 - (-weight / 2.0).exp())
+    }
+
+    /// Persist the store if it was loaded from a file
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let file = UsageFile {
+            entries: self.entries.clone(),
+        };
+        write_atomic(path, serde_json::to_string(&file)?.as_bytes())?;
+        Ok(())
+    }
+
+    fn enforce_size_cap(&mut self, now: u64) {
+        if self.entries.len() <= self.config.max_entries {
+            return;
+        }
+
+        let half_life = self.config.half_life_secs;
+        self.entries.sort_by(|a, b| {
+            b.decayed_weight(now, half_life)
+                .partial_cmp(&a.decayed_weight(now, half_life))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.entries.truncate(self.config.max_entries);
+    }
+}
+
+/// How strongly a selection made for `stored` applies to `query`
+fn prefix_affinity(query: &str, stored: &str) -> f32 {
+    if query == stored {
+        1.0
+    } else if !query.is_empty() && (stored.starts_with(query) || query.starts_with(stored)) {
+        0.6
+    } else {
+        // General popularity still counts a little
+        0.1
+    }
+}
+
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(MAX_PREFIX_CHARS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_boost_grows_with_usage() {
+        let mut store = FrecencyStore::new(FrecencyConfig::default());
+        let now = 1_000 * DAY;
+
+        assert_eq!(store.boost_at("term", "terminal", now), 0.0);
+
+        store.record_at("term", "terminal", now);
+        let once = store.boost_at("term", "terminal", now);
+        store.record_at("term", "terminal", now);
+        let twice = store.boost_at("term", "terminal", now);
+
+        assert!(once > 0.0);
+        assert!(twice > once);
+        assert!(twice <= FrecencyConfig::default().max_boost);
+    }
+
+    #[test]
+    fn test_prefix_matching() {
+        let mut store = FrecencyStore::new(FrecencyConfig::default());
+        let now = 1_000 * DAY;
+        store.record_at("Terminal", "terminal", now);
+
+        let exact = store.boost_at("terminal", "terminal", now);
+        let prefix = store.boost_at("ter", "terminal", now);
+        let unrelated = store.boost_at("calc", "terminal", now);
+
+        assert!(exact > prefix);
+        assert!(prefix > unrelated);
+        assert!(unrelated > 0.0);
+    }
+
+    #[test]
+    fn test_decay() {
+        let mut store = FrecencyStore::new(FrecencyConfig::default());
+        let now = 1_000 * DAY;
+        store.record_at("calc", "calculator", now - 60 * DAY);
+        store.record_at("calc", "terminal", now);
+
+        assert!(store.boost_at("calc", "terminal", now) > store.boost_at("calc", "calculator", now));
+    }
+
+    #[test]
+    fn test_size_cap_and_forget() {
+        let config = FrecencyConfig {
+            max_entries: 3,
+            ..FrecencyConfig::default()
+        };
+        let mut store = FrecencyStore::new(config);
+        let now = 1_000 * DAY;
+
+        for (i, id) in ["a", "b", "c", "d"].iter().enumerate() {
+            store.record_at("q", id, now + i as u64);
+        }
+        store.record_at("q", "d", now + 10);
+        store.record_at("q", "e", now + 20);
+
+        assert_eq!(store.len(), 3);
+        assert!(store.boost_at("q", "d", now + 20) > 0.0);
+
+        assert!(store.forget("d"));
+        assert!(!store.forget("d"));
+        assert_eq!(store.boost_at("q", "d", now + 20), 0.0);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+
+        let mut store = FrecencyStore::load(path.clone(), FrecencyConfig::default());
+        store.record("term", "terminal");
+        store.save().unwrap();
+
+        let reloaded = FrecencyStore::load(path.clone(), FrecencyConfig::default());
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.boost("term", "terminal") > 0.0);
+    }
+}
@@ -3,9 +3,17 @@
 pub mod traits;
 pub mod registry;
 pub mod daily;
+pub mod files;
+pub mod apps;
+pub mod desktop_entry;
 pub mod base;
+pub mod frecency;
 
 pub use traits::*;
 pub use registry::*;
 pub use daily::*;
-pub use base::*;
\ No newline at end of file
+pub use files::*;
+pub use apps::*;
+pub use desktop_entry::DesktopEntry;
+pub use base::*;
+pub use frecency::*;
\ No newline at end of file
@@ -2,7 +2,11 @@
 
 use crate::traits::*;
 use crate::daily::DailyModule;
+use crate::files::FilesModule;
+use crate::apps::AppsModule;
+use crate::frecency::SharedFrecencyStore;
 use log::{info, error, warn, debug};
+use search_engine::IndexingControl;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -11,6 +15,8 @@ pub struct ModuleRegistry {
     modules: HashMap<String, Arc<RwLock<Box<dyn SearchModule>>>>,
     enabled_modules: Vec<String>,
     default_module: Option<String>,
+    frecency: Option<SharedFrecencyStore>,
+    indexing: IndexingControl,
 }
 
 impl ModuleRegistry {
@@ -19,9 +25,29 @@ impl ModuleRegistry {
             modules: HashMap::new(),
             enabled_modules: Vec::new(),
             default_module: None,
+            frecency: None,
+            indexing: IndexingControl::new(),
         }
     }
     
+    /// Enable frecency ranking of the merged search results
+    pub fn set_frecency_store(&mut self, store: SharedFrecencyStore) {
+        self.frecency = Some(store);
+    }
+    
+    pub fn frecency_store(&self) -> Option<SharedFrecencyStore> {
+        self.frecency.clone()
+    }
+    
+    /// Share control of background file indexing. Must be called before `initialize_default_modules`.
+    pub fn set_indexing_control(&mut self, control: IndexingControl) {
+        self.indexing = control;
+    }
+    
+    pub fn indexing_control(&self) -> IndexingControl {
+        self.indexing.clone()
+    }
+    
     pub async fn initialize_default_modules(&mut self) -> anyhow::Result<()> {
         info!("🏗️  Initializing default modules...");
         
@@ -34,6 +60,36 @@ impl ModuleRegistry {
         self.register_module(module_id.clone(), Box::new(daily_module)).await?;
         self.set_default_module(module_id).await?;
         
+        // Register files module with the folders, exclusions and limits saved from the settings UI;
+        // a broken index must not take the other modules down
+        let search_config = search_engine::SearchConfig::load_saved().unwrap_or_else(|e| {
+            warn!("⚠️  Could not load saved search configuration, using defaults: {}", e);
+            search_engine::SearchConfig::default()
+        });
+        let mut files_module = FilesModule::new(search_config);
+        files_module.set_indexing_control(self.indexing.clone());
+        match files_module.initialize(HashMap::new()).await {
+            Ok(()) => {
+                let module_id = files_module.info().id.clone();
+                self.register_module(module_id, Box::new(files_module)).await?;
+            }
+            Err(e) => {
+                error!("❌ Failed to initialize files module: {}", e);
+            }
+        }
+        
+        // Register apps module
+        let mut apps_module = AppsModule::new();
+        match apps_module.initialize(HashMap::new()).await {
+            Ok(()) => {
+                let module_id = apps_module.info().id.clone();
+                self.register_module(module_id, Box::new(apps_module)).await?;
+            }
+            Err(e) => {
+                error!("❌ Failed to initialize apps module: {}", e);
+            }
+        }
+        
         info!("✅ Default modules initialized successfully");
         Ok(())
     }
@@ -107,28 +163,54 @@ impl ModuleRegistry {
         }
     }
     
+    /// Modules whose results for a query can be narrowed down to those of a longer one
+    pub async fn substring_modules(&self) -> Vec<String> {
+        let mut modules = Vec::new();
+        for (module_id, module) in &self.modules {
+            if module.read().await.matches_by_substring() {
+                modules.push(module_id.clone());
+            }
+        }
+        modules
+    }
+    
     pub async fn search_all_modules(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>> {
+        self.search_all_modules_with_context(&ModuleSearchContext::new(query.clone())).await
+    }
+    
+    pub async fn search_all_modules_with_context(&self, context: &ModuleSearchContext) -> anyhow::Result<Vec<SearchResult>> {
+        let results = self.collect_results(context, &[]).await?;
+        Ok(self.rank_results(&context.query, results))
+    }
+    
+    /// Results of the requested module (or of every enabled one), tagged with the
+    /// module that produced them but not ranked; modules in `skip` are not searched
+    pub async fn collect_results(&self, context: &ModuleSearchContext, skip: &[String]) -> anyhow::Result<Vec<SearchResult>> {
+        let query = &context.query;
         let mut all_results = Vec::new();
         
         // If a specific module is requested, search only that module
         if let Some(module_filter) = &query.module_filter {
+            if skip.contains(module_filter) {
+                return Ok(all_results);
+            }
             if let Some(module_arc) = self.modules.get(module_filter) {
                 let module = module_arc.read().await;
-                let results = module.search(query).await?;
-                all_results.extend(results);
+                let results = module.search_with_context(context).await?;
+                all_results.extend(tag_module(results, module_filter));
                 debug!("🔍 Search in module '{}' returned {} results", module_filter, all_results.len());
             } else {
                 warn!("⚠️  Requested module '{}' not found", module_filter);
             }
         } else {
             // Search enabled modules
-            for module_id in &self.enabled_modules {
+            for module_id in self.enabled_modules.iter().filter(|id| !skip.contains(id)) {
                 if let Some(module_arc) = self.modules.get(module_id) {
                     let module = module_arc.read().await;
-                    match module.search(query).await {
+                    match module.search_with_context(context).await {
                         Ok(results) => {
                             debug!("🔍 Module '{}' returned {} results", module_id, results.len());
-                            all_results.extend(results);
+                            all_results.extend(tag_module(results, module_id));
                         }
                         Err(e) => {
                             error!("❌ Search failed in module '{}': {}", module_id, e);
@@ -138,17 +220,28 @@ impl ModuleRegistry {
             }
         }
         
+        Ok(all_results)
+    }
+    
+    /// Merge results collected for `query` into the final list
+    pub fn rank_results(&self, query: &SearchQuery, mut results: Vec<SearchResult>) -> Vec<SearchResult> {
+        // Boost results the user picked before for similar queries; only here,
+        // so each result is boosted exactly once
+        self.apply_frecency(&query.text, &mut results);
+        
         // Sort results by score (descending)
-        all_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
         
         // Limit results
-        all_results.truncate(query.max_results);
+        results.truncate(query.max_results);
         
-        info!("🔍 Total search results: {}", all_results.len());
-        Ok(all_results)
+        info!("🔍 Total search results: {}", results.len());
+        results
     }
     
-    pub async fn execute_action(&self, result_id: &str, action_type: &str) -> anyhow::Result<()> {
+    /// Run the action of a result; `query` is what was typed when it was picked,
+    /// recorded in the usage history
+    pub async fn execute_action(&self, query: &str, result_id: &str, action_type: &str) -> anyhow::Result<()> {
         // Try to find which module contains this result and execute the action
         for (module_id, module_arc) in &self.modules {
             let module = module_arc.read().await;
@@ -158,6 +251,7 @@ impl ModuleRegistry {
             match module.execute_action(result_id, action_type).await {
                 Ok(_) => {
                     info!("✅ Action executed successfully by module '{}'", module_id);
+                    self.record_selection(query, result_id);
                     return Ok(());
                 }
                 Err(_) => {
@@ -171,6 +265,43 @@ impl ModuleRegistry {
         Err(anyhow::anyhow!("No module found to handle the action"))
     }
     
+    /// Drop the usage history of a result so it stops being boosted
+    pub fn forget_result(&self, result_id: &str) -> anyhow::Result<bool> {
+        let Some(store) = &self.frecency else {
+            return Ok(false);
+        };
+        
+        let mut store = store.write().map_err(|_| anyhow::anyhow!("Frecency store lock poisoned"))?;
+        let removed = store.forget(result_id);
+        if removed {
+            store.save()?;
+        }
+        Ok(removed)
+    }
+    
+    fn apply_frecency(&self, query: &str, results: &mut [SearchResult]) {
+        let Some(store) = self.frecency.as_ref().and_then(|store| store.read().ok()) else {
+            return;
+        };
+        
+        for result in results.iter_mut() {
+            result.score += store.boost(query, &result.id);
+        }
+    }
+    
+    fn record_selection(&self, query: &str, result_id: &str) {
+        let Some(store) = &self.frecency else {
+            return;
+        };
+        
+        if let Ok(mut store) = store.write() {
+            store.record(query, result_id);
+            if let Err(e) = store.save() {
+                warn!("⚠️  Failed to persist usage history: {}", e);
+            }
+        }
+    }
+    
     pub async fn health_check_all(&self) -> HashMap<String, bool> {
         let mut health_status = HashMap::new();
         
@@ -232,4 +363,137 @@ impl Default for ModuleRegistry {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Record which module produced each result (metadata "module"), e.g. for per-module cache TTLs
+fn tag_module(mut results: Vec<SearchResult>, module_id: &str) -> Vec<SearchResult> {
+    for result in &mut results {
+        result.metadata.entry("module".to_string()).or_insert_with(|| module_id.to_string());
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frecency::{FrecencyConfig, FrecencyStore};
+    use async_trait::async_trait;
+
+    /// Answers every query with one result and accepts any action on it
+    struct StubModule;
+
+    #[async_trait]
+    impl SearchModule for StubModule {
+        fn info(&self) -> ModuleInfo {
+            ModuleInfo {
+                id: "stub".to_string(),
+                name: "Stub".to_string(),
+                description: String::new(),
+                version: "0.1.0".to_string(),
+                author: String::new(),
+                enabled: true,
+                keywords: Vec::new(),
+            }
+        }
+
+        async fn initialize(&mut self, _config: HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn search(&self, _query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>> {
+            Ok(vec![SearchResult {
+                id: "notes".to_string(),
+                title: "Notes".to_string(),
+                description: String::new(),
+                icon: None,
+                action_type: "open".to_string(),
+                score: 0.5,
+                metadata: HashMap::new(),
+            }])
+        }
+
+        async fn execute_action(&self, result_id: &str, _action_type: &str) -> anyhow::Result<()> {
+            if result_id == "notes" { Ok(()) } else { Err(anyhow::anyhow!("unknown result")) }
+        }
+
+        async fn health_check(&self) -> anyhow::Result<bool> {
+            Ok(true)
+        }
+
+        fn get_settings_schema(&self) -> HashMap<String, serde_json::Value> {
+            HashMap::new()
+        }
+
+        async fn update_settings(&mut self, _settings: HashMap<String, serde_json::Value>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn cleanup(&mut self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery { text: text.to_string(), module_filter: None, max_results: 10, timeout_ms: 1000 }
+    }
+
+    #[tokio::test]
+    async fn test_selection_is_recorded_with_the_query_it_was_picked_for() {
+        let store = FrecencyStore::new(FrecencyConfig::default()).into_shared();
+        let mut registry = ModuleRegistry::new();
+        registry.set_frecency_store(store.clone());
+        registry.register_module("stub".to_string(), Box::new(StubModule)).await.unwrap();
+
+        // Another overlay searches in between; the pick still belongs to "not"
+        registry.search_all_modules(&query("not")).await.unwrap();
+        registry.search_all_modules(&query("calendar")).await.unwrap();
+        registry.execute_action("not", "notes", "open").await.unwrap();
+
+        let store = store.read().unwrap();
+        // An unrelated query only gets the general popularity share
+        assert!(store.boost("not", "notes") > store.boost("calendar", "notes"));
+    }
+
+    #[tokio::test]
+    async fn test_boost_stays_out_of_result_metadata() {
+        let store = FrecencyStore::new(FrecencyConfig::default()).into_shared();
+        let mut registry = ModuleRegistry::new();
+        registry.set_frecency_store(store.clone());
+        registry.register_module("stub".to_string(), Box::new(StubModule)).await.unwrap();
+
+        registry.execute_action("not", "notes", "open").await.unwrap();
+        let results = registry.search_all_modules(&query("not")).await.unwrap();
+        assert!(results[0].score > 0.5);
+        // Clients only see which module answered
+        assert_eq!(results[0].metadata.keys().collect::<Vec<_>>(), ["module"]);
+    }
+
+    #[tokio::test]
+    async fn test_forget_result_drops_usage_history() {
+        let store = FrecencyStore::new(FrecencyConfig::default()).into_shared();
+        let mut registry = ModuleRegistry::new();
+        registry.set_frecency_store(store.clone());
+        registry.register_module("stub".to_string(), Box::new(StubModule)).await.unwrap();
+
+        registry.execute_action("not", "notes", "open").await.unwrap();
+        assert!(registry.forget_result("notes").unwrap());
+        assert!(!registry.forget_result("notes").unwrap());
+        assert_eq!(store.read().unwrap().boost("not", "notes"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_collect_skips_modules_and_leaves_ranking_to_rank_results() {
+        let store = FrecencyStore::new(FrecencyConfig::default()).into_shared();
+        let mut registry = ModuleRegistry::new();
+        registry.set_frecency_store(store.clone());
+        registry.register_module("stub".to_string(), Box::new(StubModule)).await.unwrap();
+        registry.execute_action("not", "notes", "open").await.unwrap();
+
+        let context = ModuleSearchContext::new(query("not"));
+        assert!(registry.collect_results(&context, &["stub".to_string()]).await.unwrap().is_empty());
+
+        let collected = registry.collect_results(&context, &[]).await.unwrap();
+        assert_eq!(collected[0].score, 0.5);
+        assert!(registry.rank_results(&context.query, collected)[0].score > 0.5);
+    }
+}
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchResult {
     pub id: String,
     pub title: String,
@@ -45,6 +45,21 @@ pub trait SearchModule: Send + Sync {
     /// Perform a search with the given query
     async fn search(&self, query: &SearchQuery) -> anyhow::Result<Vec<SearchResult>>;
     
+    /// Perform a search with the full search context (preferences, locale, foreground app).
+    /// Modules that don't care about the context can rely on the default, which delegates to `search`.
+    async fn search_with_context(&self, context: &ModuleSearchContext) -> anyhow::Result<Vec<SearchResult>> {
+        self.search(&context.query).await
+    }
+    
+    /// Whether extending a query only drops results (prefix or subsequence matching),
+    /// so the results for "bud" hold every match for "budg". Callers may then answer
+    /// "budg" from the results for "bud" that contain each query word in their title,
+    /// description or `path` metadata; modules whose answers change as the query
+    /// grows (calculators, semantic ranking) keep the default.
+    fn matches_by_substring(&self) -> bool {
+        false
+    }
+    
     /// Execute an action for a specific result
     async fn execute_action(&self, result_id: &str, action_type: &str) -> anyhow::Result<()>;
     
@@ -61,17 +76,49 @@ pub trait SearchModule: Send + Sync {
     async fn cleanup(&mut self) -> anyhow::Result<()>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleSearchContext {
     pub query: SearchQuery,
     pub user_preferences: HashMap<String, String>,
+    /// BCP 47 style locale tag, e.g. "pt-BR"
+    pub locale: String,
+    /// Module currently selected by the user, if any
+    pub active_module: Option<String>,
     pub system_info: SystemInfo,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
     pub platform: String,
     pub current_app: Option<String>,
     pub screen_resolution: (u32, u32),
     pub current_workspace: Option<String>,
+}
+
+impl ModuleSearchContext {
+    /// Build a context with no preferences and unknown system information
+    pub fn new(query: SearchQuery) -> Self {
+        Self {
+            active_module: query.module_filter.clone(),
+            query,
+            user_preferences: HashMap::new(),
+            locale: "en-US".to_string(),
+            system_info: SystemInfo::default(),
+        }
+    }
+    
+    pub fn preference(&self, key: &str) -> Option<&str> {
+        self.user_preferences.get(key).map(|value| value.as_str())
+    }
+}
+
+impl Default for SystemInfo {
+    fn default() -> Self {
+        Self {
+            platform: std::env::consts::OS.to_string(),
+            current_app: None,
+            screen_resolution: (0, 0),
+            current_workspace: None,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,370 @@
+//! Configuração da indexação de arquivos
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::exclusion::Exclusions;
+
+/// Configurações de busca
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// Diretórios para indexar
+    pub indexed_paths: Vec<PathBuf>,
+
+    /// Diretórios para ignorar: absolutos excluem a pasta inteira,
+    /// relativos são padrões como os de `excluded_patterns`
+    pub ignored_paths: Vec<PathBuf>,
+
+    /// Padrões no formato do `.gitignore` (ex.: `*.log`, `build/`, `!keep.log`)
+    #[serde(default = "default_excluded_patterns")]
+    pub excluded_patterns: Vec<String>,
+
+    /// Respeitar arquivos `.gitignore` e `.ignore` nas pastas indexadas
+    #[serde(default = "default_true")]
+    pub respect_ignore_files: bool,
+
+    /// Extensões de arquivo para indexar
+    pub indexed_extensions: Vec<String>,
+
+    /// Tamanho máximo de arquivo (MB)
+    pub max_file_size_mb: u64,
+
+    /// Intervalo de atualização do índice (segundos)
+    pub index_update_interval: u64,
+
+    /// Incluir arquivos ocultos na indexação
+    pub include_hidden: bool,
+
+    /// Arquivos indexados em paralelo na varredura em segundo plano
+    #[serde(default = "default_indexing_workers")]
+    pub indexing_workers: usize,
+
+    /// Limitar a leitura de disco da varredura em segundo plano
+    #[serde(default = "default_true")]
+    pub background_throttling: bool,
+
+    /// Taxa máxima de leitura com `background_throttling` (bytes por segundo)
+    #[serde(default = "default_throttle_bytes_per_sec")]
+    pub throttle_bytes_per_sec: u64,
+
+    /// Tempo máximo para extrair o texto de um documento (ms)
+    #[serde(default = "default_extraction_timeout_ms")]
+    pub extraction_timeout_ms: u64,
+
+    /// Texto extraído além deste tamanho não é indexado (KB)
+    #[serde(default = "default_max_extracted_text_kb")]
+    pub max_extracted_text_kb: usize,
+
+    /// Indexar os nomes dos arquivos dentro de ZIPs (requer "zip" em `indexed_extensions`)
+    #[serde(default)]
+    pub index_archive_listings: bool,
+
+    /// Escopos nomeados, cada um com índice próprio; o que não cai em nenhum
+    /// vai para o escopo padrão
+    #[serde(default)]
+    pub scopes: Vec<ScopeConfig>,
+
+    /// Gerar embeddings dos documentos e combinar similaridade com BM25 na busca
+    #[serde(default)]
+    pub semantic_search: bool,
+
+    /// Modelo local de embeddings: processo que fica aberto, lê um texto por
+    /// linha (string JSON) e responde um array JSON por linha; vazio usa o
+    /// embedder por hashing embutido
+    #[serde(default)]
+    pub embedding_command: Vec<String>,
+
+    /// Tempo máximo de resposta do comando de embeddings (ms); depois disso o processo é encerrado
+    #[serde(default = "default_embedding_timeout_ms")]
+    pub embedding_timeout_ms: u64,
+
+    /// Dimensões dos vetores gerados pelo embedder
+    #[serde(default = "default_embedding_dimensions")]
+    pub embedding_dimensions: usize,
+
+    /// Peso da similaridade semântica no ranking (0 = só BM25, 1 = só vetores)
+    #[serde(default = "default_semantic_weight")]
+    pub semantic_weight: f32,
+}
+
+/// Escopo de busca (ex.: "work", "notes") com pastas e regras próprias
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScopeConfig {
+    /// Nome usado no filtro `scope:` e como diretório do índice (letras, números, `-` e `_`)
+    pub name: String,
+
+    /// Pastas do escopo; são indexadas mesmo fora de `indexed_paths`
+    pub roots: Vec<PathBuf>,
+
+    /// Extensões indexadas no escopo; vazio usa `indexed_extensions`
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// Arquivos sem modificação há mais dias que isso saem do índice
+    #[serde(default)]
+    pub retention_days: Option<u64>,
+}
+
+impl ScopeConfig {
+    /// Escopo de `path` entre `scopes`: o da pasta raiz mais específica que o contém
+    pub fn find<'a>(scopes: &'a [ScopeConfig], path: &Path) -> Option<&'a ScopeConfig> {
+        scopes
+            .iter()
+            .flat_map(|scope| scope.roots.iter().map(move |root| (scope, root)))
+            .filter(|(_, root)| !root.as_os_str().is_empty() && path.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count())
+            .map(|(scope, _)| scope)
+    }
+
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            indexed_paths: vec![
+                dirs::home_dir().unwrap_or_default(),
+                dirs::desktop_dir().unwrap_or_default(),
+                dirs::document_dir().unwrap_or_default(),
+            ],
+            ignored_paths: vec![
+                PathBuf::from(".git"),
+                PathBuf::from("node_modules"),
+                PathBuf::from(".DS_Store"),
+            ],
+            excluded_patterns: default_excluded_patterns(),
+            respect_ignore_files: true,
+            indexed_extensions: vec![
+                "txt".to_string(), "md".to_string(), "pdf".to_string(),
+                "doc".to_string(), "docx".to_string(), "xls".to_string(),
+                "xlsx".to_string(), "ppt".to_string(), "pptx".to_string(),
+                "js".to_string(), "ts".to_string(), "py".to_string(),
+                "rs".to_string(), "go".to_string(), "java".to_string(),
+                "c".to_string(), "cpp".to_string(), "h".to_string(),
+                "html".to_string(), "css".to_string(), "json".to_string(),
+            ],
+            max_file_size_mb: 50,
+            index_update_interval: 300, // 5 minutos
+            include_hidden: false, // Não incluir arquivos ocultos por padrão
+            indexing_workers: default_indexing_workers(),
+            background_throttling: true,
+            throttle_bytes_per_sec: default_throttle_bytes_per_sec(),
+            extraction_timeout_ms: default_extraction_timeout_ms(),
+            max_extracted_text_kb: default_max_extracted_text_kb(),
+            index_archive_listings: false,
+            scopes: Vec::new(),
+            semantic_search: false,
+            embedding_command: Vec::new(),
+            embedding_timeout_ms: default_embedding_timeout_ms(),
+            embedding_dimensions: default_embedding_dimensions(),
+            semantic_weight: default_semantic_weight(),
+        }
+    }
+}
+
+/// Padrões excluídos por padrão: pastas de dependências e de build
+pub fn default_excluded_patterns() -> Vec<String> {
+    ["node_modules", "target", "dist", "build"].iter().map(|p| p.to_string()).collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_indexing_workers() -> usize {
+    4
+}
+
+fn default_throttle_bytes_per_sec() -> u64 {
+    8 * 1024 * 1024 // 8 MB/s
+}
+
+fn default_extraction_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_max_extracted_text_kb() -> usize {
+    1024
+}
+
+fn default_embedding_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_embedding_dimensions() -> usize {
+    384
+}
+
+fn default_semantic_weight() -> f32 {
+    0.4
+}
+
+/// Arquivo de configuração da aplicação; a interface salva a seção `[search]` nele
+pub fn app_config_path() -> PathBuf {
+    match dirs::config_dir() {
+        Some(config_dir) => config_dir.join("r5-flowlight").join("config.toml"),
+        None => PathBuf::from("config.toml"),
+    }
+}
+
+impl SearchConfig {
+    /// Configuração salva pela interface em `app_config_path()`
+    pub fn load_saved() -> anyhow::Result<Self> {
+        Self::load_from(&app_config_path())
+    }
+
+    /// Seção `[search]` de um `config.toml`; a configuração padrão se o arquivo ou a seção não existirem
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct SavedConfig {
+            search: Option<SearchConfig>,
+        }
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let saved: SavedConfig = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid search configuration in {:?}: {}", path, e))?;
+        Ok(saved.search.unwrap_or_default())
+    }
+
+    /// Regras de exclusão compiladas; guarde o resultado ao verificar muitos caminhos
+    pub fn exclusions(&self) -> Exclusions {
+        Exclusions::from_config(self)
+    }
+
+    /// Verifica se um arquivo deve ser indexado com base nas configurações
+    pub fn should_index_file(&self, path: &Path) -> bool {
+        !self.should_ignore_path(path) && self.matches_file_filters(path)
+    }
+
+    /// Pastas varridas: `indexed_paths` mais as raízes dos escopos, sem repetição
+    pub fn roots(&self) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = Vec::new();
+        let scope_roots = self.scopes.iter().flat_map(|scope| scope.roots.iter());
+        for root in self.indexed_paths.iter().chain(scope_roots) {
+            if !root.as_os_str().is_empty() && !roots.contains(root) {
+                roots.push(root.clone());
+            }
+        }
+        roots
+    }
+
+    /// Escopo nomeado ao qual `path` pertence, se houver
+    pub fn scope_for(&self, path: &Path) -> Option<&ScopeConfig> {
+        ScopeConfig::find(&self.scopes, path)
+    }
+
+    /// Verifica extensão, tamanho máximo e retenção do escopo, sem considerar as regras de exclusão
+    pub fn matches_file_filters(&self, path: &Path) -> bool {
+        let scope = self.scope_for(path);
+
+        // Verificar extensão do arquivo
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            let allowed = match scope {
+                Some(scope) if !scope.extensions.is_empty() => {
+                    scope.extensions.iter().any(|allowed| allowed.eq_ignore_ascii_case(ext))
+                }
+                _ => self.should_index_extension(ext),
+            };
+            if !allowed {
+                return false;
+            }
+        }
+
+        if let Ok(metadata) = std::fs::metadata(path) {
+            // Verificar tamanho máximo do arquivo (se aplicável)
+            let max_size_bytes = self.max_file_size_mb * 1024 * 1024;
+            if metadata.len() > max_size_bytes {
+                return false;
+            }
+
+            // Arquivos antigos demais para o escopo
+            if let Some(days) = scope.and_then(|scope| scope.retention_days) {
+                let max_age = std::time::Duration::from_secs(days * 24 * 60 * 60);
+                let age = metadata.modified().ok().and_then(|modified| modified.elapsed().ok());
+                if age.is_some_and(|age| age > max_age) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Verifica se um diretório deve ser indexado com base nas configurações
+    pub fn should_index_dir(&self, path: &Path) -> bool {
+        // Verificar se o caminho está na lista de ignorados
+        if self.should_ignore_path(path) {
+            return false;
+        }
+
+        // Verificar se o diretório está dentro de alguma pasta indexada
+        self.roots().iter().any(|root| path.starts_with(root))
+    }
+
+    /// Verifica se um caminho deve ser ignorado com base nas configurações
+    ///
+    /// Compila as regras a cada chamada; varreduras usam as do `FileIndexer`
+    pub fn should_ignore_path(&self, path: &Path) -> bool {
+        self.exclusions().is_excluded_with_ancestors(path, path.is_dir())
+    }
+
+    /// Verifica se uma extensão de arquivo deve ser indexada
+    pub fn should_index_extension(&self, extension: &str) -> bool {
+        // Se não houver extensões especificadas, indexa todas
+        if self.indexed_extensions.is_empty() {
+            return true;
+        }
+
+        // Verificar se a extensão está na lista de extensões indexadas
+        self.indexed_extensions
+            .iter()
+            .any(|ext| ext.eq_ignore_ascii_case(extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_saved_search_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert_eq!(SearchConfig::load_from(&path).unwrap().indexing_workers, default_indexing_workers());
+
+        let saved = SearchConfig {
+            indexing_workers: 1,
+            background_throttling: false,
+            excluded_patterns: vec!["*.iso".to_string()],
+            scopes: vec![ScopeConfig {
+                name: "work".to_string(),
+                roots: vec![PathBuf::from("/srv/work")],
+                extensions: Vec::new(),
+                retention_days: None,
+            }],
+            ..SearchConfig::default()
+        };
+        // Outras seções do arquivo da aplicação são ignoradas
+        let mut table = toml::Table::new();
+        table.insert("general".to_string(), toml::Value::Table(toml::Table::from_iter([(
+            "theme".to_string(),
+            toml::Value::String("dark".to_string()),
+        )])));
+        table.insert("search".to_string(), toml::Value::try_from(&saved).unwrap());
+        std::fs::write(&path, toml::to_string(&table).unwrap()).unwrap();
+
+        let loaded = SearchConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.indexing_workers, 1);
+        assert!(!loaded.background_throttling);
+        assert_eq!(loaded.excluded_patterns, vec!["*.iso"]);
+        assert_eq!(loaded.scopes, saved.scopes);
+
+        std::fs::write(&path, "[search]\nindexing_workers = \"many\"\n").unwrap();
+        assert!(SearchConfig::load_from(&path).is_err());
+    }
+}
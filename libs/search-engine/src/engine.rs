@@ -0,0 +1,1397 @@
+//! Gerenciador do índice Tantivy: abertura, escrita e busca
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tantivy::{
+    collector::{Count, DocSetCollector, TopDocs},
+    directory::{error::OpenReadError, Directory, MmapDirectory, OwnedBytes},
+    doc,
+    query::{AllQuery, BooleanQuery, Occur, Query, RangeQuery, RegexQuery, TermQuery, TermSetQuery},
+    schema::{Field, IndexRecordOption, OwnedValue},
+    snippet::SnippetGenerator,
+    DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError, Term,
+};
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::config::ScopeConfig;
+use crate::lock::{IndexLock, LOCK_FILE_NAME};
+use crate::query_builder::QueryBuilder;
+use crate::query_syntax::{ParsedQuery, QueryFilter};
+use crate::schema::{Embedding, FileFingerprint, IndexedDocument, SearchIndexSchema};
+use crate::semantic::{self, Embedder, VectorIndex, MIN_SEMANTIC_SIMILARITY};
+use crate::snapshot::{self, PathRemap, SnapshotManifest, SnapshotScope, SnapshotWriter, SNAPSHOT_FORMAT_VERSION};
+
+/// Escopo dos arquivos fora das pastas de qualquer `ScopeConfig`
+pub const DEFAULT_SCOPE: &str = "default";
+
+/// Tamanho máximo do trecho retornado em `SearchResult::snippet`
+const SNIPPET_MAX_CHARS: usize = 160;
+
+/// Tentativas de copiar os segmentos de um escopo enquanto merges os trocam
+const SNAPSHOT_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SearchResult {
+    pub path: String,
+    pub title: String,
+    pub score: Option<f32>,
+    /// Trecho do conteúdo em volta dos termos encontrados
+    pub snippet: Option<String>,
+    /// Intervalos (em bytes) de `snippet` que casaram com a busca
+    pub highlights: Vec<(usize, usize)>,
+    /// Escopo (índice) de onde o resultado veio
+    pub scope: String,
+}
+
+/// Estado do índice para diagnóstico
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexStats {
+    pub doc_count: u64,
+    /// Documentos apagados que ainda ocupam espaço até o próximo merge
+    pub deleted_doc_count: u64,
+    pub segment_count: usize,
+    pub size_on_disk: u64,
+    /// Momento do último commit (segundos Unix), se registrado
+    pub last_commit: Option<i64>,
+    pub roots: Vec<RootStats>,
+    pub scopes: Vec<ScopeStats>,
+}
+
+/// Documentos indexados dentro de uma pasta raiz
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RootStats {
+    pub path: PathBuf,
+    pub doc_count: u64,
+}
+
+/// Resultado da verificação de integridade
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct IntegrityReport {
+    /// Arquivos de segmento cujo checksum não confere
+    pub damaged_files: Vec<PathBuf>,
+    /// Arquivos de segmento referenciados pelo meta.json mas ausentes no disco
+    pub missing_files: Vec<PathBuf>,
+}
+
+impl IndexStats {
+    fn new(roots: &[PathBuf], scope_count: usize) -> Self {
+        Self {
+            doc_count: 0,
+            deleted_doc_count: 0,
+            segment_count: 0,
+            size_on_disk: 0,
+            last_commit: None,
+            roots: roots.iter().map(|root| RootStats { path: root.clone(), doc_count: 0 }).collect(),
+            scopes: Vec::with_capacity(scope_count),
+        }
+    }
+
+    /// Soma os números de um escopo aos totais
+    fn add_scope(
+        &mut self,
+        name: &str,
+        path: &Path,
+        index: &Index,
+        reader: &IndexReader,
+        path_field: Field,
+        vector_count: u64,
+    ) -> Result<()> {
+        let searcher = reader.searcher();
+        let segment_readers = searcher.segment_readers();
+        for root in &mut self.roots {
+            root.doc_count += searcher.search(&under_dir_query(&root.path, path_field)?, &Count)? as u64;
+        }
+
+        let size_on_disk = dir_size(path)?;
+        let last_commit = index.load_metas()?.payload.and_then(|p| p.parse().ok());
+        self.doc_count += searcher.num_docs();
+        self.deleted_doc_count += segment_readers.iter().map(|r| r.num_deleted_docs() as u64).sum::<u64>();
+        self.segment_count += segment_readers.len();
+        self.size_on_disk += size_on_disk;
+        self.last_commit = self.last_commit.max(last_commit);
+        self.scopes.push(ScopeStats { name: name.to_string(), doc_count: searcher.num_docs(), vector_count, size_on_disk });
+        Ok(())
+    }
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.damaged_files.is_empty() && self.missing_files.is_empty()
+    }
+
+    /// Confere os arquivos dos segmentos ativos de um escopo
+    fn check_scope(&mut self, name: &str, path: &Path, index: &Index) -> Result<()> {
+        let mut missing_files = Vec::new();
+        for segment in index.searchable_segment_metas()? {
+            for file in segment.list_files() {
+                // O arquivo de deleções só existe se o segmento tiver deleções
+                let is_delete_file = file.extension().is_some_and(|ext| ext == "del");
+                if is_delete_file && !segment.has_deletes() {
+                    continue;
+                }
+                if !path.join(&file).exists() {
+                    missing_files.push(Path::new(name).join(file));
+                }
+            }
+        }
+
+        // Arquivos ausentes fariam a validação falhar antes de chegar aos demais
+        if missing_files.is_empty() {
+            let damaged = index.validate_checksum()?;
+            self.damaged_files.extend(damaged.into_iter().map(|file| Path::new(name).join(file)));
+        }
+        self.missing_files.extend(missing_files);
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        self.damaged_files.sort();
+        if self.is_healthy() {
+            info!("Search index integrity check passed");
+        } else {
+            warn!(
+                "Search index integrity check failed: {} damaged, {} missing files",
+                self.damaged_files.len(),
+                self.missing_files.len()
+            );
+        }
+    }
+}
+
+/// Documentos e espaço em disco de um escopo
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScopeStats {
+    pub name: String,
+    pub doc_count: u64,
+    /// Documentos com vetor na busca semântica
+    pub vector_count: u64,
+    pub size_on_disk: u64,
+}
+
+/// Alteração no índice vetorial, aplicada junto com o commit do Tantivy
+enum VectorChange {
+    Upsert(String, Vec<f32>),
+    Remove(String),
+    RemovePrefix(String),
+    Clear,
+}
+
+/// Embedder e peso da similaridade no ranking
+struct Semantic {
+    embedder: Arc<dyn Embedder>,
+    weight: f32,
+}
+
+/// Índice Tantivy de um escopo, em `<index_path>/<nome>`
+struct ScopeIndex {
+    name: String,
+    path: PathBuf,
+    index: Index,
+    reader: IndexReader,
+    writer: AsyncMutex<IndexWriter>,
+    query_builder: QueryBuilder,
+    vectors: RwLock<VectorIndex>,
+    /// Alterações desde o último commit; só registradas com a busca semântica ligada
+    pending_vectors: Mutex<Vec<VectorChange>>,
+}
+
+impl ScopeIndex {
+    async fn open(path: PathBuf, name: &str, schema: &SearchIndexSchema) -> Result<Self> {
+        tokio::fs::create_dir_all(&path).await?;
+        let index = match Index::open_or_create(MmapDirectory::open(&path)?, schema.schema.clone()) {
+            Ok(index) => index,
+            Err(TantivyError::SchemaError(e)) => {
+                // Índice criado por uma versão anterior do schema: recriar do zero
+                warn!("Search index schema changed ({}), rebuilding index at {:?}", e, path);
+                clear_index_dir(&path).await?;
+                Index::create_in_dir(&path, schema.schema.clone())?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Self::from_index(path, name, index, schema)
+    }
+
+    async fn recreate(path: PathBuf, name: &str, schema: &SearchIndexSchema) -> Result<Self> {
+        tokio::fs::create_dir_all(&path).await?;
+        clear_index_dir(&path).await?;
+        let index = Index::create_in_dir(&path, schema.schema.clone())?;
+        Self::from_index(path, name, index, schema)
+    }
+
+    fn from_index(path: PathBuf, name: &str, index: Index, schema: &SearchIndexSchema) -> Result<Self> {
+        let writer = index.writer(50_000_000)?;
+        let reader = index.reader()?;
+        let fields = &schema.fields;
+        let query_builder = QueryBuilder::new(&index, fields.title, fields.body, fields.path)?;
+        Ok(Self {
+            name: name.to_string(),
+            path,
+            index,
+            reader,
+            writer: AsyncMutex::new(writer),
+            query_builder,
+            vectors: RwLock::new(VectorIndex::new(0)),
+            pending_vectors: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Vetores gravados por `embedder` no último commit
+    fn load_vectors(&self, embedder: &dyn Embedder) -> Result<VectorIndex> {
+        let mut vectors = VectorIndex::new(embedder.dimensions());
+        let searcher = self.reader.searcher();
+        for segment in searcher.segment_readers() {
+            let fast_fields = segment.fast_fields();
+            let (Some(paths), Some(models), Some(embeddings)) =
+                (fast_fields.str("path")?, fast_fields.str("embedding_model")?, fast_fields.bytes("embedding")?)
+            else {
+                continue;
+            };
+
+            let (mut path, mut model, mut bytes) = (String::new(), String::new(), Vec::new());
+            for doc in segment.doc_ids_alive() {
+                let (Some(path_ord), Some(model_ord), Some(embedding_ord)) =
+                    (paths.term_ords(doc).next(), models.term_ords(doc).next(), embeddings.term_ords(doc).next())
+                else {
+                    continue;
+                };
+                model.clear();
+                models.ord_to_str(model_ord, &mut model)?;
+                if model != embedder.name() {
+                    continue;
+                }
+                path.clear();
+                bytes.clear();
+                paths.ord_to_str(path_ord, &mut path)?;
+                embeddings.ord_to_bytes(embedding_ord, &mut bytes)?;
+                vectors.insert(&path, semantic::vector_from_bytes(&bytes));
+            }
+        }
+        Ok(vectors)
+    }
+
+    fn apply_vector_changes(&self) {
+        let changes = std::mem::take(&mut *self.pending_vectors.lock().unwrap());
+        if changes.is_empty() {
+            return;
+        }
+        let mut vectors = self.vectors.write().unwrap();
+        for change in changes {
+            match change {
+                VectorChange::Upsert(path, vector) => vectors.insert(&path, vector),
+                VectorChange::Remove(path) => vectors.remove(&path),
+                VectorChange::RemovePrefix(prefix) => vectors.remove_prefix(&prefix),
+                VectorChange::Clear => vectors.clear(),
+            }
+        }
+    }
+
+    /// `meta.json` e os arquivos dos segmentos de um mesmo commit
+    ///
+    /// Merges em segundo plano podem trocar os segmentos e apagar arquivos
+    /// durante a leitura; nesse caso a lista é refeita. Os arquivos ficam
+    /// mapeados em memória, então continuam legíveis mesmo se apagados depois.
+    fn snapshot_files(&self) -> Result<Vec<(PathBuf, OwnedBytes)>> {
+        // Direto no disco: o diretório do `Index` devolve os arquivos sem o rodapé
+        let directory = MmapDirectory::open(&self.path)?;
+        let meta_path = Path::new("meta.json");
+        for _ in 0..SNAPSHOT_ATTEMPTS {
+            let meta = directory.atomic_read(meta_path)?;
+            let mut files = vec![(meta_path.to_path_buf(), OwnedBytes::new(meta.clone()))];
+            let mut complete = true;
+            'segments: for segment in self.index.searchable_segment_metas()? {
+                for file in segment.list_files() {
+                    let is_delete_file = file.extension().is_some_and(|ext| ext == "del");
+                    if is_delete_file && !segment.has_deletes() {
+                        continue;
+                    }
+                    match directory.open_read(&file) {
+                        Ok(slice) => files.push((file, slice.read_bytes()?)),
+                        Err(OpenReadError::FileDoesNotExist(_)) => {
+                            complete = false;
+                            break 'segments;
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+            if complete && directory.atomic_read(meta_path)? == meta {
+                return Ok(files);
+            }
+            debug!("Segments of scope '{}' changed during snapshot, retrying", self.name);
+        }
+        anyhow::bail!("Search index scope '{}' kept changing during the snapshot", self.name)
+    }
+
+}
+
+/// Índices de arquivos: um escopo padrão mais um por `ScopeConfig`
+///
+/// Cada documento fica no escopo da pasta raiz mais específica que o contém
+/// (ver `ScopeConfig::find`); buscas consultam todos os escopos, ou só os do
+/// filtro `scope:`, e juntam os resultados pela pontuação.
+pub struct SearchEngine {
+    index_path: PathBuf,
+    /// O escopo padrão é sempre o primeiro
+    scopes: Vec<ScopeIndex>,
+    scope_configs: Vec<ScopeConfig>,
+    schema: SearchIndexSchema,
+    semantic: Option<Semantic>,
+    _lock: IndexLock,
+}
+
+impl SearchEngine {
+    pub async fn new() -> Result<Self> {
+        Self::open_in_dir(&Self::default_index_path()?).await
+    }
+
+    /// Diretório padrão do índice nos dados locais do usuário
+    pub fn default_index_path() -> Result<PathBuf> {
+        Ok(directories::ProjectDirs::from("com", "r5hub", "flowlight")
+            .ok_or_else(|| anyhow::anyhow!("Could not find a valid home directory"))?
+            .data_local_dir()
+            .join("search_index"))
+    }
+
+    /// Abre (ou cria) o índice em um diretório específico, só com o escopo padrão
+    ///
+    /// Falha com `IndexLockedError` se outro processo estiver com o índice aberto.
+    pub async fn open_in_dir(index_path: &Path) -> Result<Self> {
+        Self::open_scoped(index_path, &[]).await
+    }
+
+    /// Abre (ou cria) o índice com um subdiretório por escopo
+    ///
+    /// Diretórios de escopos fora de `scopes` ficam no disco, mas não são consultados.
+    pub async fn open_scoped(index_path: &Path, scopes: &[ScopeConfig]) -> Result<Self> {
+        let lock = IndexLock::acquire(index_path)?;
+        Self::open_locked(index_path, scopes, lock).await
+    }
+
+    async fn open_locked(index_path: &Path, scopes: &[ScopeConfig], lock: IndexLock) -> Result<Self> {
+        let names = scope_names(scopes)?;
+
+        // Antes dos escopos, o índice ficava direto em `index_path`
+        if index_path.join("meta.json").exists() {
+            warn!("Migrating search index at {:?} to per-scope layout; it will be rebuilt", index_path);
+            clear_index_dir(index_path).await?;
+        }
+
+        let schema = SearchIndexSchema::new();
+        let mut indexes = Vec::with_capacity(names.len());
+        for name in &names {
+            indexes.push(ScopeIndex::open(index_path.join(name), name, &schema).await?);
+        }
+        Self::from_scopes(index_path, indexes, scopes, schema, lock)
+    }
+
+    /// Apaga o índice em `index_path` e cria um vazio no lugar, mesmo que esteja corrompido
+    pub async fn recreate_in_dir(index_path: &Path) -> Result<Self> {
+        Self::recreate_scoped(index_path, &[]).await
+    }
+
+    /// Como `recreate_in_dir`, com um índice vazio para cada escopo
+    pub async fn recreate_scoped(index_path: &Path, scopes: &[ScopeConfig]) -> Result<Self> {
+        let lock = IndexLock::acquire(index_path)?;
+        let names = scope_names(scopes)?;
+        warn!("Recreating search index at {:?}", index_path);
+        clear_index_dir(index_path).await?;
+
+        let schema = SearchIndexSchema::new();
+        let mut indexes = Vec::with_capacity(names.len());
+        for name in &names {
+            indexes.push(ScopeIndex::recreate(index_path.join(name), name, &schema).await?);
+        }
+        Self::from_scopes(index_path, indexes, scopes, schema, lock)
+    }
+
+    fn from_scopes(
+        index_path: &Path,
+        scopes: Vec<ScopeIndex>,
+        scope_configs: &[ScopeConfig],
+        schema: SearchIndexSchema,
+        lock: IndexLock,
+    ) -> Result<Self> {
+        info!("SearchEngine initialized with {} scope(s)", scopes.len());
+        Ok(Self {
+            index_path: index_path.to_path_buf(),
+            scopes,
+            scope_configs: scope_configs.to_vec(),
+            schema,
+            semantic: None,
+            _lock: lock,
+        })
+    }
+
+    /// Liga a busca semântica: carrega os vetores já indexados por `embedder`
+    /// e passa a combinar similaridade e BM25 com `semantic_weight` (0 a 1)
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>, semantic_weight: f32) -> Result<Self> {
+        for scope in &self.scopes {
+            let vectors = scope.load_vectors(embedder.as_ref())?;
+            debug!("Loaded {} vectors for scope '{}'", vectors.len(), scope.name);
+            *scope.vectors.write().unwrap() = vectors;
+        }
+        info!("Semantic search enabled with embedder '{}'", embedder.name());
+        self.semantic = Some(Semantic { embedder, weight: semantic_weight.clamp(0.0, 1.0) });
+        Ok(self)
+    }
+
+    /// Embedder da busca semântica, se ligada
+    pub fn embedder(&self) -> Option<&Arc<dyn Embedder>> {
+        self.semantic.as_ref().map(|semantic| &semantic.embedder)
+    }
+
+    fn record_vector_change(&self, scope: &ScopeIndex, change: VectorChange) {
+        if self.semantic.is_some() {
+            scope.pending_vectors.lock().unwrap().push(change);
+        }
+    }
+
+    /// Diretório onde o índice está armazenado
+    pub fn index_path(&self) -> &Path {
+        &self.index_path
+    }
+
+    /// Nomes dos escopos, começando pelo padrão
+    pub fn scope_names(&self) -> Vec<&str> {
+        self.scopes.iter().map(|scope| scope.name.as_str()).collect()
+    }
+
+    /// Escopo onde `path` é indexado
+    fn scope_for(&self, path: &Path) -> &ScopeIndex {
+        ScopeConfig::find(&self.scope_configs, path)
+            .and_then(|config| self.scopes.iter().find(|scope| scope.name == config.name.to_lowercase()))
+            .unwrap_or(&self.scopes[0])
+    }
+
+    /// Insere ou substitui o documento de `document.path` (efetivo após o commit)
+    pub async fn upsert_document(&self, document: IndexedDocument) -> Result<()> {
+        debug!("Upserting document: {}", &document.path);
+        let fields = &self.schema.fields;
+        let path = Path::new(&document.path);
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let parent = path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+        let target = self.scope_for(path);
+
+        // O caminho é a chave: a versão anterior é descartada no mesmo commit,
+        // inclusive de outro escopo se as pastas dos escopos mudaram
+        let key = Term::from_field_text(fields.path, &document.path);
+        for scope in self.scopes.iter().filter(|scope| scope.name != target.name) {
+            scope.writer.lock().await.delete_term(key.clone());
+            self.record_vector_change(scope, VectorChange::Remove(document.path.clone()));
+        }
+
+        let current_vector = document
+            .embedding
+            .as_ref()
+            .filter(|embedding| self.embedder().is_some_and(|embedder| embedder.name() == embedding.model))
+            .map(|embedding| embedding.vector.clone());
+        let mut tantivy_doc = doc!(
+            fields.extension => extension,
+            fields.parent => parent,
+            fields.path => document.path.clone(),
+            fields.title => document.title,
+            fields.body => document.body,
+            fields.modified => document.fingerprint.modified,
+            fields.size => document.fingerprint.size,
+            fields.content_hash => document.fingerprint.content_hash
+        );
+        if let Some(embedding) = &document.embedding {
+            tantivy_doc.add_bytes(fields.embedding, semantic::vector_to_bytes(&embedding.vector));
+            tantivy_doc.add_text(fields.embedding_model, &embedding.model);
+        }
+
+        let writer = target.writer.lock().await;
+        writer.delete_term(key);
+        writer.add_document(tantivy_doc)?;
+        let change = match current_vector {
+            Some(vector) => VectorChange::Upsert(document.path, vector),
+            None => VectorChange::Remove(document.path),
+        };
+        self.record_vector_change(target, change);
+        Ok(())
+    }
+
+    /// Documento indexado para `path` no último commit, se houver
+    pub fn get_document(&self, path_str: &str) -> Result<Option<IndexedDocument>> {
+        let searcher = self.scope_for(Path::new(path_str)).reader.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(self.schema.fields.path, path_str),
+            IndexRecordOption::Basic,
+        );
+        let Some((_, doc_address)) = searcher.search(&query, &TopDocs::with_limit(1))?.into_iter().next() else {
+            return Ok(None);
+        };
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        Ok(self.doc_to_indexed_document(&doc))
+    }
+
+    /// Remove o documento indexado para `path` (efetivo após o commit)
+    pub async fn remove_document(&self, path_str: &str) -> Result<()> {
+        debug!("Removing document: {}", path_str);
+        for scope in &self.scopes {
+            scope.writer.lock().await.delete_term(Term::from_field_text(self.schema.fields.path, path_str));
+            self.record_vector_change(scope, VectorChange::Remove(path_str.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Remove todos os documentos dentro de um diretório (efetivo após o commit)
+    pub async fn remove_documents_under(&self, dir: &Path) -> Result<()> {
+        debug!("Removing documents under: {}", dir.display());
+        let prefix = dir.join("").to_string_lossy().to_string();
+        for scope in &self.scopes {
+            scope.writer.lock().await.delete_query(Box::new(self.under_dir_query(dir)?))?;
+            self.record_vector_change(scope, VectorChange::RemovePrefix(prefix.clone()));
+        }
+        Ok(())
+    }
+
+    /// Caminhos indexados dentro de um diretório no último commit, em todos os escopos
+    pub fn indexed_paths_under(&self, dir: &Path) -> Result<Vec<String>> {
+        let mut paths = Vec::new();
+        for scope in &self.scopes {
+            let searcher = scope.reader.searcher();
+            let addresses = searcher.search(&self.under_dir_query(dir)?, &DocSetCollector)?;
+            paths.reserve(addresses.len());
+            for address in addresses {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                if let Some(OwnedValue::Str(path)) = doc.get_first(self.schema.fields.path) {
+                    paths.push(path.clone());
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    fn under_dir_query(&self, dir: &Path) -> Result<RegexQuery> {
+        under_dir_query(dir, self.schema.fields.path)
+    }
+
+    /// Remove todos os documentos do índice
+    pub async fn clear(&self) -> Result<()> {
+        for scope in &self.scopes {
+            scope.writer.lock().await.delete_all_documents()?;
+            self.record_vector_change(scope, VectorChange::Clear);
+        }
+        self.commit_changes().await?;
+        info!("Search index cleared");
+        Ok(())
+    }
+
+    pub async fn commit_changes(&self) -> Result<()> {
+        // O payload guarda o horário do commit para `stats`
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        for scope in &self.scopes {
+            let mut writer = scope.writer.lock().await;
+            let mut commit = writer.prepare_commit()?;
+            commit.set_payload(&timestamp);
+            commit.commit()?;
+            // Com o writer ainda travado, para nenhuma alteração nova entrar no meio
+            scope.apply_vector_changes();
+            drop(writer);
+            // Torna o commit visível imediatamente para as próximas buscas
+            scope.reader.reload()?;
+        }
+        Ok(())
+    }
+
+    /// Grava em `archive` um snapshot de todos os escopos, com `roots` no manifesto
+    ///
+    /// As alterações pendentes são gravadas antes; os writers ficam travados
+    /// durante a cópia, então os escopos refletem o mesmo momento.
+    pub async fn export_snapshot(&self, archive: &Path, roots: &[PathBuf]) -> Result<SnapshotManifest> {
+        self.commit_changes().await?;
+        let mut writers = Vec::with_capacity(self.scopes.len());
+        for scope in &self.scopes {
+            writers.push(scope.writer.lock().await);
+        }
+
+        let manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            created_at: chrono::Utc::now().timestamp(),
+            roots: roots.to_vec(),
+            scope_configs: self.scope_configs.clone(),
+            scopes: self
+                .scopes
+                .iter()
+                .map(|scope| SnapshotScope { name: scope.name.clone(), doc_count: scope.reader.searcher().num_docs() })
+                .collect(),
+            embedding_model: self.embedder().map(|embedder| embedder.name().to_string()),
+        };
+        let mut writer = SnapshotWriter::create(archive, &manifest)?;
+        for scope in &self.scopes {
+            for (file, bytes) in scope.snapshot_files()? {
+                writer.add_file(&scope.name, &file, bytes.as_slice())?;
+            }
+        }
+        writer.finish()?;
+        drop(writers);
+
+        let doc_count: u64 = manifest.scopes.iter().map(|scope| scope.doc_count).sum();
+        info!("Exported search index snapshot with {} documents to {:?}", doc_count, archive);
+        Ok(manifest)
+    }
+
+    /// Substitui o índice em `index_path` pelo snapshot em `archive`
+    ///
+    /// Os caminhos gravados passam por `remaps`, e os documentos são movidos
+    /// para os escopos de `scopes` (escopos do snapshot que não existem aqui
+    /// são incorporados aos demais). O índice atual só é apagado depois que o
+    /// snapshot inteiro foi extraído e validado.
+    pub async fn import_snapshot(
+        archive: &Path,
+        index_path: &Path,
+        scopes: &[ScopeConfig],
+        remaps: &[PathRemap],
+    ) -> Result<(Self, SnapshotManifest)> {
+        let lock = IndexLock::acquire(index_path)?;
+        scope_names(scopes)?;
+
+        let mut staging_name = index_path.file_name().unwrap_or_default().to_os_string();
+        staging_name.push(".import");
+        let staging = index_path.with_file_name(staging_name);
+        if staging.exists() {
+            tokio::fs::remove_dir_all(&staging).await?;
+        }
+        let manifest = match snapshot::extract_snapshot(archive, &staging) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                let _ = tokio::fs::remove_dir_all(&staging).await;
+                return Err(e);
+            }
+        };
+
+        warn!("Replacing search index at {:?} with snapshot {:?}", index_path, archive);
+        clear_index_dir(index_path).await?;
+        for scope in &manifest.scopes {
+            tokio::fs::rename(staging.join(&scope.name), index_path.join(&scope.name)).await?;
+        }
+        tokio::fs::remove_dir_all(&staging).await?;
+
+        let engine = Self::open_locked(index_path, scopes, lock).await?;
+        let relocated = engine.relocate_documents(&manifest, remaps).await?;
+        info!("Imported search index snapshot from {:?} ({} documents relocated)", archive, relocated);
+        Ok((engine, manifest))
+    }
+
+    /// Aplica `remaps` aos caminhos importados e move cada documento para o
+    /// escopo certo nesta configuração; devolve quantos foram regravados
+    async fn relocate_documents(&self, manifest: &SnapshotManifest, remaps: &[PathRemap]) -> Result<u64> {
+        let mut relocated = 0;
+        for imported in &manifest.scopes {
+            // Escopos que não existem nesta configuração são abertos só para a leitura
+            let local = self.scopes.iter().find(|scope| scope.name == imported.name);
+            let foreign = match local {
+                Some(_) => None,
+                None => Some(ScopeIndex::open(self.index_path.join(&imported.name), &imported.name, &self.schema).await?),
+            };
+            let Some(scope) = local.or(foreign.as_ref()) else {
+                continue;
+            };
+
+            let searcher = scope.reader.searcher();
+            for address in searcher.search(&AllQuery, &DocSetCollector)? {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                let Some(mut document) = self.doc_to_indexed_document(&doc) else {
+                    continue;
+                };
+                let path = PathRemap::apply_all(remaps, Path::new(&document.path)).to_string_lossy().to_string();
+                let moved_scope = foreign.is_some() || self.scope_for(Path::new(&path)).name != scope.name;
+                if path == document.path && !moved_scope {
+                    continue;
+                }
+                if path != document.path {
+                    self.remove_document(&document.path).await?;
+                    document.path = path;
+                }
+                self.upsert_document(document).await?;
+                relocated += 1;
+            }
+
+            if let Some(foreign) = foreign {
+                drop(searcher);
+                let path = foreign.path.clone();
+                drop(foreign);
+                tokio::fs::remove_dir_all(path).await?;
+            }
+        }
+        self.commit_changes().await?;
+        Ok(relocated)
+    }
+
+    /// Contagens e tamanho do índice, com o total de documentos em cada raiz e em cada escopo
+    pub fn stats(&self, roots: &[PathBuf]) -> Result<IndexStats> {
+        let mut stats = IndexStats::new(roots, self.scopes.len());
+        for scope in &self.scopes {
+            let vector_count = scope.vectors.read().unwrap().len() as u64;
+            stats.add_scope(&scope.name, &scope.path, &scope.index, &scope.reader, self.schema.fields.path, vector_count)?;
+        }
+        Ok(stats)
+    }
+
+    /// Confere os checksums de todos os arquivos dos segmentos ativos
+    ///
+    /// Os caminhos do relatório são relativos a `index_path` (`<escopo>/<arquivo>`).
+    pub fn check_integrity(&self) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport::default();
+        for scope in &self.scopes {
+            report.check_scope(&scope.name, &scope.path, &scope.index)?;
+        }
+        report.finish();
+        Ok(report)
+    }
+
+    pub async fn search(&self, query_str: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let parsed = ParsedQuery::parse(query_str);
+        let scope_filters: Vec<&Vec<String>> = parsed
+            .filters
+            .iter()
+            .filter_map(|filter| match filter {
+                QueryFilter::Scope(names) => Some(names),
+                _ => None,
+            })
+            .collect();
+
+        // O vetor da consulta é calculado uma vez para todos os escopos
+        let query_vector = match (&self.semantic, parsed.text.is_empty()) {
+            (Some(semantic), false) => {
+                let embedder = semantic.embedder.clone();
+                let text = parsed.text.clone();
+                match tokio::task::spawn_blocking(move || embedder.embed(&text)).await? {
+                    Ok(vector) => Some(vector),
+                    Err(e) => {
+                        warn!("Could not embed query, using keyword ranking only: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let mut results = Vec::new();
+        for scope in &self.scopes {
+            // Como nos demais filtros, vários `scope:` precisam ser todos satisfeitos
+            if !scope_filters.iter().all(|names| names.contains(&scope.name)) {
+                continue;
+            }
+            results.extend(self.search_scope(scope, &parsed, query_vector.as_deref(), limit)?);
+        }
+
+        // As pontuações de índices diferentes são comparáveis o bastante para intercalar
+        results.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    fn search_scope(
+        &self,
+        scope: &ScopeIndex,
+        parsed: &ParsedQuery,
+        query_vector: Option<&[f32]>,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let searcher = scope.reader.searcher();
+        let fields = &self.schema.fields;
+
+        let mut filters: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for filter in &parsed.filters {
+            if let Some(query) = self.filter_query(filter)? {
+                filters.push((Occur::Must, query));
+            }
+        }
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = filters.iter().map(|(occur, q)| (*occur, q.box_clone())).collect();
+        if let Some(text_query) = scope.query_builder.build(&parsed.text) {
+            clauses.push((Occur::Must, text_query));
+        } else if !parsed.text.is_empty() {
+            // Só pontuação ou palavras descartadas pelo tokenizer: nada casaria
+            return Ok(Vec::new());
+        }
+        if clauses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = BooleanQuery::new(clauses);
+
+        let mut hits: Vec<(f32, DocAddress)> = match (query_vector, &self.semantic) {
+            (Some(query_vector), Some(semantic)) => {
+                self.hybrid_hits(scope, &searcher, &query, filters, query_vector, semantic.weight, limit)?
+            }
+            _ => searcher.search(&query, &TopDocs::with_limit(limit))?,
+        };
+        hits.truncate(limit);
+
+        let mut snippet_generator = SnippetGenerator::create(&searcher, &query, fields.body)?;
+        snippet_generator.set_max_num_chars(SNIPPET_MAX_CHARS);
+
+        let mut results = Vec::new();
+        for (score, doc_address) in hits {
+            // CORREÇÃO: Explicitamente pedimos um `TantivyDocument`, que é a struct concreta.
+            let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+
+            // Agora, `doc_to_search_result` recebe um tipo concreto que TEM o método `.get_first`.
+            if let Some(mut result) = self.doc_to_search_result(&retrieved_doc) {
+                result.score = Some(score);
+                result.scope = scope.name.clone();
+
+                let snippet = snippet_generator.snippet_from_doc(&retrieved_doc);
+                if !snippet.is_empty() {
+                    result.highlights = snippet.highlighted().iter().map(|r| (r.start, r.end)).collect();
+                    result.snippet = Some(snippet.fragment().to_string());
+                }
+
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Junta os melhores por BM25 com os vizinhos mais próximos do vetor da consulta
+    ///
+    /// Os vizinhos passam pelos mesmos filtros (`ext:`, `in:`...) que a busca por
+    /// palavras; a pontuação final vem de `semantic::blend_scores`.
+    #[allow(clippy::too_many_arguments)]
+    fn hybrid_hits(
+        &self,
+        scope: &ScopeIndex,
+        searcher: &tantivy::Searcher,
+        query: &BooleanQuery,
+        mut filters: Vec<(Occur, Box<dyn Query>)>,
+        query_vector: &[f32],
+        semantic_weight: f32,
+        limit: usize,
+    ) -> Result<Vec<(f32, DocAddress)>> {
+        let candidates = limit.saturating_mul(2);
+        let keyword_hits = searcher.search(query, &TopDocs::with_limit(candidates))?;
+        let neighbours: HashMap<String, f32> = scope
+            .vectors
+            .read()
+            .unwrap()
+            .search(query_vector, candidates)
+            .into_iter()
+            .filter(|(_, similarity)| *similarity >= MIN_SEMANTIC_SIMILARITY)
+            .collect();
+
+        let mut scored: HashMap<DocAddress, (f32, f32)> =
+            keyword_hits.iter().map(|&(bm25, address)| (address, (bm25, 0.0))).collect();
+
+        if !neighbours.is_empty() {
+            let terms: Vec<Term> = neighbours.keys().map(|path| Term::from_field_text(self.schema.fields.path, path)).collect();
+            filters.push((Occur::Must, Box::new(TermSetQuery::new(terms))));
+            for (_, address) in searcher.search(&BooleanQuery::new(filters), &TopDocs::with_limit(neighbours.len()))? {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                if let Some(OwnedValue::Str(path)) = doc.get_first(self.schema.fields.path) {
+                    scored.entry(address).or_insert((0.0, 0.0)).1 = neighbours[path.as_str()];
+                }
+            }
+        }
+
+        // Resultados só por palavra também contam com a similaridade, se tiverem vetor
+        let vectors = scope.vectors.read().unwrap();
+        for &(_, address) in &keyword_hits {
+            let entry = scored.get_mut(&address).unwrap();
+            if entry.1 == 0.0 {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                if let Some(OwnedValue::Str(path)) = doc.get_first(self.schema.fields.path) {
+                    entry.1 = vectors.similarity(path, query_vector).unwrap_or(0.0);
+                }
+            }
+        }
+
+        let max_bm25 = keyword_hits.first().map_or(0.0, |(score, _)| *score);
+        let mut hits: Vec<(f32, DocAddress)> = scored
+            .into_iter()
+            .map(|(address, (bm25, similarity))| {
+                (semantic::blend_scores(bm25, max_bm25, similarity, semantic_weight), address)
+            })
+            .collect();
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(hits)
+    }
+
+    /// Consulta de um filtro; `None` para filtros que não viram consulta (`scope:`)
+    fn filter_query(&self, filter: &QueryFilter) -> Result<Option<Box<dyn Query>>> {
+        let fields = &self.schema.fields;
+        let query: Box<dyn Query> = match filter {
+            QueryFilter::Extension(extensions) => {
+                let terms = extensions
+                    .iter()
+                    .map(|ext| Term::from_field_text(fields.extension, ext))
+                    .collect();
+                Box::new(BooleanQuery::new_multiterms_query(terms))
+            }
+            QueryFilter::Size { lower, upper } => {
+                Box::new(RangeQuery::new_u64_bounds("size".to_string(), *lower, *upper))
+            }
+            QueryFilter::Modified { lower, upper } => {
+                Box::new(RangeQuery::new_u64_bounds("modified".to_string(), *lower, *upper))
+            }
+            QueryFilter::InDir(dir) => {
+                // O próprio diretório ou qualquer subdiretório
+                let dir = dir.to_string_lossy();
+                let separator = regex::escape(std::path::MAIN_SEPARATOR_STR);
+                let pattern = format!("{}({}.*)?", regex::escape(dir.trim_end_matches(std::path::MAIN_SEPARATOR)), separator);
+                Box::new(RegexQuery::from_pattern(&pattern, fields.parent)?)
+            }
+            QueryFilter::Scope(_) => return Ok(None),
+        };
+        Ok(Some(query))
+    }
+
+    // CORREÇÃO: A assinatura da função agora espera a struct concreta `&TantivyDocument`.
+    fn doc_to_search_result(&self, doc: &TantivyDocument) -> Option<SearchResult> {
+        let path = match doc.get_first(self.schema.fields.path)? {
+            OwnedValue::Str(text) => text.clone(),
+            _ => return None,
+        };
+        
+        let title = match doc.get_first(self.schema.fields.title)? {
+            OwnedValue::Str(text) => text.clone(),
+            _ => return None,
+        };
+        
+        Some(SearchResult {
+            path,
+            title,
+            score: None,
+            snippet: None,
+            highlights: Vec::new(),
+            scope: DEFAULT_SCOPE.to_string(),
+        })
+    }
+
+    fn doc_to_indexed_document(&self, doc: &TantivyDocument) -> Option<IndexedDocument> {
+        let fields = &self.schema.fields;
+        let text = |field: Field| match doc.get_first(field) {
+            Some(OwnedValue::Str(text)) => Some(text.clone()),
+            _ => None,
+        };
+        let number = |field: Field| match doc.get_first(field) {
+            Some(OwnedValue::U64(value)) => *value,
+            _ => 0,
+        };
+
+        Some(IndexedDocument {
+            path: text(fields.path)?,
+            title: text(fields.title)?,
+            body: text(fields.body).unwrap_or_default(),
+            fingerprint: FileFingerprint {
+                modified: number(fields.modified),
+                size: number(fields.size),
+                content_hash: text(fields.content_hash).unwrap_or_default(),
+            },
+            embedding: match (doc.get_first(fields.embedding), text(fields.embedding_model)) {
+                (Some(OwnedValue::Bytes(bytes)), Some(model)) => {
+                    Some(Embedding { model, vector: semantic::vector_from_bytes(bytes) })
+                }
+                _ => None,
+            },
+        })
+    }
+}
+
+/// Escopo aberto só para leitura
+struct ScopeReader {
+    name: String,
+    path: PathBuf,
+    index: Index,
+    reader: IndexReader,
+}
+
+/// Índice aberto só para leitura, para `stats` e `check_integrity` com o daemon rodando
+///
+/// Não pega a trava de escritor e não cria, migra nem apaga nada: falha se o
+/// índice não existir ou tiver sido criado com outro schema.
+pub struct ReadOnlyIndex {
+    index_path: PathBuf,
+    scopes: Vec<ScopeReader>,
+}
+
+impl ReadOnlyIndex {
+    /// Abre os escopos de `scopes` (mais o padrão) que já existem em `index_path`
+    pub fn open(index_path: &Path, scopes: &[ScopeConfig]) -> Result<Self> {
+        if !index_path.is_dir() {
+            anyhow::bail!("No search index at {:?}", index_path);
+        }
+
+        let mut readers = Vec::new();
+        for name in scope_names(scopes)? {
+            let path = index_path.join(&name);
+            if !path.join("meta.json").exists() {
+                debug!("Scope '{}' has no index at {:?}, skipping", name, path);
+                continue;
+            }
+            let index = Index::open_in_dir(&path)?;
+            // Sem recarga automática: o diagnóstico lê um único commit
+            let reader = index.reader_builder().reload_policy(ReloadPolicy::Manual).try_into()?;
+            readers.push(ScopeReader { name, path, index, reader });
+        }
+        if readers.is_empty() {
+            anyhow::bail!("No search index at {:?}", index_path);
+        }
+        Ok(Self { index_path: index_path.to_path_buf(), scopes: readers })
+    }
+
+    pub fn index_path(&self) -> &Path {
+        &self.index_path
+    }
+
+    /// Como `SearchEngine::stats`; `vector_count` conta os vetores gravados, de qualquer embedder
+    pub fn stats(&self, roots: &[PathBuf]) -> Result<IndexStats> {
+        let mut stats = IndexStats::new(roots, self.scopes.len());
+        for scope in &self.scopes {
+            let schema = scope.index.schema();
+            let path_field = schema
+                .get_field("path")
+                .map_err(|_| anyhow::anyhow!("Search index scope '{}' has an incompatible schema", scope.name))?;
+            let vector_count = stored_vector_count(&scope.reader)?;
+            stats.add_scope(&scope.name, &scope.path, &scope.index, &scope.reader, path_field, vector_count)?;
+        }
+        Ok(stats)
+    }
+
+    /// Como `SearchEngine::check_integrity`
+    pub fn check_integrity(&self) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport::default();
+        for scope in &self.scopes {
+            report.check_scope(&scope.name, &scope.path, &scope.index)?;
+        }
+        report.finish();
+        Ok(report)
+    }
+}
+
+/// Documentos com embedding gravado no último commit
+fn stored_vector_count(reader: &IndexReader) -> Result<u64> {
+    let mut count = 0;
+    for segment in reader.searcher().segment_readers() {
+        let Some(embeddings) = segment.fast_fields().bytes("embedding")? else {
+            continue;
+        };
+        count += segment.doc_ids_alive().filter(|&doc| embeddings.term_ords(doc).next().is_some()).count() as u64;
+    }
+    Ok(count)
+}
+
+/// Documentos cujo caminho fica dentro de `dir`
+fn under_dir_query(dir: &Path, path_field: Field) -> Result<RegexQuery> {
+    let prefix = dir.join("").to_string_lossy().to_string();
+    let pattern = format!("{}.*", regex::escape(&prefix));
+    Ok(RegexQuery::from_pattern(&pattern, path_field)?)
+}
+
+/// Soma dos arquivos de um diretório de escopo
+fn dir_size(path: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        let metadata = entry?.metadata()?;
+        if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Nomes dos diretórios dos escopos, com o padrão primeiro
+fn scope_names(scopes: &[ScopeConfig]) -> Result<Vec<String>> {
+    let mut names = vec![DEFAULT_SCOPE.to_string()];
+    for scope in scopes {
+        if !ScopeConfig::is_valid_name(&scope.name) {
+            anyhow::bail!("Invalid search scope name: {:?}", scope.name);
+        }
+        let name = scope.name.to_lowercase();
+        if names.contains(&name) {
+            anyhow::bail!("Duplicate search scope: {:?}", scope.name);
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Remove os arquivos do índice, mantendo a trava de escritor
+async fn clear_index_dir(index_path: &Path) -> Result<()> {
+    let mut entries = tokio::fs::read_dir(index_path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_name() == LOCK_FILE_NAME {
+            continue;
+        }
+        if entry.file_type().await?.is_dir() {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+        } else {
+            tokio::fs::remove_file(entry.path()).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{corpus_config, corpus_dir, index_dir, scanned_indexer};
+    use crate::{IndexLockedError, SearchConfig};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_single_writer_lock() {
+        let index_dir = index_dir();
+        let engine = SearchEngine::open_in_dir(index_dir.path()).await.unwrap();
+
+        let err = SearchEngine::open_in_dir(index_dir.path()).await.err().unwrap();
+        let locked = err.downcast_ref::<IndexLockedError>().unwrap();
+        assert_eq!(locked.holder_pid, Some(std::process::id()));
+        assert!(SearchEngine::recreate_in_dir(index_dir.path()).await.is_err());
+
+        // A trava é liberada junto com o engine
+        drop(engine);
+        SearchEngine::open_in_dir(index_dir.path()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stats_integrity_and_rebuild() {
+        let corpus = corpus_dir();
+        std::fs::create_dir_all(corpus.path().join("a")).unwrap();
+        std::fs::create_dir_all(corpus.path().join("b")).unwrap();
+        std::fs::write(corpus.path().join("a/one.txt"), "first file").unwrap();
+        std::fs::write(corpus.path().join("a/two.txt"), "second file").unwrap();
+        std::fs::write(corpus.path().join("b/three.txt"), "third file").unwrap();
+
+        let index_dir = index_dir();
+        let engine = Arc::new(SearchEngine::open_in_dir(index_dir.path()).await.unwrap());
+        let indexer = scanned_indexer(&engine, corpus_config(corpus.path())).await;
+
+        let roots = [corpus.path().join("a"), corpus.path().join("b")];
+        let stats = engine.stats(&roots).unwrap();
+        assert_eq!(stats.doc_count, 3);
+        assert_eq!(stats.segment_count, 1);
+        assert!(stats.size_on_disk > 0);
+        assert!(stats.last_commit.unwrap() > 0);
+        assert_eq!(stats.roots.iter().map(|r| r.doc_count).collect::<Vec<_>>(), vec![2, 1]);
+        assert!(engine.check_integrity().unwrap().is_healthy());
+
+        // Corromper um arquivo de segmento no lugar
+        let segment = engine.scopes[0].index.searchable_segment_metas().unwrap()[0].clone();
+        let store = index_dir.path().join(DEFAULT_SCOPE).join(segment.relative_path(tantivy::index::SegmentComponent::Store));
+        let mut bytes = std::fs::read(&store).unwrap();
+        bytes[0] ^= 0xff;
+        std::fs::write(&store, bytes).unwrap();
+        assert_eq!(engine.check_integrity().unwrap().damaged_files.len(), 1);
+
+        indexer.rebuild().await.unwrap();
+        assert!(engine.check_integrity().unwrap().is_healthy());
+        assert_eq!(engine.stats(&[]).unwrap().doc_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_open_while_locked() {
+        let corpus = corpus_dir();
+        std::fs::write(corpus.path().join("one.txt"), "first file").unwrap();
+
+        let index_dir = index_dir();
+        let engine = Arc::new(SearchEngine::open_in_dir(index_dir.path()).await.unwrap());
+        scanned_indexer(&engine, corpus_config(corpus.path())).await;
+
+        // O engine continua com a trava de escritor
+        let read_only = ReadOnlyIndex::open(index_dir.path(), &[]).unwrap();
+        let stats = read_only.stats(&[corpus.path().to_path_buf()]).unwrap();
+        assert_eq!(stats.doc_count, 1);
+        assert_eq!(stats.roots[0].doc_count, 1);
+        assert!(read_only.check_integrity().unwrap().is_healthy());
+
+        // Nada é criado onde não há índice
+        let missing = index_dir.path().join("missing");
+        assert!(ReadOnlyIndex::open(&missing, &[]).is_err());
+        assert!(!missing.exists());
+    }
+
+    #[tokio::test]
+    async fn test_named_scopes() {
+        let corpus = corpus_dir();
+        let work = corpus.path().join("work");
+        let notes = corpus.path().join("notes");
+        std::fs::create_dir_all(&work).unwrap();
+        std::fs::create_dir_all(&notes).unwrap();
+        std::fs::write(work.join("roadmap.md"), "roadmap for the quarter").unwrap();
+        std::fs::write(work.join("roadmap.txt"), "roadmap draft").unwrap();
+        std::fs::write(notes.join("roadmap.md"), "personal roadmap ideas").unwrap();
+        std::fs::write(corpus.path().join("roadmap.md"), "loose roadmap").unwrap();
+        // Mais antigo que a retenção do escopo "notes"
+        let old = std::fs::File::create(notes.join("old-roadmap.md")).unwrap();
+        std::io::Write::write_all(&mut &old, b"old roadmap").unwrap();
+        old.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(60 * 24 * 60 * 60)).unwrap();
+        drop(old);
+
+        let scopes = vec![
+            ScopeConfig { name: "work".to_string(), roots: vec![work.clone()], extensions: vec!["md".to_string()], retention_days: None },
+            ScopeConfig { name: "Notes".to_string(), roots: vec![notes.clone()], extensions: vec![], retention_days: Some(30) },
+        ];
+        let index_dir = index_dir();
+        let engine = Arc::new(SearchEngine::open_scoped(index_dir.path(), &scopes).await.unwrap());
+        assert_eq!(engine.scope_names(), vec!["default", "work", "notes"]);
+        assert!(index_dir.path().join("work").is_dir());
+
+        scanned_indexer(&engine, SearchConfig { scopes: scopes.clone(), ..corpus_config(corpus.path()) }).await;
+
+        // A extensão do escopo "work" deixa o .txt de fora e a retenção de "notes", o arquivo antigo
+        let all = engine.search("roadmap", 10).await.unwrap();
+        assert_eq!(all.len(), 3);
+        let scope_of = |path: &Path| all.iter().find(|r| Path::new(&r.path) == path).map(|r| r.scope.as_str());
+        assert_eq!(scope_of(&work.join("roadmap.md")), Some("work"));
+        assert_eq!(scope_of(&notes.join("roadmap.md")), Some("notes"));
+        assert_eq!(scope_of(&corpus.path().join("roadmap.md")), Some(DEFAULT_SCOPE));
+
+        let work_only = engine.search("roadmap scope:work", 10).await.unwrap();
+        assert_eq!(work_only.len(), 1);
+        assert_eq!(Path::new(&work_only[0].path), work.join("roadmap.md"));
+        assert_eq!(engine.search("roadmap scope:work,notes", 10).await.unwrap().len(), 2);
+        assert!(engine.search("roadmap scope:missing", 10).await.unwrap().is_empty());
+
+        let stats = engine.stats(&[]).unwrap();
+        assert_eq!(stats.doc_count, 3);
+        assert_eq!(stats.scopes.iter().map(|s| s.doc_count).collect::<Vec<_>>(), vec![1, 1, 1]);
+        assert!(engine.check_integrity().unwrap().is_healthy());
+
+        // Escopo fora da configuração: o índice continua no disco, mas não é consultado
+        drop(engine);
+        let engine = SearchEngine::open_scoped(index_dir.path(), &scopes[..1]).await.unwrap();
+        assert_eq!(engine.scope_names(), vec!["default", "work"]);
+        assert_eq!(engine.search("roadmap", 10).await.unwrap().len(), 2);
+        assert!(index_dir.path().join("notes").is_dir());
+    }
+
+    /// Embedder de teste que entende alguns sinônimos: cada conceito é uma dimensão
+    struct ConceptEmbedder;
+
+    impl Embedder for ConceptEmbedder {
+        fn name(&self) -> &str {
+            "concepts"
+        }
+
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let mut vector = vec![0.0; 3];
+            for word in text.to_lowercase().split(|c: char| !c.is_alphanumeric()) {
+                match word {
+                    "budget" | "finance" | "money" => vector[0] += 1.0,
+                    "quarterly" | "q3" | "quarter" => vector[1] += 1.0,
+                    "beach" | "holiday" | "photos" => vector[2] += 1.0,
+                    _ => {}
+                }
+            }
+            semantic::normalize(&mut vector);
+            Ok(vector)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_semantic_search() {
+        let corpus = corpus_dir();
+        std::fs::write(corpus.path().join("plan.md"), "Q3 finance plan").unwrap();
+        std::fs::write(corpus.path().join("budget.md"), "quarterly budget notes").unwrap();
+        std::fs::write(corpus.path().join("trip.md"), "holiday photos at the beach").unwrap();
+        let config = corpus_config(corpus.path());
+
+        // Sem embedder, só a busca por palavras
+        let index_dir = index_dir();
+        let engine = Arc::new(SearchEngine::open_in_dir(index_dir.path()).await.unwrap());
+        scanned_indexer(&engine, config.clone()).await;
+        let titles = |results: Vec<SearchResult>| results.into_iter().map(|r| r.title).collect::<Vec<_>>();
+        assert_eq!(titles(engine.search("quarterly budget", 10).await.unwrap()), vec!["budget.md"]);
+        drop(engine);
+
+        // Ligar a busca semântica recalcula os vetores mesmo dos arquivos sem mudança
+        let engine = SearchEngine::open_in_dir(index_dir.path()).await.unwrap();
+        let engine = Arc::new(engine.with_embedder(Arc::new(ConceptEmbedder), 0.5).unwrap());
+        let indexer = scanned_indexer(&engine, config).await;
+        assert_eq!(engine.stats(&[]).unwrap().scopes[0].vector_count, 3);
+
+        let results = engine.search("quarterly budget", 10).await.unwrap();
+        assert_eq!(titles(results.clone()), vec!["budget.md", "plan.md"]);
+        assert!(results[0].score > results[1].score);
+        assert!(results[1].snippet.is_none());
+        // Os filtros também valem para os resultados só semânticos
+        assert!(engine.search("quarterly budget ext:txt", 10).await.unwrap().is_empty());
+        assert_eq!(titles(engine.search("money in:/nonexistent", 10).await.unwrap()), Vec::<String>::new());
+
+        // Remoções saem do índice vetorial no commit
+        std::fs::remove_file(corpus.path().join("plan.md")).unwrap();
+        indexer.remove_missing_under(corpus.path()).await.unwrap();
+        engine.commit_changes().await.unwrap();
+        assert_eq!(titles(engine.search("quarterly budget", 10).await.unwrap()), vec!["budget.md"]);
+        drop(indexer);
+        drop(engine);
+
+        // Os vetores são carregados do índice ao reabrir
+        let engine = SearchEngine::open_in_dir(index_dir.path()).await.unwrap();
+        let engine = engine.with_embedder(Arc::new(ConceptEmbedder), 0.5).unwrap();
+        assert_eq!(engine.stats(&[]).unwrap().scopes[0].vector_count, 2);
+        assert_eq!(titles(engine.search("holiday", 10).await.unwrap()), vec!["trip.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_export_import_with_remap() {
+        let document = |path: &str, body: &str| IndexedDocument {
+            path: path.to_string(),
+            title: Path::new(path).file_name().unwrap().to_string_lossy().to_string(),
+            body: body.to_string(),
+            fingerprint: FileFingerprint { modified: 42, size: body.len() as u64, content_hash: "abc".to_string() },
+            embedding: None,
+        };
+        let work = ScopeConfig { name: "work".to_string(), roots: vec!["/home/alice/work".into()], extensions: vec![], retention_days: None };
+
+        let source_dir = index_dir();
+        let source = SearchEngine::open_scoped(source_dir.path(), std::slice::from_ref(&work)).await.unwrap();
+        source.upsert_document(document("/home/alice/work/plan.md", "roadmap plan")).await.unwrap();
+        source.upsert_document(document("/home/alice/notes.md", "roadmap notes")).await.unwrap();
+        source.upsert_document(document("/srv/shared/roadmap.txt", "shared roadmap")).await.unwrap();
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let archive = archive_dir.path().join("index.tar");
+        let manifest = source.export_snapshot(&archive, &["/home/alice".into()]).await.unwrap();
+        assert_eq!(manifest.scopes.iter().map(|s| s.doc_count).sum::<u64>(), 3);
+        // O índice de origem continua utilizável
+        source.upsert_document(document("/home/alice/later.md", "roadmap later")).await.unwrap();
+        source.commit_changes().await.unwrap();
+
+        // Sem o escopo "work" aqui, os documentos dele vão para o padrão
+        let remaps = ["/home/alice=/home/bob".parse().unwrap()];
+        let target_dir = index_dir();
+        let (target, manifest) =
+            SearchEngine::import_snapshot(&archive, target_dir.path(), &[], &remaps).await.unwrap();
+        assert_eq!(manifest.remapped_roots(&remaps), vec![PathBuf::from("/home/bob")]);
+        assert!(!target_dir.path().join("work").exists());
+        let mut paths: Vec<_> = target.search("roadmap", 10).await.unwrap().into_iter().map(|r| (r.path, r.scope)).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                ("/home/bob/notes.md".to_string(), DEFAULT_SCOPE.to_string()),
+                ("/home/bob/work/plan.md".to_string(), DEFAULT_SCOPE.to_string()),
+                ("/srv/shared/roadmap.txt".to_string(), DEFAULT_SCOPE.to_string()),
+            ]
+        );
+        let plan = target.get_document("/home/bob/work/plan.md").unwrap().unwrap();
+        assert_eq!(plan.fingerprint, document("/x", "roadmap plan").fingerprint);
+        assert_eq!(plan.body, "roadmap plan");
+        drop(target);
+
+        // Importar de novo substitui o índice, agora com o escopo nas pastas novas
+        let work = ScopeConfig { roots: vec!["/home/bob/work".into()], ..work };
+        let (target, _) =
+            SearchEngine::import_snapshot(&archive, target_dir.path(), &[work], &remaps).await.unwrap();
+        let results = target.search("roadmap", 10).await.unwrap();
+        assert_eq!(results.len(), 3);
+        let plan = results.iter().find(|r| r.path == "/home/bob/work/plan.md").unwrap();
+        assert_eq!(plan.scope, "work");
+
+        // Um snapshot inválido não apaga o índice atual
+        std::fs::write(&archive, b"not a snapshot").unwrap();
+        drop(target);
+        assert!(SearchEngine::import_snapshot(&archive, target_dir.path(), &[], &remaps).await.is_err());
+        let target = SearchEngine::open_in_dir(target_dir.path()).await.unwrap();
+        assert_eq!(target.search("roadmap", 10).await.unwrap().len(), 2);
+    }
+}
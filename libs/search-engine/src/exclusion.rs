@@ -0,0 +1,318 @@
+//! Regras de exclusão da indexação
+//!
+//! Reúne numa só configuração tudo o que tira um caminho do índice:
+//! - caminhos absolutos de `SearchConfig::ignored_paths` (a pasta e tudo dentro dela);
+//! - padrões glob no formato do `.gitignore` (`excluded_patterns` e entradas
+//!   relativas de `ignored_paths`, como `.git` ou `*.log`);
+//! - arquivos `.gitignore` e `.ignore` encontrados nas pastas indexadas;
+//! - arquivos e pastas ocultos, a menos que `include_hidden` esteja ligado.
+//!
+//! Como no git, a última regra que casar vence, regras de pastas mais
+//! profundas têm precedência e `!padrão` reinclui um caminho. Os padrões são
+//! compilados pelo crate `ignore`, o mesmo do ripgrep.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::config::SearchConfig;
+
+/// Arquivos de regras lidos em cada pasta, do menos para o mais prioritário
+pub const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".ignore"];
+
+/// Regras no formato do `.gitignore`, compiladas com `ignore::gitignore`
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    matcher: Gitignore,
+}
+
+impl IgnoreRules {
+    /// Compila linhas de `.gitignore`; padrões inválidos são avisados e descartados
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut builder = GitignoreBuilder::new("");
+        for line in lines {
+            if let Err(e) = builder.add_line(None, line) {
+                warn!("Invalid ignore pattern '{}': {}", line, e);
+            }
+        }
+        Self::build(builder)
+    }
+
+    /// Regras dos arquivos de ignore que existirem em `dir`
+    fn load(dir: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(dir);
+        for name in IGNORE_FILE_NAMES {
+            let file = dir.join(name);
+            if file.is_file() {
+                debug!("Loaded ignore rules from {:?}", file);
+                if let Some(e) = builder.add(&file) {
+                    warn!("Invalid ignore rules in {:?}: {}", file, e);
+                }
+            }
+        }
+        Self::build(builder)
+    }
+
+    fn build(builder: GitignoreBuilder) -> Self {
+        let matcher = builder.build().unwrap_or_else(|e| {
+            warn!("Could not compile ignore rules: {}", e);
+            Gitignore::empty()
+        });
+        Self { matcher }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_empty()
+    }
+
+    /// Decisão da última regra que casar com `relative` (relativo à pasta das regras):
+    /// `Some(true)` exclui, `Some(false)` reinclui (`!padrão`), `None` sem regra
+    pub fn decision(&self, relative: &Path, is_dir: bool) -> Option<bool> {
+        match self.matcher.matched(relative, is_dir) {
+            Match::None => None,
+            Match::Ignore(_) => Some(true),
+            Match::Whitelist(_) => Some(false),
+        }
+    }
+}
+
+/// Regras de exclusão compiladas a partir de um `SearchConfig`
+#[derive(Debug)]
+pub struct Exclusions {
+    roots: Vec<PathBuf>,
+    excluded_dirs: Vec<PathBuf>,
+    patterns: IgnoreRules,
+    include_hidden: bool,
+    respect_ignore_files: bool,
+    /// Regras dos arquivos de ignore já lidos, por pasta
+    ignore_files: RwLock<HashMap<PathBuf, Arc<IgnoreRules>>>,
+}
+
+impl Exclusions {
+    pub fn from_config(config: &SearchConfig) -> Self {
+        let mut excluded_dirs = Vec::new();
+        let mut lines = Vec::new();
+
+        for ignored in &config.ignored_paths {
+            if ignored.is_absolute() {
+                excluded_dirs.push(ignored.clone());
+            } else {
+                lines.push(ignored.to_string_lossy().to_string());
+            }
+        }
+        lines.extend(config.excluded_patterns.iter().cloned());
+
+        Self {
+            roots: config.roots(),
+            excluded_dirs,
+            patterns: IgnoreRules::parse(lines.iter().map(String::as_str)),
+            include_hidden: config.include_hidden,
+            respect_ignore_files: config.respect_ignore_files,
+            ignore_files: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Verifica só o próprio caminho, supondo que as pastas acima já foram aceitas (varredura)
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let root = self.root_of(path);
+        self.is_excluded_in(root, path, is_dir)
+    }
+
+    /// Verifica o caminho e cada pasta entre ele e a raiz indexada (eventos avulsos)
+    pub fn is_excluded_with_ancestors(&self, path: &Path, is_dir: bool) -> bool {
+        let root = self.root_of(path);
+        let mut ancestors: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|ancestor| Some(*ancestor) != root && !ancestor.as_os_str().is_empty())
+            .collect();
+        // Sem raiz conhecida só o próprio caminho é avaliado
+        if root.is_none() {
+            ancestors.clear();
+        }
+
+        ancestors.iter().rev().any(|ancestor| self.is_excluded_in(root, ancestor, true))
+            || self.is_excluded_in(root, path, is_dir)
+    }
+
+    /// Descarta as regras em cache de `dir` depois que um arquivo de ignore mudou
+    pub fn invalidate(&self, dir: &Path) {
+        if let Ok(mut cache) = self.ignore_files.write() {
+            cache.remove(dir);
+        }
+    }
+
+    fn root_of(&self, path: &Path) -> Option<&Path> {
+        // A raiz mais profunda que contém o caminho
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    fn is_excluded_in(&self, root: Option<&Path>, path: &Path, is_dir: bool) -> bool {
+        if self.excluded_dirs.iter().any(|dir| path.starts_with(dir)) {
+            return true;
+        }
+
+        if !self.include_hidden && path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+            return true;
+        }
+
+        let relative_to_root = root.and_then(|root| relative_path(root, path)).unwrap_or_else(|| slash_path(path));
+        let mut excluded = self.patterns.decision(Path::new(&relative_to_root), is_dir);
+
+        if let (true, Some(root)) = (self.respect_ignore_files, root) {
+            // Pastas da raiz até o pai do caminho: regras mais profundas são avaliadas por último
+            let dirs: Vec<&Path> = path.ancestors().skip(1).take_while(|dir| dir.starts_with(root)).collect();
+            for dir in dirs.into_iter().rev() {
+                let Some(relative) = relative_path(dir, path) else {
+                    continue;
+                };
+                if let Some(decision) = self.ignore_file_rules(dir).decision(Path::new(&relative), is_dir) {
+                    excluded = Some(decision);
+                }
+            }
+        }
+
+        excluded.unwrap_or(false)
+    }
+
+    fn ignore_file_rules(&self, dir: &Path) -> Arc<IgnoreRules> {
+        if let Some(rules) = self.ignore_files.read().ok().and_then(|cache| cache.get(dir).cloned()) {
+            return rules;
+        }
+
+        let rules = Arc::new(IgnoreRules::load(dir));
+        if let Ok(mut cache) = self.ignore_files.write() {
+            cache.insert(dir.to_path_buf(), rules.clone());
+        }
+        rules
+    }
+}
+
+/// Se o arquivo é um `.gitignore` ou `.ignore`
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| IGNORE_FILE_NAMES.contains(&name))
+}
+
+fn relative_path(base: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(base).ok()?;
+    if relative.as_os_str().is_empty() {
+        return None;
+    }
+    Some(slash_path(relative))
+}
+
+/// Caminho com `/` como separador, sem o prefixo de raiz
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decisão de uma única linha de `.gitignore` para `relative`
+    fn matches(line: &str, relative: &str, is_dir: bool) -> bool {
+        IgnoreRules::parse([line]).decision(Path::new(relative), is_dir) == Some(true)
+    }
+
+    #[test]
+    fn test_gitignore_patterns() {
+        assert!(matches("*.log", "debug.log", false));
+        assert!(matches("*.log", "logs/debug.log", false));
+        assert!(!matches("*.log", "debug.log.txt", false));
+
+        // Barra no meio ou no início ancora o padrão
+        assert!(matches("/build", "build", true));
+        assert!(!matches("/build", "src/build", true));
+        assert!(matches("doc/*.txt", "doc/notes.txt", false));
+        assert!(!matches("doc/*.txt", "doc/server/notes.txt", false));
+
+        // Barra no fim: só pastas
+        assert!(matches("cache/", "a/cache", true));
+        assert!(!matches("cache/", "a/cache", false));
+
+        assert!(matches("**/logs", "a/b/logs", true));
+        assert!(matches("**/logs", "logs", true));
+        assert!(matches("a/**/b", "a/b", false));
+        assert!(matches("a/**/b", "a/x/y/b", false));
+        assert!(matches("a/**", "a/x/y", false));
+        assert!(!matches("a/**", "b/x", false));
+
+        assert!(matches("file?.[ch]", "file1.c", false));
+        assert!(!matches("file[!0-9].c", "file1.c", false));
+        assert!(matches("\\#notes", "#notes", false));
+
+        // A última regra que casar vence
+        let rules = IgnoreRules::parse(["*.log", "!keep.log"]);
+        assert_eq!(rules.decision(Path::new("keep.log"), false), Some(false));
+        assert_eq!(rules.decision(Path::new("debug.log"), false), Some(true));
+        assert_eq!(rules.decision(Path::new("notes.txt"), false), None);
+
+        assert!(IgnoreRules::parse(["# comment"]).is_empty());
+        assert!(IgnoreRules::parse(["   "]).is_empty());
+        // Colchete sem fechamento é literal, como no git
+        assert!(matches("broken[", "broken[", false));
+        assert!(!matches("broken[", "broken", false));
+    }
+
+    #[test]
+    fn test_nested_ignore_files() {
+        let root = tempfile::Builder::new().prefix("r5-ignore").tempdir().unwrap();
+        let root = root.path();
+        std::fs::create_dir_all(root.join("project/src")).unwrap();
+        std::fs::create_dir_all(root.join("project/vendor/lib")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\nsecret/\n").unwrap();
+        std::fs::write(root.join("project/.gitignore"), "!important.log\nvendor/\n").unwrap();
+        std::fs::write(root.join("project/src/.ignore"), "generated.rs\n").unwrap();
+
+        let config = SearchConfig {
+            indexed_paths: vec![root.to_path_buf()],
+            ignored_paths: vec![root.join("private")],
+            excluded_patterns: vec!["*.tmp".to_string(), "node_modules".to_string()],
+            ..SearchConfig::default()
+        };
+        let exclusions = Exclusions::from_config(&config);
+
+        assert!(exclusions.is_excluded(&root.join("debug.log"), false));
+        assert!(exclusions.is_excluded(&root.join("project/src/debug.log"), false));
+        // Regra negada numa pasta mais profunda reinclui o arquivo
+        assert!(!exclusions.is_excluded(&root.join("project/important.log"), false));
+        assert!(exclusions.is_excluded(&root.join("important.log"), false));
+
+        assert!(exclusions.is_excluded(&root.join("project/vendor"), true));
+        assert!(exclusions.is_excluded_with_ancestors(&root.join("project/vendor/lib/mod.rs"), false));
+        assert!(exclusions.is_excluded(&root.join("project/src/generated.rs"), false));
+        assert!(!exclusions.is_excluded(&root.join("project/generated.rs"), false));
+        assert!(exclusions.is_excluded(&root.join("secret"), true));
+        assert!(!exclusions.is_excluded(&root.join("secret"), false));
+
+        // Padrões da configuração e pastas absolutas
+        assert!(exclusions.is_excluded(&root.join("project/a.tmp"), false));
+        assert!(exclusions.is_excluded_with_ancestors(&root.join("private/notes.txt"), false));
+        assert!(exclusions.is_excluded_with_ancestors(&root.join("project/node_modules/x/index.js"), false));
+        assert!(exclusions.is_excluded_with_ancestors(&root.join("project/.git/config"), false));
+        assert!(!exclusions.is_excluded_with_ancestors(&root.join("project/src/main.rs"), false));
+
+        // Mudanças no arquivo de regras valem após invalidar o cache
+        std::fs::write(root.join("project/src/.ignore"), "main.rs\n").unwrap();
+        assert!(exclusions.is_excluded(&root.join("project/src/generated.rs"), false));
+        exclusions.invalidate(&root.join("project/src"));
+        assert!(!exclusions.is_excluded(&root.join("project/src/generated.rs"), false));
+        assert!(exclusions.is_excluded(&root.join("project/src/main.rs"), false));
+    }
+}
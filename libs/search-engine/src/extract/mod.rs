@@ -0,0 +1,338 @@
+//! Extração de texto de documentos para o campo `body` do índice
+//!
+//! Cada formato implementa `Extractor` e é registrado no `ExtractorRegistry`
+//! pelas extensões e tipos MIME que atende. Os extratores rodam fora do
+//! runtime async (`spawn_blocking`) e respeitam `ExtractionLimits`: tamanho da
+//! entrada, bytes descomprimidos, tamanho do texto e tempo. O limite de tempo
+//! é cooperativo: cada extrator chama `Budget::check_time` nos seus laços.
+//!
+//! - `text`: texto puro e código-fonte
+//! - `pdf`: texto das páginas de PDFs, decodificado pelas fontes (`lopdf`)
+//! - `ooxml`: documentos do Office (docx, xlsx, pptx), com `quick-xml`
+//! - `zip`: leitura de arquivos ZIP e listagem do conteúdo (crate `zip`)
+
+pub mod ooxml;
+pub mod pdf;
+pub mod text;
+pub mod zip;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::SearchConfig;
+
+pub use ooxml::OoxmlExtractor;
+pub use pdf::PdfExtractor;
+pub use text::PlainTextExtractor;
+pub use zip::ZipListingExtractor;
+
+/// Falha ao extrair o texto de um documento
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// Entrada ou conteúdo descomprimido maior que o limite (bytes)
+    TooLarge { limit: u64 },
+    /// O tempo limite acabou antes do fim da extração
+    TimedOut,
+    /// Recurso do formato não suportado (ex.: PDF criptografado)
+    Unsupported(String),
+    /// Arquivo corrompido ou fora do formato esperado
+    Malformed(String),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::TooLarge { limit } => write!(f, "Document exceeds the {} byte extraction limit", limit),
+            ExtractError::TimedOut => write!(f, "Text extraction timed out"),
+            ExtractError::Unsupported(what) => write!(f, "Unsupported document feature: {}", what),
+            ExtractError::Malformed(what) => write!(f, "Malformed document: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+/// Limites aplicados a cada extração
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractionLimits {
+    /// Tamanho máximo do arquivo de entrada
+    pub max_input_bytes: u64,
+    /// Tamanho máximo de cada parte descomprimida (proteção contra zip bombs)
+    pub max_decompressed_bytes: u64,
+    /// Texto além deste tamanho é descartado
+    pub max_output_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: 50 * 1024 * 1024,
+            max_decompressed_bytes: 256 * 1024 * 1024,
+            max_output_bytes: 1024 * 1024,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ExtractionLimits {
+    pub fn from_config(config: &SearchConfig) -> Self {
+        Self {
+            max_input_bytes: config.max_file_size_mb * 1024 * 1024,
+            max_output_bytes: config.max_extracted_text_kb * 1024,
+            timeout: Duration::from_millis(config.extraction_timeout_ms),
+            ..Self::default()
+        }
+    }
+}
+
+/// Limites de uma extração em andamento
+#[derive(Debug)]
+pub struct Budget<'a> {
+    limits: &'a ExtractionLimits,
+    deadline: Instant,
+}
+
+impl<'a> Budget<'a> {
+    pub fn new(limits: &'a ExtractionLimits) -> Self {
+        Self { limits, deadline: Instant::now() + limits.timeout }
+    }
+
+    pub fn limits(&self) -> &ExtractionLimits {
+        self.limits
+    }
+
+    /// Falha com `TimedOut` quando o prazo passou
+    pub fn check_time(&self) -> Result<(), ExtractError> {
+        if Instant::now() >= self.deadline {
+            Err(ExtractError::TimedOut)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Texto vazio com o limite de tamanho desta extração
+    pub fn text_buffer(&self) -> TextBuffer {
+        TextBuffer::new(self.limits.max_output_bytes)
+    }
+}
+
+/// Texto extraído, truncado em `max_bytes`
+#[derive(Debug)]
+pub struct TextBuffer {
+    text: String,
+    max_bytes: usize,
+    full: bool,
+}
+
+impl TextBuffer {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { text: String::new(), max_bytes, full: false }
+    }
+
+    /// Acrescenta `s`; retorna `false` quando o limite foi atingido
+    pub fn push_str(&mut self, s: &str) -> bool {
+        if self.full {
+            return false;
+        }
+        let room = self.max_bytes - self.text.len();
+        if s.len() <= room {
+            self.text.push_str(s);
+            return true;
+        }
+
+        let mut end = room;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.text.push_str(&s[..end]);
+        self.full = true;
+        false
+    }
+
+    /// Separa blocos de texto sem repetir quebras de linha
+    pub fn newline(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.push_str("\n");
+        }
+    }
+
+    /// Separa palavras sem repetir espaços
+    pub fn space(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+            self.push_str(" ");
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    pub fn into_string(self) -> String {
+        let mut text = self.text;
+        text.truncate(text.trim_end().len());
+        text
+    }
+}
+
+/// Lê o texto de um formato de documento
+pub trait Extractor: Send + Sync {
+    /// Nome usado nos logs
+    fn name(&self) -> &'static str;
+
+    /// Extensões atendidas, em minúsculas e sem ponto
+    fn extensions(&self) -> &[&'static str];
+
+    /// Tipos MIME atendidos
+    fn mime_types(&self) -> &[&'static str] {
+        &[]
+    }
+
+    fn extract(&self, bytes: &[u8], budget: &Budget) -> Result<String, ExtractError>;
+}
+
+/// Extratores indexados por extensão e tipo MIME
+pub struct ExtractorRegistry {
+    extractors: Vec<Arc<dyn Extractor>>,
+    by_extension: HashMap<String, usize>,
+    by_mime: HashMap<String, usize>,
+    limits: ExtractionLimits,
+}
+
+impl ExtractorRegistry {
+    /// Registro vazio
+    pub fn new(limits: ExtractionLimits) -> Self {
+        Self { extractors: Vec::new(), by_extension: HashMap::new(), by_mime: HashMap::new(), limits }
+    }
+
+    /// Extratores embutidos; a listagem de ZIPs depende de `index_archive_listings`
+    pub fn from_config(config: &SearchConfig) -> Self {
+        let mut registry = Self::new(ExtractionLimits::from_config(config));
+        registry.register(Arc::new(PlainTextExtractor));
+        registry.register(Arc::new(PdfExtractor));
+        registry.register(Arc::new(OoxmlExtractor));
+        if config.index_archive_listings {
+            registry.register(Arc::new(ZipListingExtractor));
+        }
+        registry
+    }
+
+    /// Registra `extractor`; substitui quem já atendia as mesmas extensões ou tipos
+    pub fn register(&mut self, extractor: Arc<dyn Extractor>) {
+        let index = self.extractors.len();
+        for extension in extractor.extensions() {
+            self.by_extension.insert(extension.to_lowercase(), index);
+        }
+        for mime in extractor.mime_types() {
+            self.by_mime.insert(mime.to_lowercase(), index);
+        }
+        self.extractors.push(extractor);
+    }
+
+    pub fn limits(&self) -> &ExtractionLimits {
+        &self.limits
+    }
+
+    pub fn for_extension(&self, extension: &str) -> Option<&dyn Extractor> {
+        let index = *self.by_extension.get(&extension.to_lowercase())?;
+        Some(self.extractors[index].as_ref())
+    }
+
+    pub fn for_mime(&self, mime: &str) -> Option<&dyn Extractor> {
+        let index = *self.by_mime.get(&mime.to_lowercase())?;
+        Some(self.extractors[index].as_ref())
+    }
+
+    /// Extrator pela extensão de `path` ou, sem ela, pelo conteúdo
+    pub fn for_file(&self, path: &Path, bytes: &[u8]) -> Option<&dyn Extractor> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| self.for_extension(extension))
+            .or_else(|| sniff_mime(bytes).and_then(|mime| self.for_mime(mime)))
+    }
+
+    /// Texto de `bytes`; `Ok(None)` quando nenhum extrator atende o arquivo
+    pub fn extract(&self, path: &Path, bytes: &[u8]) -> Result<Option<String>, ExtractError> {
+        let Some(extractor) = self.for_file(path, bytes) else {
+            return Ok(None);
+        };
+        if bytes.len() as u64 > self.limits.max_input_bytes {
+            return Err(ExtractError::TooLarge { limit: self.limits.max_input_bytes });
+        }
+        extractor.extract(bytes, &Budget::new(&self.limits)).map(Some)
+    }
+}
+
+/// Tipo MIME pelos primeiros bytes, para formatos com assinatura conhecida
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        Some("application/zip")
+    } else {
+        None
+    }
+}
+
+/// Posição de `needle` em `haystack` a partir de `from`
+pub(crate) fn find_bytes(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= haystack.len() || needle.is_empty() {
+        return None;
+    }
+    haystack[from..].windows(needle.len()).position(|window| window == needle).map(|pos| pos + from)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn extract_with(extractor: &dyn Extractor, bytes: &[u8]) -> Result<String, ExtractError> {
+        let limits = ExtractionLimits::default();
+        extractor.extract(bytes, &Budget::new(&limits))
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let config = SearchConfig::default();
+        let registry = ExtractorRegistry::from_config(&config);
+
+        assert_eq!(registry.for_extension("PDF").unwrap().name(), "pdf");
+        assert_eq!(registry.for_extension("docx").unwrap().name(), "ooxml");
+        assert_eq!(registry.for_extension("rs").unwrap().name(), "text");
+        assert!(registry.for_extension("zip").is_none());
+        assert_eq!(registry.for_mime("application/pdf").unwrap().name(), "pdf");
+
+        // Sem extensão, pelo conteúdo
+        let pdf = include_bytes!("../../fixtures/sample.pdf");
+        assert_eq!(registry.for_file(Path::new("/tmp/scan"), pdf).unwrap().name(), "pdf");
+        assert!(registry.extract(Path::new("/tmp/photo.png"), b"\x89PNG").unwrap().is_none());
+
+        let config = SearchConfig { index_archive_listings: true, ..SearchConfig::default() };
+        let registry = ExtractorRegistry::from_config(&config);
+        assert_eq!(registry.for_extension("zip").unwrap().name(), "zip");
+    }
+
+    #[test]
+    fn test_limits() {
+        let docx = include_bytes!("../../fixtures/sample.docx");
+
+        let mut registry = ExtractorRegistry::new(ExtractionLimits { max_output_bytes: 10, ..ExtractionLimits::default() });
+        registry.register(Arc::new(OoxmlExtractor));
+        assert_eq!(registry.extract(Path::new("a.docx"), docx).unwrap().unwrap(), "Quarterly");
+
+        let mut registry = ExtractorRegistry::new(ExtractionLimits { max_input_bytes: 100, ..ExtractionLimits::default() });
+        registry.register(Arc::new(OoxmlExtractor));
+        assert_eq!(registry.extract(Path::new("a.docx"), docx), Err(ExtractError::TooLarge { limit: 100 }));
+
+        let mut registry = ExtractorRegistry::new(ExtractionLimits { timeout: Duration::ZERO, ..ExtractionLimits::default() });
+        registry.register(Arc::new(OoxmlExtractor));
+        assert_eq!(registry.extract(Path::new("a.docx"), docx), Err(ExtractError::TimedOut));
+
+        let mut buffer = TextBuffer::new(4);
+        assert!(!buffer.push_str("ação"));
+        assert_eq!(buffer.into_string(), "aç");
+    }
+}
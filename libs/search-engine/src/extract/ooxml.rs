@@ -0,0 +1,235 @@
+//! Documentos Office Open XML (docx, xlsx, pptx)
+//!
+//! São arquivos ZIP com partes XML, lidas com `quick-xml`; o texto vem dos
+//! elementos de texto de cada formato (`w:t`, `t`, `a:t`). O tipo é detectado
+//! pelas partes presentes, não pela extensão, então variantes como `.docm`
+//! também servem.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use super::zip::ZipArchive;
+use super::{Budget, ExtractError, Extractor, TextBuffer};
+
+/// Elementos de texto e elementos cujo fechamento termina uma linha
+struct XmlTextRules {
+    text_elements: &'static [&'static str],
+    line_elements: &'static [&'static str],
+}
+
+const WORD_RULES: XmlTextRules = XmlTextRules { text_elements: &["t"], line_elements: &["p", "tr"] };
+const SHARED_STRING_RULES: XmlTextRules = XmlTextRules { text_elements: &["t"], line_elements: &["si"] };
+const SHEET_RULES: XmlTextRules = XmlTextRules { text_elements: &["t"], line_elements: &["row"] };
+const SLIDE_RULES: XmlTextRules = XmlTextRules { text_elements: &["t"], line_elements: &["p"] };
+
+pub struct OoxmlExtractor;
+
+impl Extractor for OoxmlExtractor {
+    fn name(&self) -> &'static str {
+        "ooxml"
+    }
+
+    fn extensions(&self) -> &[&'static str] {
+        &["docx", "docm", "xlsx", "xlsm", "pptx", "pptm"]
+    }
+
+    fn mime_types(&self) -> &[&'static str] {
+        &[
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        ]
+    }
+
+    fn extract(&self, bytes: &[u8], budget: &Budget) -> Result<String, ExtractError> {
+        let archive = ZipArchive::parse(bytes)?;
+        let mut text = budget.text_buffer();
+
+        if archive.find("word/document.xml").is_some() {
+            for part in ["word/document.xml", "word/footnotes.xml", "word/endnotes.xml"] {
+                append_part(&archive, part, &WORD_RULES, &mut text, budget)?;
+            }
+        } else if archive.find("xl/workbook.xml").is_some() {
+            if let Some(workbook) = archive.read_by_name("xl/workbook.xml", budget)? {
+                for name in sheet_names(&workbook) {
+                    text.push_str(&name);
+                    text.newline();
+                }
+            }
+            append_part(&archive, "xl/sharedStrings.xml", &SHARED_STRING_RULES, &mut text, budget)?;
+            for part in numbered_parts(&archive, "xl/worksheets/sheet") {
+                append_part(&archive, &part, &SHEET_RULES, &mut text, budget)?;
+            }
+        } else if archive.find("ppt/presentation.xml").is_some() {
+            for part in numbered_parts(&archive, "ppt/slides/slide") {
+                append_part(&archive, &part, &SLIDE_RULES, &mut text, budget)?;
+            }
+        } else {
+            return Err(ExtractError::Malformed("not an Office Open XML document".to_string()));
+        }
+
+        Ok(text.into_string())
+    }
+}
+
+fn append_part(
+    archive: &ZipArchive,
+    part: &str,
+    rules: &XmlTextRules,
+    text: &mut TextBuffer,
+    budget: &Budget,
+) -> Result<(), ExtractError> {
+    if text.is_full() {
+        return Ok(());
+    }
+    if let Some(xml) = archive.read_by_name(part, budget)? {
+        xml_text(&xml, rules, text, budget)?;
+        text.newline();
+    }
+    Ok(())
+}
+
+/// Partes `<prefix>N.xml` em ordem numérica (slide2 antes de slide10)
+fn numbered_parts(archive: &ZipArchive, prefix: &str) -> Vec<String> {
+    let mut parts: Vec<(u32, String)> = archive
+        .entries()
+        .iter()
+        .filter_map(|entry| {
+            let number = entry.name.strip_prefix(prefix)?.strip_suffix(".xml")?.parse().ok()?;
+            Some((number, entry.name.clone()))
+        })
+        .collect();
+    parts.sort();
+    parts.into_iter().map(|(_, name)| name).collect()
+}
+
+fn xml_error(error: impl std::fmt::Display) -> ExtractError {
+    ExtractError::Malformed(format!("xml: {}", error))
+}
+
+/// Nomes das planilhas, na ordem do `workbook.xml`
+fn sheet_names(workbook: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut reader = Reader::from_reader(workbook);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) if tag.local_name().as_ref() == b"sheet" => {
+                if let Some(name) = tag.try_get_attribute("name").ok().flatten() {
+                    if let Ok(name) = name.unescape_value() {
+                        names.push(name.into_owned());
+                    }
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    names
+}
+
+/// Elementos vazios que viram espaço em branco no texto
+fn inline_break(name: &[u8], text: &mut TextBuffer) {
+    match name {
+        b"tab" => {
+            text.push_str("\t");
+        }
+        b"br" | b"cr" => text.newline(),
+        _ => {}
+    }
+}
+
+/// Texto dos elementos de `rules`, com quebras de linha e tabulações
+fn xml_text(xml: &[u8], rules: &XmlTextRules, text: &mut TextBuffer, budget: &Budget) -> Result<(), ExtractError> {
+    let is_text = |name: &[u8]| rules.text_elements.iter().any(|element| element.as_bytes() == name);
+    let is_line = |name: &[u8]| rules.line_elements.iter().any(|element| element.as_bytes() == name);
+
+    let mut reader = Reader::from_reader(xml);
+    let mut depth_in_text = 0usize;
+    let mut events = 0usize;
+
+    loop {
+        if text.is_full() {
+            return Ok(());
+        }
+        events += 1;
+        if events.is_multiple_of(4096) {
+            budget.check_time()?;
+        }
+
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(tag) => {
+                let name = tag.local_name();
+                if is_text(name.as_ref()) {
+                    depth_in_text += 1;
+                } else {
+                    inline_break(name.as_ref(), text);
+                }
+            }
+            Event::Empty(tag) => {
+                let name = tag.local_name();
+                if !is_text(name.as_ref()) {
+                    inline_break(name.as_ref(), text);
+                }
+            }
+            Event::End(tag) => {
+                let name = tag.local_name();
+                if is_text(name.as_ref()) {
+                    depth_in_text = depth_in_text.saturating_sub(1);
+                } else if is_line(name.as_ref()) {
+                    text.newline();
+                }
+            }
+            Event::Text(content) if depth_in_text > 0 => match content.unescape() {
+                Ok(content) => {
+                    text.push_str(&content);
+                }
+                // Entidade desconhecida: o texto vai como está
+                Err(_) => {
+                    text.push_str(&String::from_utf8_lossy(&content));
+                }
+            },
+            Event::CData(content) if depth_in_text > 0 => {
+                text.push_str(&String::from_utf8_lossy(&content));
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::tests::extract_with;
+
+    #[test]
+    fn test_ooxml_fixtures() {
+        let docx = extract_with(&OoxmlExtractor, include_bytes!("../../fixtures/sample.docx")).unwrap();
+        assert_eq!(docx, "Quarterly budget review\nMarketing & sales\tRelatório");
+
+        let xlsx = extract_with(&OoxmlExtractor, include_bytes!("../../fixtures/sample.xlsx")).unwrap();
+        assert_eq!(xlsx, "Forecast 2024\nNotes\nRevenue\nOperating expenses\nInline cell");
+
+        let pptx = extract_with(&OoxmlExtractor, include_bytes!("../../fixtures/sample.pptx")).unwrap();
+        assert_eq!(pptx, "Roadmap overview\nSecond slide\nTenth slide");
+
+        // Um ZIP qualquer não é um documento do Office
+        let zip = include_bytes!("../../fixtures/sample.zip");
+        assert!(matches!(extract_with(&OoxmlExtractor, zip), Err(ExtractError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_xml_text() {
+        let limits = crate::extract::ExtractionLimits::default();
+        let budget = Budget::new(&limits);
+        let mut text = budget.text_buffer();
+        let xml = br#"<?xml version="1.0"?><w:body><w:p><w:r><w:t xml:space="preserve">a &amp; b &lt;c&gt; &#233;&#x1F600; </w:t><w:tab/><w:t><![CDATA[<raw>]]></w:t></w:r></w:p><w:p><w:t>next</w:t></w:p></w:body>"#;
+        xml_text(xml, &WORD_RULES, &mut text, &budget).unwrap();
+        assert_eq!(text.into_string(), "a & b <c> é😀 \t<raw>\nnext");
+
+        assert!(matches!(
+            xml_text(b"<w:p><w:t>open</w:p>", &WORD_RULES, &mut budget.text_buffer(), &budget),
+            Err(ExtractError::Malformed(_))
+        ));
+    }
+}
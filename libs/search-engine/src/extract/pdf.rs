@@ -0,0 +1,607 @@
+//! Texto de PDFs
+//!
+//! O arquivo é lido com `lopdf`; para cada página, os content streams são
+//! descomprimidos (só `FlateDecode`, dentro do limite de `Budget`) e os
+//! operadores de texto (`Tj`, `TJ`, `'`, `"`) executados. As strings são
+//! decodificadas pela fonte ativa (`Tf`): pelo CMap `ToUnicode` quando a fonte
+//! tem um; fontes compostas (Type0) sem ele não têm codificação utilizável e
+//! seu texto é descartado em vez de virar lixo. Form XObjects não são seguidos.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+
+use super::{find_bytes, Budget, ExtractError, Extractor, TextBuffer};
+
+/// Deslocamento no `TJ` (milésimos de em) a partir do qual há um espaço entre palavras
+const TJ_SPACE_THRESHOLD: f64 = -200.0;
+
+pub struct PdfExtractor;
+
+impl Extractor for PdfExtractor {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn extensions(&self) -> &[&'static str] {
+        &["pdf"]
+    }
+
+    fn mime_types(&self) -> &[&'static str] {
+        &["application/pdf"]
+    }
+
+    fn extract(&self, bytes: &[u8], budget: &Budget) -> Result<String, ExtractError> {
+        if find_bytes(&bytes[..bytes.len().min(1024)], b"%PDF-", 0).is_none() {
+            return Err(ExtractError::Malformed("missing PDF header".to_string()));
+        }
+        if find_bytes(bytes, b"/Encrypt", 0).is_some() {
+            return Err(ExtractError::Unsupported("encrypted PDF".to_string()));
+        }
+
+        let document = Document::load_mem(bytes).map_err(|e| ExtractError::Malformed(format!("pdf: {}", e)))?;
+        let mut text = budget.text_buffer();
+        for page_id in document.get_pages().into_values() {
+            budget.check_time()?;
+            let fonts = page_fonts(&document, page_id, budget)?;
+
+            // Os streams de uma página formam um só content stream
+            let mut content = Vec::new();
+            for stream_id in document.get_page_contents(page_id) {
+                let Ok(stream) = document.get_object(stream_id).and_then(Object::as_stream) else {
+                    continue;
+                };
+                match stream_content(stream, budget) {
+                    Ok(Some(data)) => {
+                        content.extend_from_slice(&data);
+                        content.push(b'\n');
+                    }
+                    Ok(None) | Err(ExtractError::Malformed(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            content_text(&content, &fonts, &mut text, budget)?;
+            text.newline();
+            if text.is_full() {
+                break;
+            }
+        }
+
+        Ok(text.into_string())
+    }
+}
+
+/// Conteúdo de um stream sem filtro ou com `FlateDecode`; `None` para os demais filtros
+fn stream_content(stream: &Stream, budget: &Budget) -> Result<Option<Vec<u8>>, ExtractError> {
+    let filters = stream.filters().unwrap_or_default();
+    match filters.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => Ok(Some(stream.content.clone())),
+        ["FlateDecode"] => inflate(&stream.content, budget).map(Some),
+        // DCT, LZW, etc.: não são texto ou não valem o suporte
+        _ => Ok(None),
+    }
+}
+
+/// zlib com o limite de bytes descomprimidos (proteção contra bombas)
+fn inflate(data: &[u8], budget: &Budget) -> Result<Vec<u8>, ExtractError> {
+    budget.check_time()?;
+    let limit = budget.limits().max_decompressed_bytes;
+    let mut content = Vec::new();
+    ZlibDecoder::new(data)
+        .take(limit + 1)
+        .read_to_end(&mut content)
+        .map_err(|e| ExtractError::Malformed(format!("pdf: {}", e)))?;
+    if content.len() as u64 > limit {
+        return Err(ExtractError::TooLarge { limit });
+    }
+    Ok(content)
+}
+
+/// Como as strings de uma fonte viram texto
+#[derive(Debug)]
+enum FontEncoding {
+    /// Fonte simples sem `ToUnicode`: PDFDocEncoding/UTF-16 com BOM
+    Simple,
+    /// CMap `ToUnicode` da fonte
+    ToUnicode(ToUnicodeMap),
+    /// Fonte composta sem `ToUnicode`: os códigos são glifos, não caracteres
+    Unusable,
+}
+
+impl FontEncoding {
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            FontEncoding::Simple => decode_string(bytes),
+            FontEncoding::ToUnicode(map) => map.decode(bytes),
+            FontEncoding::Unusable => String::new(),
+        }
+    }
+}
+
+/// Fontes dos recursos da página, pelo nome usado no `Tf`
+fn page_fonts(document: &Document, page_id: ObjectId, budget: &Budget) -> Result<HashMap<Vec<u8>, FontEncoding>, ExtractError> {
+    let mut fonts = HashMap::new();
+    for (name, font) in document.get_page_fonts(page_id).unwrap_or_default() {
+        fonts.insert(name, font_encoding(document, font, budget)?);
+    }
+    Ok(fonts)
+}
+
+fn font_encoding(document: &Document, font: &Dictionary, budget: &Budget) -> Result<FontEncoding, ExtractError> {
+    let to_unicode = font.get_deref(b"ToUnicode", document).and_then(Object::as_stream);
+    if let Ok(stream) = to_unicode {
+        match stream_content(stream, budget) {
+            Ok(Some(cmap)) => return Ok(FontEncoding::ToUnicode(ToUnicodeMap::parse(&cmap))),
+            Ok(None) | Err(ExtractError::Malformed(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let composite = font.get(b"Subtype").and_then(Object::as_name).is_ok_and(|subtype| subtype == b"Type0");
+    Ok(if composite { FontEncoding::Unusable } else { FontEncoding::Simple })
+}
+
+/// Códigos de caractere para texto, lidos de um CMap `ToUnicode`
+#[derive(Debug, Default)]
+struct ToUnicodeMap {
+    /// Bytes por código, do `codespacerange` (1 quando ausente)
+    code_length: usize,
+    chars: HashMap<u32, String>,
+}
+
+impl ToUnicodeMap {
+    fn parse(cmap: &[u8]) -> Self {
+        let mut map = Self { code_length: 0, chars: HashMap::new() };
+        let tokens = cmap_tokens(cmap);
+        let mut section: &[u8] = b"";
+        let mut i = 0;
+
+        while i < tokens.len() {
+            match &tokens[i] {
+                CmapToken::Keyword(keyword) if keyword.starts_with(b"begin") => {
+                    section = keyword;
+                    i += 1;
+                }
+                CmapToken::Keyword(_) => {
+                    section = b"";
+                    i += 1;
+                }
+                CmapToken::Hex(low) if section == b"begincodespacerange" => {
+                    if map.code_length == 0 {
+                        map.code_length = low.len();
+                    }
+                    i += 2;
+                }
+                CmapToken::Hex(source) if section == b"beginbfchar" => {
+                    if let Some(CmapToken::Hex(target)) = tokens.get(i + 1) {
+                        map.chars.insert(code_value(source), utf16_text(target));
+                    }
+                    i += 2;
+                }
+                CmapToken::Hex(low) if section == b"beginbfrange" => {
+                    let (Some(CmapToken::Hex(high)), Some(target)) = (tokens.get(i + 1), tokens.get(i + 2)) else {
+                        break;
+                    };
+                    let (low, high) = (code_value(low), code_value(high));
+                    // Intervalos enormes vêm de CMaps inválidos
+                    if high >= low && high - low <= 0xffff {
+                        match target {
+                            CmapToken::Hex(first) => {
+                                for (offset, code) in (low..=high).enumerate() {
+                                    map.chars.insert(code, offset_utf16_text(first, offset as u32));
+                                }
+                            }
+                            CmapToken::Array(targets) => {
+                                for (code, target) in (low..=high).zip(targets) {
+                                    map.chars.insert(code, utf16_text(target));
+                                }
+                            }
+                            CmapToken::Keyword(_) => {}
+                        }
+                    }
+                    i += 3;
+                }
+                _ => i += 1,
+            }
+        }
+
+        map.code_length = map.code_length.clamp(1, 4);
+        map
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .chunks(self.code_length)
+            .filter_map(|code| self.chars.get(&code_value(code)))
+            .flat_map(|text| text.chars())
+            .filter(|c| !c.is_control() || *c == '\t')
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+enum CmapToken {
+    Hex(Vec<u8>),
+    Array(Vec<Vec<u8>>),
+    Keyword(Vec<u8>),
+}
+
+/// Tokens de um CMap: strings hexadecimais, arrays delas e palavras-chave
+fn cmap_tokens(cmap: &[u8]) -> Vec<CmapToken> {
+    let mut tokens = Vec::new();
+    let mut array: Option<Vec<Vec<u8>>> = None;
+    let mut pos = 0;
+
+    while pos < cmap.len() {
+        match cmap[pos] {
+            b'%' => {
+                while pos < cmap.len() && cmap[pos] != b'\n' && cmap[pos] != b'\r' {
+                    pos += 1;
+                }
+            }
+            b'<' if cmap.get(pos + 1) == Some(&b'<') => pos += 2,
+            b'<' => {
+                let end = cmap[pos..].iter().position(|&b| b == b'>').map_or(cmap.len(), |end| pos + end);
+                let string = hex_string(&cmap[pos + 1..end]);
+                pos = end + 1;
+                match &mut array {
+                    Some(array) => array.push(string),
+                    None => tokens.push(CmapToken::Hex(string)),
+                }
+            }
+            b'[' => {
+                array = Some(Vec::new());
+                pos += 1;
+            }
+            b']' => {
+                tokens.extend(array.take().map(CmapToken::Array));
+                pos += 1;
+            }
+            byte if is_delimiter(byte) => pos += 1,
+            _ => {
+                let start = pos;
+                while pos < cmap.len() && !is_delimiter(cmap[pos]) {
+                    pos += 1;
+                }
+                tokens.push(CmapToken::Keyword(cmap[start..pos].to_vec()));
+            }
+        }
+    }
+    tokens
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes.iter().take(4).fold(0, |value, &byte| value << 8 | byte as u32)
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+    char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+}
+
+/// Destino de um `bfrange`: o último código UTF-16 avança junto com o código de origem
+fn offset_utf16_text(first: &[u8], offset: u32) -> String {
+    let mut units: Vec<u16> = first.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+    if let Some(last) = units.last_mut() {
+        *last = last.wrapping_add(offset as u16);
+    }
+    char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || b"()<>[]{}/%".contains(&byte)
+}
+
+/// Executa os operadores de texto de um content stream
+fn content_text(
+    content: &[u8],
+    fonts: &HashMap<Vec<u8>, FontEncoding>,
+    text: &mut TextBuffer,
+    budget: &Budget,
+) -> Result<(), ExtractError> {
+    // Fontes fora dos recursos da página são tratadas como simples
+    let mut font = &FontEncoding::Simple;
+    let mut strings: Vec<Vec<u8>> = Vec::new();
+    let mut numbers: Vec<f64> = Vec::new();
+    let mut names: Vec<&[u8]> = Vec::new();
+    // Texto de um array `[...]` em construção, para o `TJ`
+    let mut array: Option<String> = None;
+    let mut pos = 0;
+    let mut tokens = 0usize;
+
+    while pos < content.len() {
+        tokens += 1;
+        if tokens.is_multiple_of(4096) {
+            budget.check_time()?;
+        }
+        if text.is_full() {
+            return Ok(());
+        }
+
+        let byte = content[pos];
+        match byte {
+            _ if byte.is_ascii_whitespace() => pos += 1,
+            b'%' => {
+                while pos < content.len() && content[pos] != b'\n' && content[pos] != b'\r' {
+                    pos += 1;
+                }
+            }
+            b'(' => {
+                let (string, end) = literal_string(content, pos + 1);
+                pos = end;
+                match &mut array {
+                    Some(array) => array.push_str(&font.decode(&string)),
+                    None => strings.push(string),
+                }
+            }
+            b'<' if content.get(pos + 1) == Some(&b'<') => pos += 2,
+            b'>' if content.get(pos + 1) == Some(&b'>') => pos += 2,
+            b'<' => {
+                let end = content[pos..].iter().position(|&b| b == b'>').map_or(content.len(), |end| pos + end);
+                let string = hex_string(&content[pos + 1..end]);
+                pos = end + 1;
+                match &mut array {
+                    Some(array) => array.push_str(&font.decode(&string)),
+                    None => strings.push(string),
+                }
+            }
+            b'[' => {
+                array = Some(String::new());
+                pos += 1;
+            }
+            b']' => pos += 1,
+            b'/' => {
+                let start = pos + 1;
+                pos = start;
+                while pos < content.len() && !is_delimiter(content[pos]) {
+                    pos += 1;
+                }
+                names.push(&content[start..pos]);
+            }
+            _ => {
+                let start = pos;
+                while pos < content.len() && !is_delimiter(content[pos]) {
+                    pos += 1;
+                }
+                if pos == start {
+                    pos += 1;
+                    continue;
+                }
+                let token = &content[start..pos];
+
+                if let Some(number) = std::str::from_utf8(token).ok().and_then(|t| t.parse::<f64>().ok()) {
+                    match &mut array {
+                        Some(array) if number <= TJ_SPACE_THRESHOLD && !array.ends_with(' ') => array.push(' '),
+                        Some(_) => {}
+                        None => numbers.push(number),
+                    }
+                    continue;
+                }
+
+                match token {
+                    b"Tf" => {
+                        if let Some(name) = names.last() {
+                            font = fonts.get(*name).unwrap_or(&FontEncoding::Simple);
+                        }
+                    }
+                    b"Tj" => {
+                        if let Some(string) = strings.last() {
+                            text.push_str(&font.decode(string));
+                        }
+                    }
+                    b"TJ" => {
+                        if let Some(array) = array.take() {
+                            text.push_str(&array);
+                        }
+                    }
+                    b"'" | b"\"" => {
+                        text.newline();
+                        if let Some(string) = strings.last() {
+                            text.push_str(&font.decode(string));
+                        }
+                    }
+                    b"T*" | b"ET" => text.newline(),
+                    b"Td" | b"TD" => {
+                        // Deslocamento vertical: nova linha; horizontal: novo trecho na mesma linha
+                        if numbers.len() >= 2 && numbers[numbers.len() - 1] != 0.0 {
+                            text.newline();
+                        } else {
+                            text.space();
+                        }
+                    }
+                    b"BI" => {
+                        // Imagem inline: pular os dados binários até `EI`
+                        pos = find_bytes(content, b"EI", pos).map_or(content.len(), |end| end + 2);
+                    }
+                    _ => {}
+                }
+                strings.clear();
+                numbers.clear();
+                names.clear();
+                array = None;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// String literal `( ... )` a partir de `start` (depois do parêntese); retorna a posição seguinte
+fn literal_string(content: &[u8], start: usize) -> (Vec<u8>, usize) {
+    let mut string = Vec::new();
+    let mut depth = 1;
+    let mut pos = start;
+
+    while pos < content.len() {
+        let byte = content[pos];
+        pos += 1;
+        match byte {
+            b'\\' => {
+                let Some(&escaped) = content.get(pos) else {
+                    break;
+                };
+                pos += 1;
+                match escaped {
+                    b'n' => string.push(b'\n'),
+                    b'r' => string.push(b'\r'),
+                    b't' => string.push(b'\t'),
+                    b'b' => string.push(0x08),
+                    b'f' => string.push(0x0c),
+                    b'0'..=b'7' => {
+                        let mut value = (escaped - b'0') as u32;
+                        for _ in 0..2 {
+                            match content.get(pos) {
+                                Some(&digit @ b'0'..=b'7') => {
+                                    value = value * 8 + (digit - b'0') as u32;
+                                    pos += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        string.push(value as u8);
+                    }
+                    // Continuação de linha
+                    b'\n' => {}
+                    b'\r' => {
+                        if content.get(pos) == Some(&b'\n') {
+                            pos += 1;
+                        }
+                    }
+                    other => string.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                string.push(byte);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                string.push(byte);
+            }
+            _ => string.push(byte),
+        }
+    }
+    (string, pos)
+}
+
+fn hex_string(hex: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .iter()
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    // Um dígito final sozinho vale como seguido de 0
+    digits.chunks(2).map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0)).collect()
+}
+
+/// UTF-16BE com BOM ou, sem ele, PDFDocEncoding (aproximado por Latin-1)
+fn decode_string(bytes: &[u8]) -> String {
+    let decoded: String = match bytes.strip_prefix(&[0xfe, 0xff]) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+            char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+        }
+        None => bytes.iter().map(|&b| b as char).collect(),
+    };
+    decoded.chars().filter(|c| !c.is_control() || *c == '\t').collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::tests::extract_with;
+
+    #[test]
+    fn test_pdf_fixture() {
+        let text = extract_with(&PdfExtractor, include_bytes!("../../fixtures/sample.pdf")).unwrap();
+        assert_eq!(text, "Quarterly budget report\nRevenue grew (fast)\nRelatório\nCafé total");
+        // O stream da imagem tem operadores de texto, mas é ignorado
+        assert!(!text.contains("not text"));
+    }
+
+    #[test]
+    fn test_pdf_rejections() {
+        assert!(matches!(extract_with(&PdfExtractor, b"plain text"), Err(ExtractError::Malformed(_))));
+        assert!(matches!(
+            extract_with(&PdfExtractor, b"%PDF-1.7\ntrailer << /Encrypt 5 0 R >>"),
+            Err(ExtractError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_string_decoding() {
+        assert_eq!(literal_string(b"a\\(b\\) (c) \\101\\\nd) tail", 0), (b"a(b) (c) Ad".to_vec(), 19));
+        assert_eq!(hex_string(b"48 65 6C6C 6F7"), b"Hellop");
+        assert_eq!(decode_string(&[0xfe, 0xff, 0x00, 0xe7, 0x00, 0xe3]), "çã");
+    }
+
+    /// PDF de uma página com uma fonte composta com `ToUnicode` (F1) e outra sem (F2)
+    fn composite_font_pdf(content: &[u8], filter: Option<&str>) -> Vec<u8> {
+        use lopdf::dictionary;
+
+        let cmap = b"/CIDInit /ProcSet findresource begin 12 dict begin begincmap
+1 begincodespacerange <0000> <FFFF> endcodespacerange
+2 beginbfchar <0001> <0048> <0002> <0069> endbfchar
+2 beginbfrange <0003> <0004> <0041> <0010> <0011> [<00E7> <00E3>] endbfrange
+endcmap CMapName currentdict /CMap defineresource pop end end";
+
+        let mut document = Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let cmap_id = document.add_object(Stream::new(Dictionary::new(), cmap.to_vec()));
+        let mapped = document.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type0", "BaseFont" => "Mapped", "Encoding" => "Identity-H", "ToUnicode" => cmap_id,
+        });
+        let unmapped = document.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type0", "BaseFont" => "Unmapped", "Encoding" => "Identity-H",
+        });
+        let mut stream_dict = Dictionary::new();
+        if let Some(filter) = filter {
+            stream_dict.set("Filter", Object::Name(filter.as_bytes().to_vec()));
+        }
+        let content_id = document.add_object(Stream::new(stream_dict, content.to_vec()));
+        let page_id = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => mapped, "F2" => unmapped } },
+        });
+        document.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages", "Kids" => vec![page_id.into()], "Count" => 1,
+        }));
+        let catalog_id = document.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        document.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        document.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_to_unicode_cmaps() {
+        let content = b"BT /F1 12 Tf <0001000200030004> Tj 0 -14 Td [<0010> -300 <0011>] TJ /F2 12 Tf <00050006> Tj ET";
+        let pdf = composite_font_pdf(content, None);
+        // Os glifos de F2 não têm codificação: nada de lixo no texto
+        assert_eq!(extract_with(&PdfExtractor, &pdf).unwrap(), "HiAB\nç ã");
+    }
+
+    #[test]
+    fn test_compressed_content_limit() {
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write;
+
+        let mut content = b"BT /F1 12 Tf <0001> Tj ET ".to_vec();
+        content.resize(2 * 1024 * 1024, b' ');
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&content).unwrap();
+        let pdf = composite_font_pdf(&encoder.finish().unwrap(), Some("FlateDecode"));
+
+        assert_eq!(extract_with(&PdfExtractor, &pdf).unwrap(), "H");
+
+        let limits = crate::extract::ExtractionLimits { max_decompressed_bytes: 1024 * 1024, ..Default::default() };
+        assert_eq!(PdfExtractor.extract(&pdf, &Budget::new(&limits)), Err(ExtractError::TooLarge { limit: 1024 * 1024 }));
+    }
+}
@@ -0,0 +1,39 @@
+//! Texto puro e código-fonte
+
+use super::{Budget, ExtractError, Extractor};
+
+/// Extensões cujo conteúdo é indexado como texto
+pub const PLAIN_TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "rst", "csv", "log",
+    "json", "toml", "yaml", "yml", "xml", "ini",
+    "html", "htm", "css", "scss",
+    "rs", "js", "jsx", "ts", "tsx", "py", "go", "java", "kt", "rb", "php",
+    "c", "cpp", "cc", "h", "hpp", "cs", "swift", "sh", "sql",
+];
+
+pub struct PlainTextExtractor;
+
+impl Extractor for PlainTextExtractor {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn extensions(&self) -> &[&'static str] {
+        PLAIN_TEXT_EXTENSIONS
+    }
+
+    fn mime_types(&self) -> &[&'static str] {
+        &["text/plain"]
+    }
+
+    fn extract(&self, bytes: &[u8], budget: &Budget) -> Result<String, ExtractError> {
+        // Bytes nulos indicam um arquivo binário, mesmo com extensão de texto
+        if bytes.iter().take(8192).any(|&b| b == 0) {
+            return Err(ExtractError::Malformed("binary content".to_string()));
+        }
+
+        let mut text = budget.text_buffer();
+        text.push_str(&String::from_utf8_lossy(bytes));
+        Ok(text.into_string())
+    }
+}
@@ -0,0 +1,167 @@
+//! Leitura de arquivos ZIP e listagem do conteúdo
+//!
+//! Usa o crate `zip` (com ZIP64 e DEFLATE); entradas criptografadas são
+//! recusadas e o tamanho descomprimido é limitado durante a leitura, não só
+//! pelo valor declarado no diretório central.
+
+use std::cell::RefCell;
+use std::io::{Cursor, Read};
+
+use ::zip::result::ZipError;
+
+use super::{Budget, ExtractError, Extractor};
+
+fn zip_error(error: ZipError) -> ExtractError {
+    match error {
+        ZipError::UnsupportedArchive(what) => ExtractError::Unsupported(format!("zip: {}", what)),
+        error => ExtractError::Malformed(format!("zip: {}", error)),
+    }
+}
+
+/// Entrada do diretório central
+#[derive(Debug, Clone)]
+pub struct ZipEntry {
+    pub name: String,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    index: usize,
+}
+
+impl ZipEntry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Arquivo ZIP em memória
+pub struct ZipArchive<'a> {
+    archive: RefCell<::zip::ZipArchive<Cursor<&'a [u8]>>>,
+    entries: Vec<ZipEntry>,
+}
+
+impl<'a> ZipArchive<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, ExtractError> {
+        let mut archive = ::zip::ZipArchive::new(Cursor::new(data)).map_err(zip_error)?;
+        let mut entries = Vec::with_capacity(archive.len().min(4096));
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index).map_err(zip_error)?;
+            entries.push(ZipEntry {
+                name: file.name().to_string(),
+                compressed_size: file.compressed_size(),
+                uncompressed_size: file.size(),
+                index,
+            });
+        }
+        Ok(Self { archive: RefCell::new(archive), entries })
+    }
+
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ZipEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Conteúdo descomprimido de `entry`, conferido pelo CRC
+    pub fn read(&self, entry: &ZipEntry, budget: &Budget) -> Result<Vec<u8>, ExtractError> {
+        budget.check_time()?;
+        let limit = budget.limits().max_decompressed_bytes;
+        if entry.uncompressed_size > limit {
+            return Err(ExtractError::TooLarge { limit });
+        }
+
+        let mut archive = self.archive.borrow_mut();
+        let file = archive.by_index(entry.index).map_err(zip_error)?;
+        if file.encrypted() {
+            return Err(ExtractError::Unsupported("encrypted zip entry".to_string()));
+        }
+
+        // O tamanho declarado pode ser falso: o limite vale durante a descompressão
+        let mut contents = Vec::with_capacity(entry.uncompressed_size.min(limit) as usize);
+        file.take(limit + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| ExtractError::Malformed(format!("zip: {}", e)))?;
+        if contents.len() as u64 > limit {
+            return Err(ExtractError::TooLarge { limit });
+        }
+        Ok(contents)
+    }
+
+    /// Conteúdo da entrada `name`, se existir
+    pub fn read_by_name(&self, name: &str, budget: &Budget) -> Result<Option<Vec<u8>>, ExtractError> {
+        self.find(name).map(|entry| self.read(entry, budget)).transpose()
+    }
+}
+
+/// Indexa os nomes dos arquivos contidos num ZIP, um por linha
+pub struct ZipListingExtractor;
+
+impl Extractor for ZipListingExtractor {
+    fn name(&self) -> &'static str {
+        "zip"
+    }
+
+    fn extensions(&self) -> &[&'static str] {
+        &["zip"]
+    }
+
+    fn mime_types(&self) -> &[&'static str] {
+        &["application/zip"]
+    }
+
+    fn extract(&self, bytes: &[u8], budget: &Budget) -> Result<String, ExtractError> {
+        let archive = ZipArchive::parse(bytes)?;
+        let mut text = budget.text_buffer();
+        for entry in archive.entries().iter().filter(|entry| !entry.is_dir()) {
+            text.push_str(&entry.name);
+            text.newline();
+            if text.is_full() {
+                break;
+            }
+        }
+        Ok(text.into_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::tests::extract_with;
+    use crate::extract::ExtractionLimits;
+
+    #[test]
+    fn test_zip_archive_and_listing() {
+        let bytes = include_bytes!("../../fixtures/sample.zip");
+        let limits = ExtractionLimits::default();
+        let budget = Budget::new(&limits);
+
+        let archive = ZipArchive::parse(bytes).unwrap();
+        assert_eq!(archive.entries().len(), 3);
+        assert_eq!(archive.read_by_name("docs/readme.txt", &budget).unwrap().unwrap(), "hello ".repeat(100).as_bytes());
+        assert_eq!(archive.read_by_name("photos/beach.jpg", &budget).unwrap().unwrap(), (0..=255).collect::<Vec<u8>>());
+        assert!(archive.read_by_name("missing", &budget).unwrap().is_none());
+
+        assert_eq!(extract_with(&ZipListingExtractor, bytes).unwrap(), "docs/readme.txt\nphotos/beach.jpg");
+        assert!(matches!(extract_with(&ZipListingExtractor, b"PK\x03\x04 not really"), Err(ExtractError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_zip_bomb_is_rejected() {
+        // 4 MB de zeros comprimidos em ~4 KB
+        let bytes = include_bytes!("../../fixtures/bomb.zip");
+        let archive = ZipArchive::parse(bytes).unwrap();
+        let entry = &archive.entries()[0];
+
+        let limits = ExtractionLimits::default();
+        assert_eq!(archive.read(entry, &Budget::new(&limits)).unwrap().len(), 4 * 1024 * 1024);
+
+        let limits = ExtractionLimits { max_decompressed_bytes: 1024 * 1024, ..ExtractionLimits::default() };
+        assert_eq!(archive.read(entry, &Budget::new(&limits)), Err(ExtractError::TooLarge { limit: 1024 * 1024 }));
+
+        // Tamanho declarado falso: o limite vale durante a descompressão
+        let mut entry = entry.clone();
+        entry.uncompressed_size = 10;
+        assert_eq!(archive.read(&entry, &Budget::new(&limits)), Err(ExtractError::TooLarge { limit: 1024 * 1024 }));
+    }
+}
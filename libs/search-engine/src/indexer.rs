@@ -0,0 +1,408 @@
+//! Indexação de diretórios e arquivos
+
+use anyhow::Result;
+use log::{debug, info, warn}; // Removido `error` que não era usado, limpando um warning.
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+use crate::config::SearchConfig;
+use crate::engine::SearchEngine;
+use crate::exclusion::Exclusions;
+use crate::extract::ExtractorRegistry;
+use crate::schema::{Embedding, FileFingerprint, IndexedDocument};
+use crate::semantic;
+
+#[derive(Clone)]
+pub struct FileIndexer {
+    search_engine: Arc<SearchEngine>,
+    config: SearchConfig,
+    exclusions: Arc<Exclusions>,
+    extractors: Arc<ExtractorRegistry>,
+}
+
+impl FileIndexer {
+    pub fn new(search_engine: Arc<SearchEngine>, config: SearchConfig) -> Self {
+        let exclusions = Arc::new(config.exclusions());
+        let extractors = Arc::new(ExtractorRegistry::from_config(&config));
+        Self { search_engine, config, exclusions, extractors }
+    }
+
+    /// Usa `extractors` no lugar dos extratores embutidos
+    pub fn with_extractors(mut self, extractors: ExtractorRegistry) -> Self {
+        self.extractors = Arc::new(extractors);
+        self
+    }
+
+    pub fn extractors(&self) -> &ExtractorRegistry {
+        &self.extractors
+    }
+
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    pub fn exclusions(&self) -> &Exclusions {
+        &self.exclusions
+    }
+
+    /// Se `path` pode estar no índice: regras de exclusão (inclusive das pastas acima), extensão e tamanho
+    pub fn should_index_file(&self, path: &Path) -> bool {
+        path.is_file()
+            && !path.starts_with(self.search_engine.index_path())
+            && !self.exclusions.is_excluded_with_ancestors(path, false)
+            && self.config.matches_file_filters(path)
+    }
+
+    pub fn search_engine(&self) -> &Arc<SearchEngine> {
+        &self.search_engine
+    }
+
+    pub async fn start_indexing(&self) -> Result<()> {
+        info!("Starting file indexing...");
+        for root in self.config.roots() {
+            self.index_folder(root.clone()).await?;
+            self.remove_missing_under(&root).await?;
+        }
+        self.search_engine.commit_changes().await?;
+        info!("Initial indexing commit complete.");
+        Ok(())
+    }
+
+    /// Descarta o índice inteiro e indexa as pastas configuradas de novo
+    pub async fn rebuild(&self) -> Result<()> {
+        info!("Rebuilding search index...");
+        self.search_engine.clear().await?;
+        self.start_indexing().await
+    }
+
+    pub fn index_folder<'a>(&'a self, path: PathBuf) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            if !path.is_dir() { return Ok(()); }
+            // Uma pasta sem permissão de leitura não impede o resto da varredura
+            let mut entries = match fs::read_dir(&path).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Could not read directory '{}': {}", path.display(), e);
+                    return Ok(());
+                }
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let entry_path = entry.path();
+                if entry_path.is_dir() {
+                    // Nunca indexar o próprio diretório do índice
+                    if !self.exclusions.is_excluded(&entry_path, true)
+                        && !entry_path.starts_with(self.search_engine.index_path())
+                    {
+                        self.index_folder(entry_path).await?;
+                    }
+                } else if entry_path.is_file()
+                    && !self.exclusions.is_excluded(&entry_path, false)
+                    && self.config.matches_file_filters(&entry_path)
+                {
+                    if let Err(e) = self.index_file(&entry_path).await {
+                        warn!("Could not index file '{}': {}", entry_path.display(), e);
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove do índice arquivos sob `dir` que não existem mais ou passaram a ser ignorados
+    pub async fn remove_missing_under(&self, dir: &Path) -> Result<()> {
+        for path_str in self.search_engine.indexed_paths_under(dir)? {
+            let path = Path::new(&path_str);
+            if !self.should_index_file(path) {
+                self.search_engine.remove_document(&path_str).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Indexa um arquivo; retorna `false` quando ele não mudou desde a última indexação
+    pub async fn index_file(&self, path: &Path) -> Result<bool> {
+        let path_str = path.to_string_lossy().to_string();
+        let metadata = fs::metadata(path).await?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let size = metadata.len();
+
+        let existing = self.search_engine.get_document(&path_str)?;
+        if let Some(existing) = &existing {
+            if existing.fingerprint.modified == modified
+                && existing.fingerprint.size == size
+                && self.has_current_embedding(existing)
+            {
+                debug!("Unchanged, skipping: {}", path_str);
+                return Ok(false);
+            }
+        }
+
+        let contents = self.read_contents(path, size).await;
+        let content_hash = contents
+            .as_ref()
+            .map(|bytes| format!("{:x}", Sha256::digest(bytes)))
+            .unwrap_or_default();
+        let fingerprint = FileFingerprint { modified, size, content_hash };
+
+        let mut document = match existing {
+            // Só o mtime mudou (ex.: `touch`): reaproveita o conteúdo já extraído
+            Some(existing) if !fingerprint.content_hash.is_empty()
+                && existing.fingerprint.content_hash == fingerprint.content_hash =>
+            {
+                IndexedDocument { fingerprint, ..existing }
+            }
+            _ => IndexedDocument {
+                title: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                body: match contents {
+                    Some(bytes) => self.extract_body(path, bytes).await,
+                    None => String::new(),
+                },
+                path: path_str,
+                fingerprint,
+                embedding: None,
+            },
+        };
+        if !self.has_current_embedding(&document) {
+            document.embedding = self.embed(&document).await;
+        }
+        self.search_engine.upsert_document(document).await?;
+        Ok(true)
+    }
+
+    /// Se o documento já tem o vetor do embedder atual (sempre, com a busca semântica desligada)
+    fn has_current_embedding(&self, document: &IndexedDocument) -> bool {
+        match self.search_engine.embedder() {
+            Some(embedder) => document.embedding.as_ref().is_some_and(|e| e.model == embedder.name()),
+            None => true,
+        }
+    }
+
+    /// Vetor do título e do começo do conteúdo; `None` se o embedder falhar
+    async fn embed(&self, document: &IndexedDocument) -> Option<Embedding> {
+        let embedder = self.search_engine.embedder()?.clone();
+        let input = semantic::embedding_input(&document.title, &document.body);
+        let embedded = tokio::task::spawn_blocking(move || {
+            embedder.embed(&input).map(|vector| Embedding { model: embedder.name().to_string(), vector })
+        })
+        .await;
+
+        match embedded {
+            Ok(Ok(embedding)) => Some(embedding),
+            Ok(Err(e)) => {
+                warn!("Could not embed {}: {}", document.path, e);
+                None
+            }
+            Err(e) => {
+                warn!("Embedder panicked for {}: {}", document.path, e);
+                None
+            }
+        }
+    }
+
+    /// Texto do arquivo pelo extrator da extensão/MIME; vazio se nenhum atende ou a extração falha
+    async fn extract_body(&self, path: &Path, bytes: Vec<u8>) -> String {
+        let extractors = self.extractors.clone();
+        let owned_path = path.to_path_buf();
+        let extracted = tokio::task::spawn_blocking(move || extractors.extract(&owned_path, &bytes)).await;
+
+        match extracted {
+            Ok(Ok(body)) => body.unwrap_or_default(),
+            Ok(Err(e)) => {
+                debug!("No text extracted from {}: {}", path.display(), e);
+                String::new()
+            }
+            Err(e) => {
+                warn!("Text extraction panicked for {}: {}", path.display(), e);
+                String::new()
+            }
+        }
+    }
+
+    /// Lê o arquivo inteiro, respeitando `max_file_size_mb`
+    async fn read_contents(&self, path: &Path, size: u64) -> Option<Vec<u8>> {
+        let max_bytes = self.config.max_file_size_mb * 1024 * 1024;
+        if size > max_bytes {
+            return None;
+        }
+        fs::read(path).await.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SearchResult;
+    use crate::test_support::{corpus_config, index_corpus, index_corpus_with, scanned_indexer};
+
+    #[tokio::test]
+    async fn test_indexes_file_contents() {
+        let (engine, _corpus, _index) = index_corpus(&[
+            ("notes.md", b"Meeting notes: the quarterly budget was approved."),
+            ("main.rs", b"fn main() { println!(\"hello tantivy\"); }"),
+            ("todo.txt", b"buy milk"),
+        ]).await;
+
+        let results = engine.search("budget", 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "notes.md");
+
+        let results = engine.search("tantivy", 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "main.rs");
+    }
+
+    #[tokio::test]
+    async fn test_indexes_document_formats() {
+        let (engine, _corpus, _index) = index_corpus(&[
+            ("report.pdf", include_bytes!("../fixtures/sample.pdf")),
+            ("minutes.docx", include_bytes!("../fixtures/sample.docx")),
+            ("deck.pptx", include_bytes!("../fixtures/sample.pptx")),
+        ]).await;
+
+        assert_eq!(engine.search("revenue", 10).await.unwrap()[0].title, "report.pdf");
+        assert_eq!(engine.search("marketing", 10).await.unwrap()[0].title, "minutes.docx");
+        assert_eq!(engine.search("roadmap", 10).await.unwrap()[0].title, "deck.pptx");
+    }
+
+    #[tokio::test]
+    async fn test_snippet_highlights_match() {
+        let (engine, _corpus, _index) = index_corpus(&[
+            ("notes.md", b"Meeting notes: the quarterly budget was approved."),
+        ]).await;
+
+        let result = &engine.search("budget", 10).await.unwrap()[0];
+        let snippet = result.snippet.as_ref().expect("snippet for body match");
+        assert_eq!(result.highlights.len(), 1);
+        let (start, end) = result.highlights[0];
+        assert_eq!(&snippet[start..end], "budget");
+    }
+
+    #[tokio::test]
+    async fn test_skips_binary_and_oversized_content() {
+        let large = vec![b'a'; 2 * 1024 * 1024];
+        let (engine, _corpus, _index) = index_corpus_with(&[
+            ("binary.txt", b"needle\0\0\0"),
+            ("large.txt", &large),
+            ("small.txt", b"needle in a haystack"),
+        ], |config| SearchConfig { max_file_size_mb: 1, ..config }).await;
+
+        let results = engine.search("needle", 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "small.txt");
+
+        // O arquivo binário continua encontrável pelo nome
+        assert_eq!(engine.search("binary", 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reindexing_upserts_by_path() {
+        let (engine, corpus, _index) = index_corpus(&[
+            ("notes.md", b"quarterly budget"),
+            ("todo.txt", b"buy milk"),
+        ]).await;
+
+        // Um segundo scan não duplica nem reescreve arquivos inalterados
+        let indexer = scanned_indexer(&engine, corpus_config(corpus.path())).await;
+        assert_eq!(engine.search("budget", 10).await.unwrap().len(), 1);
+        let notes = corpus.path().join("notes.md");
+        assert!(!indexer.index_file(&notes).await.unwrap());
+
+        std::fs::write(&notes, "annual forecast, longer than before").unwrap();
+        assert!(indexer.index_file(&notes).await.unwrap());
+        engine.commit_changes().await.unwrap();
+        assert_eq!(engine.search("budget", 10).await.unwrap().len(), 0);
+        assert_eq!(engine.search("forecast", 10).await.unwrap().len(), 1);
+
+        let stored = engine.get_document(&notes.to_string_lossy()).unwrap().unwrap();
+        assert_eq!(stored.fingerprint.size, 35);
+        assert_eq!(stored.fingerprint.content_hash.len(), 64);
+
+        engine.remove_document(&notes.to_string_lossy()).await.unwrap();
+        engine.commit_changes().await.unwrap();
+        assert_eq!(engine.search("forecast", 10).await.unwrap().len(), 0);
+
+        std::fs::remove_file(corpus.path().join("todo.txt")).unwrap();
+        indexer.start_indexing().await.unwrap();
+        assert_eq!(engine.search("milk", 10).await.unwrap().len(), 0);
+
+        std::fs::write(corpus.path().join("todo.txt"), "buy milk").unwrap();
+        indexer.start_indexing().await.unwrap();
+        assert_eq!(engine.search("milk", 10).await.unwrap().len(), 1);
+        engine.clear().await.unwrap();
+        assert_eq!(engine.search("milk", 10).await.unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_filters() {
+        let (engine, corpus, _index) = index_corpus(&[
+            ("report.pdf", b"%PDF-1.4 tiny"),
+            ("report.md", b"quarterly report draft"),
+            ("old.txt", b"quarterly report from last year"),
+        ]).await;
+
+        std::fs::create_dir(corpus.path().join("archive")).unwrap();
+        let archived = corpus.path().join("archive").join("report.txt");
+        std::fs::write(&archived, vec![b'x'; 4096]).unwrap();
+        let old = corpus.path().join("old.txt");
+        let year_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(400 * 24 * 60 * 60);
+        std::fs::File::options().write(true).open(&old).unwrap().set_modified(year_ago).unwrap();
+
+        scanned_indexer(&engine, corpus_config(corpus.path())).await;
+
+        let paths = |results: Vec<SearchResult>| {
+            let mut paths: Vec<String> = results.into_iter().map(|r| r.path).collect();
+            paths.sort();
+            paths
+        };
+        let path = |p: &Path| p.to_string_lossy().to_string();
+
+        let results = engine.search("report ext:PDF", 10).await.unwrap();
+        assert_eq!(paths(results), vec![path(&corpus.path().join("report.pdf"))]);
+
+        let results = engine.search("size:>1kb", 10).await.unwrap();
+        assert_eq!(paths(results), vec![path(&archived)]);
+
+        let results = engine.search("quarterly modified:>1y", 10).await.unwrap();
+        assert_eq!(paths(results), vec![path(&old)]);
+
+        let results = engine.search("quarterly modified:<7d", 10).await.unwrap();
+        assert_eq!(paths(results), vec![path(&corpus.path().join("report.md"))]);
+
+        let filter = format!("in:{}", corpus.path().join("archive").display());
+        let results = engine.search(&filter, 10).await.unwrap();
+        assert_eq!(paths(results), vec![path(&archived)]);
+
+        let filter = format!("in:{} ext:txt", corpus.path().display());
+        assert_eq!(engine.search(&filter, 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_prefix_fuzzy_and_escaped_queries() {
+        let (engine, _corpus, _index) = index_corpus(&[
+            ("documentation.md", b"Installation guide for the launcher"),
+            ("budget.txt", b"quarterly numbers"),
+        ]).await;
+
+        let results = engine.search("documen", 10).await.unwrap();
+        assert_eq!(results[0].title, "documentation.md");
+
+        let results = engine.search("instalation", 10).await.unwrap();
+        assert_eq!(results[0].title, "documentation.md");
+
+        let results = engine.search("budgte", 10).await.unwrap();
+        assert_eq!(results[0].title, "budget.txt");
+
+        // Entrada com sintaxe inválida não é mais um erro
+        assert!(engine.search("guide (launcher", 10).await.is_ok());
+        assert!(engine.search("\"unterminated", 10).await.is_ok());
+        assert!(engine.search("quarterly AND", 10).await.is_ok());
+        assert_eq!(engine.search("quarterly (numbers", 10).await.unwrap()[0].title, "budget.txt");
+    }
+}
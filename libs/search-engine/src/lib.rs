@@ -1 +1,44 @@
-// Search engine placeholder
\ No newline at end of file
+//! R5 Flowlight - Search Engine
+//!
+//! Indexação e busca de arquivos com Tantivy, sem dependência de Tauri:
+//! - `engine`: gerenciador dos índices, um por escopo (abrir, gravar, buscar)
+//! - `schema`: campos do índice e documentos
+//! - `lock`: trava de escritor único do diretório do índice
+//! - `indexer`: varredura de diretórios e extração de conteúdo
+//! - `extract`: texto de PDFs, documentos do Office e ZIPs, por extensão/MIME
+//! - `exclusion`: padrões glob, `.gitignore`/`.ignore` e pastas excluídas
+//! - `scheduler`: varredura em segundo plano com workers, limite de leitura e pausa
+//! - `watcher`: atualização incremental a partir do sistema de arquivos
+//! - `query_syntax` / `query_builder`: filtros e consultas tolerantes a erros
+//! - `semantic`: embeddings, índice vetorial aproximado e ranking híbrido
+//! - `snapshot`: exportação e importação do índice, com remapeamento de caminhos
+
+pub mod config;
+pub mod engine;
+pub mod exclusion;
+pub mod extract;
+pub mod indexer;
+pub mod lock;
+pub mod query_builder;
+pub mod query_syntax;
+pub mod scheduler;
+pub mod schema;
+pub mod semantic;
+pub mod snapshot;
+pub mod watcher;
+
+#[cfg(test)]
+mod test_support;
+
+pub use config::{app_config_path, ScopeConfig, SearchConfig};
+pub use exclusion::{Exclusions, IgnoreRules};
+pub use extract::{ExtractError, ExtractionLimits, Extractor, ExtractorRegistry};
+pub use engine::{IndexStats, IntegrityReport, ReadOnlyIndex, RootStats, ScopeStats, SearchEngine, SearchResult, DEFAULT_SCOPE};
+pub use indexer::FileIndexer;
+pub use lock::{IndexLock, IndexLockedError};
+pub use query_syntax::{ParsedQuery, QueryFilter};
+pub use scheduler::{IndexScheduler, IndexingControl, IndexingProgress, SchedulerConfig};
+pub use schema::{Embedding, FileFingerprint, IndexedDocument};
+pub use semantic::{Embedder, HashEmbedder, VectorIndex};
+pub use snapshot::{PathRemap, SnapshotManifest, SnapshotScope};
+pub use watcher::{FileWatcher, WatcherHandle};
@@ -0,0 +1,79 @@
+//! Trava de escritor único para o diretório do índice
+//!
+//! A trava é advisory (`flock`/`LockFileEx`): o sistema operacional a libera
+//! quando o processo termina, então um arquivo de trava "velho" nunca
+//! bloqueia o índice e não precisa ser apagado.
+
+use fs4::FileExt;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Nome do arquivo de trava dentro do diretório do índice
+pub const LOCK_FILE_NAME: &str = ".flowlight-writer.lock";
+
+/// Outro processo já está escrevendo no índice
+#[derive(Debug, Clone)]
+pub struct IndexLockedError {
+    pub index_path: PathBuf,
+    /// PID registrado por quem detém a trava, se legível
+    pub holder_pid: Option<u32>,
+}
+
+impl fmt::Display for IndexLockedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.holder_pid {
+            Some(pid) => write!(f, "Search index at {:?} is in use by process {}", self.index_path, pid),
+            None => write!(f, "Search index at {:?} is in use by another process", self.index_path),
+        }
+    }
+}
+
+impl std::error::Error for IndexLockedError {}
+
+/// Trava exclusiva mantida enquanto o `SearchEngine` estiver aberto
+#[derive(Debug)]
+pub struct IndexLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl IndexLock {
+    /// Obtém a trava sem esperar; falha com `IndexLockedError` se já estiver em uso
+    pub fn acquire(index_path: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(index_path)?;
+        let path = index_path.join(LOCK_FILE_NAME);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        if file.try_lock_exclusive().is_err() {
+            let mut contents = String::new();
+            let holder_pid = file.read_to_string(&mut contents).ok().and_then(|_| contents.trim().parse().ok());
+            return Err(IndexLockedError { index_path: index_path.to_path_buf(), holder_pid }.into());
+        }
+
+        // Registrar o dono para a mensagem de erro de quem vier depois
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        Ok(Self { file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
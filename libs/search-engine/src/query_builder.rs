@@ -0,0 +1,173 @@
+//! Construção de consultas Tantivy tolerantes para um launcher
+//!
+//! O texto digitado é incompleto e cheio de erros de digitação, então cada termo
+//! vira uma combinação de busca exata, por prefixo e fuzzy. A sintaxe do
+//! `QueryParser` continua valendo quando o texto é válido; caso contrário, a
+//! entrada é escapada em vez de virar um erro.
+
+use log::debug;
+use tantivy::{
+    query::{BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query, QueryParser, TermQuery},
+    schema::{Field, IndexRecordOption},
+    tokenizer::TextAnalyzer,
+    Index, Term,
+};
+
+/// Limite de termos expandidos, para manter o custo de cada tecla previsível
+const MAX_FUZZY_TERMS: usize = 8;
+
+/// Termos menores que isso não viram prefixo: "a*" casaria com quase tudo
+const MIN_PREFIX_CHARS: usize = 2;
+
+const EXACT_BOOST: f32 = 3.0;
+const PREFIX_BOOST: f32 = 1.5;
+const FUZZY_BOOST: f32 = 1.0;
+
+/// Peso da interpretação literal do `QueryParser` sobre a expansão fuzzy
+const PARSED_QUERY_BOOST: f32 = 2.0;
+
+pub struct QueryBuilder {
+    query_parser: QueryParser,
+    analyzer: TextAnalyzer,
+    /// Campos tokenizados que recebem prefixo/fuzzy, com seus pesos
+    fuzzy_fields: Vec<(Field, f32)>,
+}
+
+impl QueryBuilder {
+    /// `title` pesa mais que `body`; `path` participa apenas da sintaxe do parser
+    pub fn new(index: &Index, title: Field, body: Field, path: Field) -> tantivy::Result<Self> {
+        let mut query_parser = QueryParser::for_index(index, vec![title, path, body]);
+        // Um termo no nome do arquivo vale mais que no conteúdo
+        query_parser.set_field_boost(title, 2.0);
+        query_parser.set_conjunction_by_default();
+
+        Ok(Self {
+            query_parser,
+            analyzer: index.tokenizer_for_field(title)?,
+            fuzzy_fields: vec![(title, 2.0), (body, 1.0)],
+        })
+    }
+
+    /// Consulta para o texto livre; `None` quando não sobra nenhum termo pesquisável
+    pub fn build(&self, text: &str) -> Option<Box<dyn Query>> {
+        if text.trim().is_empty() {
+            return None;
+        }
+
+        let parsed = self.parse_strict(text);
+        let fuzzy = self.build_fuzzy(text);
+
+        match (parsed, fuzzy) {
+            (Some(parsed), Some(fuzzy)) => Some(Box::new(BooleanQuery::new(vec![
+                (Occur::Should, Box::new(BoostQuery::new(parsed, PARSED_QUERY_BOOST))),
+                (Occur::Should, fuzzy),
+            ]))),
+            (parsed, fuzzy) => parsed.or(fuzzy),
+        }
+    }
+
+    /// Texto interpretado pelo `QueryParser`, escapado se a sintaxe for inválida
+    fn parse_strict(&self, text: &str) -> Option<Box<dyn Query>> {
+        match self.query_parser.parse_query(text) {
+            Ok(query) => Some(query),
+            Err(e) => {
+                debug!("Query '{}' is not valid syntax ({}), escaping it", text, e);
+                self.query_parser.parse_query(&escape_query(text)).ok()
+            }
+        }
+    }
+
+    /// Todos os termos devem casar, cada um de forma exata, por prefixo ou aproximada
+    fn build_fuzzy(&self, text: &str) -> Option<Box<dyn Query>> {
+        let mut analyzer = self.analyzer.clone();
+        let mut stream = analyzer.token_stream(text);
+        let mut words = Vec::new();
+        stream.process(&mut |token| {
+            if words.len() < MAX_FUZZY_TERMS {
+                words.push(token.text.clone());
+            }
+        });
+
+        let clauses: Vec<(Occur, Box<dyn Query>)> = words
+            .iter()
+            .map(|word| (Occur::Must, self.term_query(word)))
+            .collect();
+
+        if clauses.is_empty() {
+            None
+        } else {
+            Some(Box::new(BooleanQuery::new(clauses)))
+        }
+    }
+
+    fn term_query(&self, word: &str) -> Box<dyn Query> {
+        let distance = edit_distance(word);
+        let mut alternatives: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+        for &(field, boost) in &self.fuzzy_fields {
+            let term = Term::from_field_text(field, word);
+            alternatives.push((
+                Occur::Should,
+                Box::new(BoostQuery::new(
+                    Box::new(TermQuery::new(term.clone(), IndexRecordOption::WithFreqs)),
+                    boost * EXACT_BOOST,
+                )),
+            ));
+            if word.chars().count() >= MIN_PREFIX_CHARS {
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(FuzzyTermQuery::new_prefix(term.clone(), 0, true)),
+                        boost * PREFIX_BOOST,
+                    )),
+                ));
+            }
+            if distance > 0 {
+                alternatives.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(FuzzyTermQuery::new(term, distance, true)),
+                        boost * FUZZY_BOOST,
+                    )),
+                ));
+            }
+        }
+
+        Box::new(BooleanQuery::new(alternatives))
+    }
+}
+
+/// Quantos erros de digitação tolerar: nenhum em termos curtos, até 2 nos longos
+fn edit_distance(word: &str) -> u8 {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Transforma cada palavra em uma frase entre aspas, anulando a sintaxe do parser
+pub fn escape_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance_scales_with_length() {
+        assert_eq!(edit_distance("pdf"), 0);
+        assert_eq!(edit_distance("notes"), 1);
+        assert_eq!(edit_distance("documentation"), 2);
+    }
+
+    #[test]
+    fn test_escape_query() {
+        assert_eq!(escape_query("c++ (draft)"), "\"c++\" \"(draft)\"");
+        assert_eq!(escape_query("say \"hi\""), "\"say\" \"\\\"hi\\\"\"");
+    }
+}
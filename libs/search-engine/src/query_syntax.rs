@@ -0,0 +1,326 @@
+//! Mini-linguagem de filtros da busca de arquivos
+//!
+//! Além do texto livre, a consulta aceita filtros no formato `chave:valor`:
+//!
+//! - `ext:pdf`, `ext:jpg,png` — extensão do arquivo
+//! - `size:>1mb`, `size:<=500kb` — tamanho; sem operador significa "pelo menos"
+//! - `modified:<7d` (últimos 7 dias), `modified:>1y`, `modified:>=2024-01-31`
+//! - `in:~/Documents`, `in:"~/My Files"` — diretório, incluindo subpastas
+//! - `scope:work`, `scope:work,notes` — só os escopos nomeados (ver `ScopeConfig`)
+//!
+//! Filtros inválidos ou desconhecidos continuam fazendo parte do texto livre.
+
+use chrono::{Local, NaiveDate, Utc};
+use std::ops::Bound;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryFilter {
+    /// Qualquer uma das extensões, em minúsculas e sem o ponto
+    Extension(Vec<String>),
+    /// Tamanho em bytes
+    Size { lower: Bound<u64>, upper: Bound<u64> },
+    /// Data de modificação em segundos desde a época Unix
+    Modified { lower: Bound<u64>, upper: Bound<u64> },
+    /// Diretório que contém o arquivo, em qualquer nível
+    InDir(PathBuf),
+    /// Nomes dos escopos onde buscar; sem este filtro, busca em todos
+    Scope(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// Parte da consulta que não é filtro
+    pub text: String,
+    pub filters: Vec<QueryFilter>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+impl ParsedQuery {
+    pub fn parse(input: &str) -> Self {
+        Self::parse_at(input, u64::try_from(Utc::now().timestamp()).unwrap_or(0))
+    }
+
+    /// Interpreta a consulta usando `now` como referência para datas relativas
+    pub fn parse_at(input: &str, now: u64) -> Self {
+        let mut text = Vec::new();
+        let mut filters = Vec::new();
+
+        for token in tokenize(input) {
+            match parse_filter(&token, now) {
+                Some(filter) => filters.push(filter),
+                None => text.push(token),
+            }
+        }
+
+        Self { text: text.join(" "), filters }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.filters.is_empty()
+    }
+}
+
+/// Separa por espaços, mantendo trechos entre aspas no mesmo token
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_filter(token: &str, now: u64) -> Option<QueryFilter> {
+    let (key, value) = token.split_once(':')?;
+    let value = value.trim_matches('"');
+    if value.is_empty() {
+        return None;
+    }
+
+    match key.to_lowercase().as_str() {
+        "ext" => parse_extensions(value),
+        "size" => parse_size(value),
+        "modified" | "mtime" => parse_modified(value, now),
+        "in" => Some(QueryFilter::InDir(expand_home(value))),
+        "scope" => parse_scopes(value),
+        _ => None,
+    }
+}
+
+fn parse_extensions(value: &str) -> Option<QueryFilter> {
+    let extensions: Vec<String> = value
+        .split(',')
+        .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect();
+
+    if extensions.is_empty() {
+        None
+    } else {
+        Some(QueryFilter::Extension(extensions))
+    }
+}
+
+fn parse_scopes(value: &str) -> Option<QueryFilter> {
+    let scopes: Vec<String> = value
+        .split(',')
+        .map(|scope| scope.trim().to_lowercase())
+        .filter(|scope| !scope.is_empty())
+        .collect();
+
+    if scopes.is_empty() {
+        None
+    } else {
+        Some(QueryFilter::Scope(scopes))
+    }
+}
+
+fn split_comparison(value: &str) -> (Option<Comparison>, &str) {
+    for (prefix, comparison) in [
+        (">=", Comparison::GreaterOrEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+    ] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (Some(comparison), rest);
+        }
+    }
+    (None, value)
+}
+
+fn parse_size(value: &str) -> Option<QueryFilter> {
+    let (comparison, amount) = split_comparison(value);
+    let bytes = parse_bytes(amount)?;
+
+    let (lower, upper) = match comparison.unwrap_or(Comparison::GreaterOrEqual) {
+        Comparison::Less => (Bound::Unbounded, Bound::Excluded(bytes)),
+        Comparison::LessOrEqual => (Bound::Unbounded, Bound::Included(bytes)),
+        Comparison::Greater => (Bound::Excluded(bytes), Bound::Unbounded),
+        Comparison::GreaterOrEqual => (Bound::Included(bytes), Bound::Unbounded),
+        Comparison::Equal => (Bound::Included(bytes), Bound::Included(bytes)),
+    };
+    Some(QueryFilter::Size { lower, upper })
+}
+
+/// "1.5mb" → 1572864; sem unidade, o valor está em bytes
+fn parse_bytes(amount: &str) -> Option<u64> {
+    let amount = amount.to_lowercase();
+    let split = amount.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(amount.len());
+    let (number, unit) = amount.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" | "kb" => 1 << 10,
+        "m" | "mb" => 1 << 20,
+        "g" | "gb" => 1 << 30,
+        "t" | "tb" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+fn parse_modified(value: &str, now: u64) -> Option<QueryFilter> {
+    let (comparison, amount) = split_comparison(value);
+
+    if let Some(age) = parse_age(amount) {
+        // Uma idade aponta para o passado: "<7d" significa "mais recente que 7 dias atrás"
+        let cutoff = now.saturating_sub(age);
+        let (lower, upper) = match comparison.unwrap_or(Comparison::LessOrEqual) {
+            Comparison::Less => (Bound::Excluded(cutoff), Bound::Unbounded),
+            Comparison::LessOrEqual | Comparison::Equal => (Bound::Included(cutoff), Bound::Unbounded),
+            Comparison::Greater => (Bound::Unbounded, Bound::Excluded(cutoff)),
+            Comparison::GreaterOrEqual => (Bound::Unbounded, Bound::Included(cutoff)),
+        };
+        return Some(QueryFilter::Modified { lower, upper });
+    }
+
+    let date = NaiveDate::parse_from_str(amount, "%Y-%m-%d").ok()?;
+    let day_start = local_midnight(date)?;
+    let next_day_start = local_midnight(date.succ_opt()?)?;
+    let (lower, upper) = match comparison.unwrap_or(Comparison::Equal) {
+        Comparison::Less => (Bound::Unbounded, Bound::Excluded(day_start)),
+        Comparison::LessOrEqual => (Bound::Unbounded, Bound::Excluded(next_day_start)),
+        Comparison::Greater => (Bound::Included(next_day_start), Bound::Unbounded),
+        Comparison::GreaterOrEqual => (Bound::Included(day_start), Bound::Unbounded),
+        Comparison::Equal => (Bound::Included(day_start), Bound::Excluded(next_day_start)),
+    };
+    Some(QueryFilter::Modified { lower, upper })
+}
+
+/// "7d" → segundos; unidades: h, d, w, m (30 dias), y
+fn parse_age(amount: &str) -> Option<u64> {
+    let amount = amount.to_lowercase();
+    let split = amount.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = amount.split_at(split);
+    let number: u64 = number.parse().ok()?;
+
+    let unit_secs: u64 = match unit {
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "m" => 30 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return None,
+    };
+    number.checked_mul(unit_secs)
+}
+
+fn local_midnight(date: NaiveDate) -> Option<u64> {
+    let timestamp = date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest()?.timestamp();
+    u64::try_from(timestamp).ok()
+}
+
+fn expand_home(value: &str) -> PathBuf {
+    let path = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => {
+            match dirs::home_dir() {
+                Some(home) => home.join(rest.trim_start_matches(['/', '\\'])),
+                None => PathBuf::from(value),
+            }
+        }
+        _ => PathBuf::from(value),
+    };
+
+    // "in:/tmp/" e "in:/tmp" devem se comportar igual
+    path.components().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_parse_filters_and_text() {
+        let parsed = ParsedQuery::parse_at("budget ext:pdf,.DOCX size:>1mb modified:<7d report", NOW);
+
+        assert_eq!(parsed.text, "budget report");
+        assert_eq!(parsed.filters, vec![
+            QueryFilter::Extension(vec!["pdf".to_string(), "docx".to_string()]),
+            QueryFilter::Size { lower: Bound::Excluded(1 << 20), upper: Bound::Unbounded },
+            QueryFilter::Modified { lower: Bound::Excluded(NOW - 7 * DAY), upper: Bound::Unbounded },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_size_units_and_defaults() {
+        assert_eq!(ParsedQuery::parse_at("size:1.5k", NOW).filters, vec![
+            QueryFilter::Size { lower: Bound::Included(1536), upper: Bound::Unbounded },
+        ]);
+        assert_eq!(ParsedQuery::parse_at("size:<=10", NOW).filters, vec![
+            QueryFilter::Size { lower: Bound::Unbounded, upper: Bound::Included(10) },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_modified() {
+        assert_eq!(ParsedQuery::parse_at("modified:>2w", NOW).filters, vec![
+            QueryFilter::Modified { lower: Bound::Unbounded, upper: Bound::Excluded(NOW - 14 * DAY) },
+        ]);
+
+        let parsed = ParsedQuery::parse_at("mtime:2024-01-31", NOW);
+        match &parsed.filters[..] {
+            [QueryFilter::Modified { lower: Bound::Included(start), upper: Bound::Excluded(end) }] => {
+                assert!(end - start >= 23 * 60 * 60 && end - start <= 25 * 60 * 60);
+            }
+            other => panic!("unexpected filters: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_in_dir_expands_home_and_quotes() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(ParsedQuery::parse_at("in:~/Documents/", NOW).filters, vec![
+            QueryFilter::InDir(home.join("Documents")),
+        ]);
+
+        let parsed = ParsedQuery::parse_at("notes in:\"/tmp/My Files\"", NOW);
+        assert_eq!(parsed.text, "notes");
+        assert_eq!(parsed.filters, vec![QueryFilter::InDir(PathBuf::from("/tmp/My Files"))]);
+    }
+
+    #[test]
+    fn test_parse_scopes() {
+        let parsed = ParsedQuery::parse_at("roadmap scope:Work,notes", NOW);
+        assert_eq!(parsed.text, "roadmap");
+        assert_eq!(parsed.filters, vec![QueryFilter::Scope(vec!["work".to_string(), "notes".to_string()])]);
+        assert!(ParsedQuery::parse_at("scope:,", NOW).filters.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_filters_stay_in_text() {
+        let parsed = ParsedQuery::parse_at("size:huge ext: modified:soon http://example.com", NOW);
+        assert!(parsed.filters.is_empty());
+        assert_eq!(parsed.text, "size:huge ext: modified:soon http://example.com");
+    }
+}
@@ -0,0 +1,376 @@
+//! Indexação em segundo plano com uso de recursos controlado
+//!
+//! O `IndexScheduler` percorre as pastas indexadas e distribui os arquivos
+//! entre um número fixo de workers. A leitura de disco pode ser limitada
+//! (bytes por segundo) e o trabalho pode ser pausado enquanto o usuário
+//! está com o overlay aberto. O progresso é publicado por um
+//! `IndexingControl`, que também recebe os pedidos de pausa.
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+use crate::config::SearchConfig;
+use crate::indexer::FileIndexer;
+
+/// Intervalo entre verificações enquanto a indexação está pausada
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Documentos novos entre commits intermediários, para resultados parciais aparecerem na busca
+const COMMIT_EVERY: u64 = 1000;
+
+/// Contadores de uma varredura
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexingProgress {
+    /// Arquivos encontrados nas pastas indexadas
+    pub files_seen: u64,
+    /// Arquivos gravados no índice
+    pub files_indexed: u64,
+    /// Arquivos excluídos, sem mudanças ou que falharam
+    pub files_skipped: u64,
+    /// A varredura terminou
+    pub done: bool,
+}
+
+/// Limites de recursos da indexação em segundo plano
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerConfig {
+    pub workers: usize,
+    /// Limite de leitura de disco; `None` desliga o limite
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl SchedulerConfig {
+    pub fn from_search_config(config: &SearchConfig) -> Self {
+        Self {
+            workers: config.indexing_workers.max(1),
+            max_bytes_per_sec: config.background_throttling.then_some(config.throttle_bytes_per_sec.max(1)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused: bool,
+    /// Retomar automaticamente neste instante, se definido
+    until: Option<Instant>,
+}
+
+/// Pausa/retomada e progresso da indexação, compartilhados entre o scheduler e quem o controla
+#[derive(Debug, Clone)]
+pub struct IndexingControl {
+    pause: Arc<Mutex<PauseState>>,
+    progress: Arc<watch::Sender<IndexingProgress>>,
+}
+
+impl Default for IndexingControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IndexingControl {
+    pub fn new() -> Self {
+        let (progress, _) = watch::channel(IndexingProgress::default());
+        Self {
+            pause: Arc::new(Mutex::new(PauseState::default())),
+            progress: Arc::new(progress),
+        }
+    }
+
+    /// Pausa até `resume`
+    pub fn pause(&self) {
+        self.set_pause(true, None);
+    }
+
+    /// Pausa por no máximo `duration`; útil quando o fim do uso pode não ser avisado
+    pub fn pause_for(&self, duration: Duration) {
+        self.set_pause(true, Some(Instant::now() + duration));
+    }
+
+    pub fn resume(&self) {
+        self.set_pause(false, None);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        let Ok(state) = self.pause.lock() else {
+            return false;
+        };
+        state.paused && state.until.is_none_or(|until| Instant::now() < until)
+    }
+
+    /// Progresso mais recente
+    pub fn progress(&self) -> IndexingProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// Recebe cada atualização de progresso
+    pub fn subscribe(&self) -> watch::Receiver<IndexingProgress> {
+        self.progress.subscribe()
+    }
+
+    fn set_pause(&self, paused: bool, until: Option<Instant>) {
+        if let Ok(mut state) = self.pause.lock() {
+            if state.paused != paused {
+                debug!("Background indexing {}", if paused { "paused" } else { "resumed" });
+            }
+            *state = PauseState { paused, until };
+        }
+    }
+
+    async fn wait_while_paused(&self) {
+        while self.is_paused() {
+            tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut IndexingProgress)) {
+        self.progress.send_modify(change);
+    }
+}
+
+/// Limita a taxa média de leitura reservando uma janela de tempo para cada leitura
+#[derive(Debug)]
+struct RateLimiter {
+    bytes_per_sec: u64,
+    next_free: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self { bytes_per_sec, next_free: tokio::sync::Mutex::new(Instant::now()) }
+    }
+
+    /// Registra `bytes` lidos, esperando se a taxa foi excedida
+    async fn consume(&self, bytes: u64) {
+        let cost = Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64);
+        let start = {
+            let mut next_free = self.next_free.lock().await;
+            let start = (*next_free).max(Instant::now());
+            *next_free = start + cost;
+            start
+        };
+        tokio::time::sleep_until(start + cost).await;
+    }
+}
+
+/// Varre as pastas indexadas com workers e limites de recursos
+pub struct IndexScheduler {
+    indexer: FileIndexer,
+    config: SchedulerConfig,
+    control: IndexingControl,
+}
+
+impl IndexScheduler {
+    pub fn new(indexer: FileIndexer, config: SchedulerConfig, control: IndexingControl) -> Self {
+        Self { indexer, config, control }
+    }
+
+    pub fn control(&self) -> &IndexingControl {
+        &self.control
+    }
+
+    /// Indexa todas as pastas configuradas e remove do índice o que sumiu
+    pub async fn run(&self) -> Result<IndexingProgress> {
+        info!(
+            "Starting background indexing with {} workers{}",
+            self.config.workers,
+            self.config.max_bytes_per_sec.map(|rate| format!(", limited to {} bytes/s", rate)).unwrap_or_default()
+        );
+        self.control.update(|progress| *progress = IndexingProgress::default());
+
+        let (tx, rx) = mpsc::channel::<PathBuf>(self.config.workers * 64);
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        let limiter = self.config.max_bytes_per_sec.map(|rate| Arc::new(RateLimiter::new(rate)));
+        let indexed_since_commit = Arc::new(AtomicU64::new(0));
+
+        let mut workers = Vec::with_capacity(self.config.workers);
+        for _ in 0..self.config.workers {
+            let worker = Worker {
+                indexer: self.indexer.clone(),
+                control: self.control.clone(),
+                limiter: limiter.clone(),
+                indexed_since_commit: indexed_since_commit.clone(),
+            };
+            let rx = rx.clone();
+            workers.push(tokio::spawn(async move { worker.run(rx).await }));
+        }
+
+        let roots: Vec<PathBuf> = self.indexer.config().roots().into_iter().filter(|p| p.is_dir()).collect();
+        let walk_result = self.walk(&roots, tx).await;
+        for worker in workers {
+            worker.await?;
+        }
+        walk_result?;
+
+        for root in &roots {
+            self.indexer.remove_missing_under(root).await?;
+        }
+        self.indexer.search_engine().commit_changes().await?;
+
+        self.control.update(|progress| progress.done = true);
+        let progress = self.control.progress();
+        info!(
+            "Background indexing finished: {} seen, {} indexed, {} skipped",
+            progress.files_seen, progress.files_indexed, progress.files_skipped
+        );
+        Ok(progress)
+    }
+
+    /// Percorre as pastas e entrega os arquivos candidatos aos workers
+    async fn walk(&self, roots: &[PathBuf], tx: mpsc::Sender<PathBuf>) -> Result<()> {
+        let exclusions = self.indexer.exclusions();
+        let index_path = self.indexer.search_engine().index_path();
+        let mut pending: Vec<PathBuf> = roots.to_vec();
+
+        while let Some(dir) = pending.pop() {
+            self.control.wait_while_paused().await;
+
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Could not read directory '{}': {}", dir.display(), e);
+                    continue;
+                }
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let Ok(file_type) = entry.file_type().await else {
+                    continue;
+                };
+
+                if file_type.is_dir() {
+                    if !exclusions.is_excluded(&path, true) && !path.starts_with(index_path) {
+                        pending.push(path);
+                    }
+                } else if file_type.is_file() {
+                    self.control.update(|progress| progress.files_seen += 1);
+                    if exclusions.is_excluded(&path, false) || !self.indexer.config().matches_file_filters(&path) {
+                        self.control.update(|progress| progress.files_skipped += 1);
+                    } else if tx.send(path).await.is_err() {
+                        // Todos os workers terminaram
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Worker {
+    indexer: FileIndexer,
+    control: IndexingControl,
+    limiter: Option<Arc<RateLimiter>>,
+    indexed_since_commit: Arc<AtomicU64>,
+}
+
+impl Worker {
+    async fn run(self, rx: Arc<tokio::sync::Mutex<mpsc::Receiver<PathBuf>>>) {
+        loop {
+            let next = rx.lock().await.recv().await;
+            let Some(path) = next else {
+                return;
+            };
+
+            self.control.wait_while_paused().await;
+
+            match self.indexer.index_file(&path).await {
+                Ok(true) => {
+                    self.control.update(|progress| progress.files_indexed += 1);
+                    if let Some(limiter) = &self.limiter {
+                        let size = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+                        limiter.consume(size).await;
+                    }
+                    if self.indexed_since_commit.fetch_add(1, Ordering::Relaxed) + 1 >= COMMIT_EVERY {
+                        self.indexed_since_commit.store(0, Ordering::Relaxed);
+                        if let Err(e) = self.indexer.search_engine().commit_changes().await {
+                            warn!("Intermediate index commit failed: {}", e);
+                        }
+                    }
+                }
+                Ok(false) => self.control.update(|progress| progress.files_skipped += 1),
+                Err(e) => {
+                    warn!("Could not index file '{}': {}", path.display(), e);
+                    self.control.update(|progress| progress.files_skipped += 1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SearchEngine;
+    use crate::test_support::{corpus_config, corpus_dir, index_dir};
+
+    async fn scheduler(corpus: &std::path::Path, index: &std::path::Path, config: SchedulerConfig) -> IndexScheduler {
+        let engine = Arc::new(SearchEngine::open_in_dir(index).await.unwrap());
+        IndexScheduler::new(FileIndexer::new(engine, corpus_config(corpus)), config, IndexingControl::new())
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_reports_progress() {
+        let corpus = corpus_dir();
+        for i in 0..20 {
+            std::fs::create_dir_all(corpus.path().join(format!("dir{}", i % 3))).unwrap();
+            std::fs::write(corpus.path().join(format!("dir{}/note{}.txt", i % 3, i)), format!("note number {}", i)).unwrap();
+        }
+        std::fs::write(corpus.path().join("image.png"), [0u8; 16]).unwrap();
+        let index = index_dir();
+
+        let scheduler = scheduler(corpus.path(), index.path(), SchedulerConfig { workers: 3, max_bytes_per_sec: None }).await;
+        let mut updates = scheduler.control().subscribe();
+
+        let progress = scheduler.run().await.unwrap();
+        assert_eq!(progress, IndexingProgress { files_seen: 21, files_indexed: 20, files_skipped: 1, done: true });
+        assert!(updates.has_changed().unwrap());
+        assert_eq!(*updates.borrow_and_update(), progress);
+        assert_eq!(scheduler.indexer.search_engine().stats(&[]).unwrap().doc_count, 20);
+
+        // Segunda varredura: nada mudou
+        let progress = scheduler.run().await.unwrap();
+        assert_eq!((progress.files_indexed, progress.files_skipped), (0, 21));
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_rate_limit() {
+        let corpus = corpus_dir();
+        std::fs::write(corpus.path().join("a.txt"), vec![b'a'; 2000]).unwrap();
+        std::fs::write(corpus.path().join("b.txt"), vec![b'b'; 2000]).unwrap();
+        let index = index_dir();
+
+        // 4 KB a 10 KB/s: ao menos ~400 ms
+        let scheduler = scheduler(corpus.path(), index.path(), SchedulerConfig { workers: 2, max_bytes_per_sec: Some(10_000) }).await;
+        let started = std::time::Instant::now();
+        scheduler.run().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(350));
+
+        // Pausado, nada avança até retomar
+        std::fs::write(corpus.path().join("c.txt"), "new file").unwrap();
+        let control = scheduler.control().clone();
+        control.pause();
+        let run = tokio::spawn(async move { scheduler.run().await.unwrap() });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!run.is_finished());
+        assert_eq!(control.progress().files_indexed, 0);
+
+        control.resume();
+        let progress = run.await.unwrap();
+        assert_eq!(progress.files_indexed, 1);
+
+        // A pausa com prazo expira sozinha
+        control.pause_for(Duration::from_millis(50));
+        assert!(control.is_paused());
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(!control.is_paused());
+    }
+}
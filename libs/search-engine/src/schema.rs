@@ -0,0 +1,86 @@
+//! Schema do índice de arquivos
+
+use tantivy::schema::{Field, Schema, FAST, INDEXED, STORED, STRING, TEXT};
+
+/// Estado de um arquivo no momento em que foi indexado
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFingerprint {
+    /// Última modificação, em segundos desde a época Unix
+    pub modified: u64,
+    pub size: u64,
+    /// SHA-256 do conteúdo; vazio quando o arquivo não foi lido
+    pub content_hash: String,
+}
+
+/// Vetor semântico do documento e o modelo que o gerou
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embedding {
+    pub model: String,
+    pub vector: Vec<f32>,
+}
+
+/// Documento como é gravado no índice, um por caminho
+#[derive(Debug, Clone)]
+pub struct IndexedDocument {
+    pub path: String,
+    pub title: String,
+    pub body: String,
+    pub fingerprint: FileFingerprint,
+    /// Presente só com a busca semântica ligada
+    pub embedding: Option<Embedding>,
+}
+
+#[derive(Clone)]
+pub(crate) struct SearchIndexFields {
+    pub(crate) title: Field,
+    pub(crate) path: Field,
+    pub(crate) body: Field,
+    pub(crate) modified: Field,
+    pub(crate) size: Field,
+    pub(crate) content_hash: Field,
+    pub(crate) extension: Field,
+    pub(crate) parent: Field,
+    pub(crate) embedding: Field,
+    pub(crate) embedding_model: Field,
+}
+
+pub(crate) struct SearchIndexSchema {
+    pub(crate) schema: Schema,
+    pub(crate) fields: SearchIndexFields,
+}
+
+impl SearchIndexSchema {
+    pub(crate) fn new() -> Self {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        // Rápido para carregar os vetores sem ler os documentos armazenados
+        let path = schema_builder.add_text_field("path", STRING | STORED | FAST);
+        // Armazenado para permitir a geração de snippets
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let modified = schema_builder.add_u64_field("modified", INDEXED | STORED | FAST);
+        let size = schema_builder.add_u64_field("size", INDEXED | STORED | FAST);
+        let content_hash = schema_builder.add_text_field("content_hash", STRING | STORED);
+        // Derivados do caminho, usados pelos filtros `ext:` e `in:`
+        let extension = schema_builder.add_text_field("extension", STRING | STORED | FAST);
+        let parent = schema_builder.add_text_field("parent", STRING | STORED | FAST);
+        // Vetor da busca semântica (f32 little-endian) e o embedder que o gerou
+        let embedding = schema_builder.add_bytes_field("embedding", STORED | FAST);
+        let embedding_model = schema_builder.add_text_field("embedding_model", STRING | STORED | FAST);
+        let schema = schema_builder.build();
+        Self {
+            schema,
+            fields: SearchIndexFields {
+                title,
+                path,
+                body,
+                modified,
+                size,
+                content_hash,
+                extension,
+                parent,
+                embedding,
+                embedding_model,
+            },
+        }
+    }
+}
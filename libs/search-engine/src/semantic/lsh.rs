@@ -0,0 +1,248 @@
+//! Índice vetorial aproximado por LSH de hiperplanos aleatórios
+//!
+//! Cada tabela usa `BITS_PER_TABLE` hiperplanos; o lado de cada um dá um bit
+//! da chave do balde. Vetores próximos (cosseno alto) tendem a cair no mesmo
+//! balde em pelo menos uma tabela. A busca também visita os baldes a um bit de
+//! distância e confere os candidatos com o cosseno exato. Com poucos vetores,
+//! a força bruta é mais rápida e exata.
+
+use std::collections::HashMap;
+
+use super::{dot, fnv1a};
+
+const TABLES: usize = 8;
+const BITS_PER_TABLE: usize = 12;
+
+/// Abaixo disso a busca compara com todos os vetores
+const BRUTE_FORCE_LIMIT: usize = 2048;
+
+struct Entry {
+    key: String,
+    vector: Vec<f32>,
+}
+
+pub struct VectorIndex {
+    dimensions: usize,
+    /// `TABLES * BITS_PER_TABLE` hiperplanos, gerados de forma determinística
+    planes: Vec<Vec<f32>>,
+    tables: Vec<HashMap<u32, Vec<usize>>>,
+    entries: Vec<Option<Entry>>,
+    slots: HashMap<String, usize>,
+    free: Vec<usize>,
+}
+
+impl VectorIndex {
+    pub fn new(dimensions: usize) -> Self {
+        let mut random = SplitMix64(fnv1a(b"flowlight-lsh") ^ dimensions as u64);
+        let planes = (0..TABLES * BITS_PER_TABLE)
+            .map(|_| (0..dimensions).map(|_| random.next_f32()).collect())
+            .collect();
+        Self {
+            dimensions,
+            planes,
+            tables: (0..TABLES).map(|_| HashMap::new()).collect(),
+            entries: Vec::new(),
+            slots: HashMap::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Insere ou substitui o vetor de `key`; vetores de outra dimensão são ignorados
+    pub fn insert(&mut self, key: &str, vector: Vec<f32>) {
+        if vector.len() != self.dimensions {
+            return;
+        }
+        self.remove(key);
+
+        let slot = self.free.pop().unwrap_or(self.entries.len());
+        for (table, bucket) in self.buckets(&vector).into_iter().enumerate() {
+            self.tables[table].entry(bucket).or_default().push(slot);
+        }
+        let entry = Some(Entry { key: key.to_string(), vector });
+        if slot == self.entries.len() {
+            self.entries.push(entry);
+        } else {
+            self.entries[slot] = entry;
+        }
+        self.slots.insert(key.to_string(), slot);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(slot) = self.slots.remove(key) else {
+            return;
+        };
+        if let Some(entry) = self.entries[slot].take() {
+            for (table, bucket) in self.buckets(&entry.vector).into_iter().enumerate() {
+                if let Some(slots) = self.tables[table].get_mut(&bucket) {
+                    slots.retain(|&s| s != slot);
+                    if slots.is_empty() {
+                        self.tables[table].remove(&bucket);
+                    }
+                }
+            }
+        }
+        self.free.push(slot);
+    }
+
+    /// Remove as chaves que começam com `prefix`
+    pub fn remove_prefix(&mut self, prefix: &str) {
+        let keys: Vec<String> = self.slots.keys().filter(|key| key.starts_with(prefix)).cloned().collect();
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.tables.iter_mut().for_each(HashMap::clear);
+        self.entries.clear();
+        self.slots.clear();
+        self.free.clear();
+    }
+
+    /// Cosseno entre o vetor de `key` e `query`, se `key` estiver no índice
+    pub fn similarity(&self, key: &str, query: &[f32]) -> Option<f32> {
+        let slot = *self.slots.get(key)?;
+        self.entries[slot].as_ref().map(|entry| dot(query, &entry.vector))
+    }
+
+    /// Até `limit` chaves mais próximas de `query` (normalizado), com o cosseno
+    pub fn search(&self, query: &[f32], limit: usize) -> Vec<(String, f32)> {
+        if query.len() != self.dimensions || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<usize> = if self.len() <= BRUTE_FORCE_LIMIT {
+            self.slots.values().copied().collect()
+        } else {
+            let mut candidates = Vec::new();
+            for (table, bucket) in self.buckets(query).into_iter().enumerate() {
+                // O próprio balde e os vizinhos a um bit (multi-probe)
+                let probes = std::iter::once(bucket).chain((0..BITS_PER_TABLE).map(|bit| bucket ^ (1 << bit)));
+                for probe in probes {
+                    if let Some(slots) = self.tables[table].get(&probe) {
+                        candidates.extend_from_slice(slots);
+                    }
+                }
+            }
+            candidates
+        };
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut scored: Vec<(String, f32)> = candidates
+            .into_iter()
+            .filter_map(|slot| self.entries[slot].as_ref())
+            .map(|entry| (entry.key.clone(), dot(query, &entry.vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        scored
+    }
+
+    fn buckets(&self, vector: &[f32]) -> Vec<u32> {
+        self.planes
+            .chunks(BITS_PER_TABLE)
+            .map(|planes| {
+                planes
+                    .iter()
+                    .enumerate()
+                    .fold(0u32, |bucket, (bit, plane)| if dot(plane, vector) >= 0.0 { bucket | 1 << bit } else { bucket })
+            })
+            .collect()
+    }
+}
+
+/// Gerador pseudoaleatório simples, só para os hiperplanos
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniforme em [-1, 1)
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::normalize;
+
+    fn random_vectors(count: usize, dimensions: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut random = SplitMix64(seed);
+        (0..count)
+            .map(|_| {
+                let mut vector: Vec<f32> = (0..dimensions).map(|_| random.next_f32()).collect();
+                normalize(&mut vector);
+                vector
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_remove_and_exact_search() {
+        let mut index = VectorIndex::new(3);
+        index.insert("/a/x", vec![1.0, 0.0, 0.0]);
+        index.insert("/a/y", vec![0.0, 1.0, 0.0]);
+        index.insert("/b/z", vec![0.6, 0.8, 0.0]);
+        index.insert("/bad", vec![1.0]);
+        assert_eq!(index.len(), 3);
+
+        let results = index.search(&[1.0, 0.0, 0.0], 2);
+        assert_eq!(results.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(), vec!["/a/x", "/b/z"]);
+        assert_eq!(index.similarity("/b/z", &[1.0, 0.0, 0.0]), Some(0.6));
+        assert_eq!(index.similarity("/missing", &[1.0, 0.0, 0.0]), None);
+
+        // Substituir reaproveita a chave
+        index.insert("/a/x", vec![0.0, 0.0, 1.0]);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.search(&[0.0, 0.0, 1.0], 1)[0].0, "/a/x");
+
+        index.remove_prefix("/a/");
+        assert_eq!(index.len(), 1);
+        index.clear();
+        assert!(index.is_empty());
+        assert_eq!(index.dimensions(), 3);
+    }
+
+    #[test]
+    fn test_lsh_finds_near_neighbours() {
+        let dimensions = 32;
+        let vectors = random_vectors(BRUTE_FORCE_LIMIT * 2, dimensions, 7);
+        let mut index = VectorIndex::new(dimensions);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.insert(&format!("doc{}", i), vector.clone());
+        }
+
+        // Uma versão levemente alterada de cada vetor deve encontrar o original
+        let noise = random_vectors(100, dimensions, 11);
+        let mut found = 0;
+        for (i, noise) in noise.iter().enumerate() {
+            let mut query: Vec<f32> = vectors[i].iter().zip(noise).map(|(v, n)| v + 0.1 * n).collect();
+            normalize(&mut query);
+            if index.search(&query, 5).iter().any(|(key, _)| *key == format!("doc{}", i)) {
+                found += 1;
+            }
+        }
+        assert!(found >= 95, "recall too low: {}/100", found);
+    }
+}
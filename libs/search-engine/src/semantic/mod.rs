@@ -0,0 +1,322 @@
+//! Busca semântica: embeddings dos documentos e ranking híbrido
+//!
+//! O `Embedder` transforma texto em vetores; os vetores ficam no próprio
+//! índice Tantivy (campo `embedding`) e, em memória, num `VectorIndex` para a
+//! busca aproximada. Na busca, a similaridade de cosseno é combinada com a
+//! pontuação BM25 (ver `blend_scores`).
+
+mod lsh;
+
+pub use lsh::VectorIndex;
+
+use anyhow::Result;
+use log::{debug, warn};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::SearchConfig;
+
+/// Caracteres do documento enviados ao embedder (título + começo do conteúdo)
+pub const EMBEDDING_INPUT_CHARS: usize = 4096;
+
+/// Similaridade mínima para um resultado vir só da busca vetorial
+pub const MIN_SEMANTIC_SIMILARITY: f32 = 0.25;
+
+/// Gera embeddings de texto
+pub trait Embedder: Send + Sync {
+    /// Identifica o modelo; vetores de outro modelo no índice são recalculados
+    fn name(&self) -> &str;
+
+    fn dimensions(&self) -> usize;
+
+    /// Vetor de `dimensions()` posições para `text`
+    fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Embedder configurado em `SearchConfig`, se a busca semântica estiver ligada
+pub fn embedder_from_config(config: &SearchConfig) -> Option<Arc<dyn Embedder>> {
+    if !config.semantic_search {
+        return None;
+    }
+    let embedder: Arc<dyn Embedder> = if config.embedding_command.is_empty() {
+        Arc::new(HashEmbedder::new(config.embedding_dimensions))
+    } else {
+        Arc::new(
+            CommandEmbedder::new(config.embedding_command.clone(), config.embedding_dimensions)
+                .with_timeout(Duration::from_millis(config.embedding_timeout_ms)),
+        )
+    };
+    Some(embedder)
+}
+
+/// Texto do documento usado para o embedding
+pub fn embedding_input(title: &str, body: &str) -> String {
+    let mut input = String::with_capacity(title.len() + 1 + body.len().min(EMBEDDING_INPUT_CHARS));
+    input.push_str(title);
+    input.push('\n');
+    input.extend(body.chars().take(EMBEDDING_INPUT_CHARS));
+    input
+}
+
+/// Normaliza para norma 1, de modo que o cosseno seja o produto escalar
+pub fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Pontuação híbrida: BM25 normalizado pelo maior da busca, misturado ao cosseno
+///
+/// `semantic_weight` 0 é só BM25 e 1 é só similaridade; documentos sem vetor
+/// contam com similaridade 0.
+pub fn blend_scores(bm25: f32, max_bm25: f32, similarity: f32, semantic_weight: f32) -> f32 {
+    let keyword = if max_bm25 > 0.0 { bm25 / max_bm25 } else { 0.0 };
+    (1.0 - semantic_weight) * keyword + semantic_weight * similarity.max(0.0)
+}
+
+/// Vetor em bytes (f32 little-endian), como é gravado no índice
+pub(crate) fn vector_to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub(crate) fn vector_from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// Embedder determinístico por hashing de palavras e trigramas
+///
+/// Não entende sinônimos: aproxima textos com vocabulário parecido. Serve para
+/// testes e como padrão quando nenhum modelo local foi configurado.
+pub struct HashEmbedder {
+    dimensions: usize,
+    name: String,
+}
+
+impl HashEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self { dimensions, name: format!("hash-{}", dimensions) }
+    }
+
+    fn add_feature(&self, vector: &mut [f32], feature: &str, weight: f32) {
+        let hash = fnv1a(feature.as_bytes());
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[(hash % self.dimensions as u64) as usize] += sign * weight;
+    }
+}
+
+impl Embedder for HashEmbedder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vector = vec![0.0; self.dimensions];
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+            let word = word.to_lowercase();
+            self.add_feature(&mut vector, &word, 1.0);
+            // Trigramas aproximam variações da mesma palavra ("budget", "budgets")
+            let padded: Vec<char> = format!("#{}#", word).chars().collect();
+            for trigram in padded.windows(3) {
+                self.add_feature(&mut vector, &trigram.iter().collect::<String>(), 0.5);
+            }
+        }
+        normalize(&mut vector);
+        Ok(vector)
+    }
+}
+
+/// FNV-1a de 64 bits: estável entre versões do Rust, ao contrário do `DefaultHasher`
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Tempo de resposta padrão do `CommandEmbedder`
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Modelo local executado como processo de longa duração
+///
+/// O processo é iniciado no primeiro uso e reaproveitado entre documentos:
+/// cada texto vai como uma string JSON numa linha da entrada padrão e o vetor
+/// volta como um array JSON numa linha da saída. Se não responder dentro do
+/// prazo, ou sair, o processo é encerrado e recriado na próxima chamada.
+pub struct CommandEmbedder {
+    command: Vec<String>,
+    dimensions: usize,
+    name: String,
+    timeout: Duration,
+    process: Mutex<Option<EmbedderProcess>>,
+}
+
+/// Processo em execução e as linhas da sua saída, lidas numa thread
+struct EmbedderProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<std::io::Result<String>>,
+}
+
+impl EmbedderProcess {
+    fn spawn(command: &[String]) -> Result<Self> {
+        let (program, args) = command.split_first().ok_or_else(|| anyhow::anyhow!("Embedding command is empty"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| anyhow::anyhow!("Embedding command has no stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("Embedding command has no stdout"))?;
+
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        debug!("Started embedding command (pid {})", child.id());
+        Ok(Self { child, stdin, lines })
+    }
+
+    fn request(&mut self, text: &str, timeout: Duration) -> Result<String> {
+        let mut request = serde_json::to_string(text)?;
+        request.push('\n');
+        self.stdin.write_all(request.as_bytes())?;
+        self.stdin.flush()?;
+
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(line?),
+            Err(RecvTimeoutError::Timeout) => anyhow::bail!("Embedding command did not answer within {:?}", timeout),
+            Err(RecvTimeoutError::Disconnected) => match self.child.try_wait()? {
+                Some(status) => anyhow::bail!("Embedding command exited with {}", status),
+                None => anyhow::bail!("Embedding command closed its output"),
+            },
+        }
+    }
+}
+
+impl Drop for EmbedderProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl CommandEmbedder {
+    pub fn new(command: Vec<String>, dimensions: usize) -> Self {
+        let name = format!("command:{}", command.join(" "));
+        Self { command, dimensions, name, timeout: DEFAULT_COMMAND_TIMEOUT, process: Mutex::new(None) }
+    }
+
+    /// Prazo para cada resposta do processo
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Embedder for CommandEmbedder {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut process = self.process.lock().unwrap_or_else(|e| e.into_inner());
+        if process.is_none() {
+            *process = Some(EmbedderProcess::spawn(&self.command)?);
+        }
+        let response = match process.as_mut().map(|running| running.request(text, self.timeout)) {
+            Some(Ok(response)) => response,
+            Some(Err(e)) => {
+                // Um processo travado ou morto não serve para o próximo documento
+                warn!("Restarting embedding command: {}", e);
+                *process = None;
+                return Err(e);
+            }
+            None => unreachable!("embedding process was just started"),
+        };
+
+        let mut vector: Vec<f32> = serde_json::from_str(&response)?;
+        if vector.len() != self.dimensions {
+            anyhow::bail!("Embedding command returned {} dimensions, expected {}", vector.len(), self.dimensions);
+        }
+        normalize(&mut vector);
+        Ok(vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_embedder_is_deterministic_and_normalized() {
+        let embedder = HashEmbedder::new(64);
+        let a = embedder.embed("Quarterly budget review").unwrap();
+        assert_eq!(a, HashEmbedder::new(64).embed("quarterly BUDGET review").unwrap());
+        assert!((dot(&a, &a) - 1.0).abs() < 1e-5);
+
+        let similar = embedder.embed("budgets for the quarter").unwrap();
+        let unrelated = embedder.embed("holiday photos from the beach").unwrap();
+        assert!(dot(&a, &similar) > dot(&a, &unrelated));
+        assert_eq!(vector_from_bytes(&vector_to_bytes(&a)), a);
+    }
+
+    #[test]
+    fn test_blend_scores() {
+        assert_eq!(blend_scores(5.0, 10.0, 0.9, 0.0), 0.5);
+        assert_eq!(blend_scores(5.0, 10.0, 0.9, 1.0), 0.9);
+        assert!((blend_scores(10.0, 10.0, -0.5, 0.4) - 0.6).abs() < 1e-6);
+        assert_eq!(blend_scores(0.0, 0.0, 0.5, 0.5), 0.25);
+    }
+
+    #[cfg(unix)]
+    fn shell_embedder(script: &str, timeout: Duration) -> CommandEmbedder {
+        let command = vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        CommandEmbedder::new(command, 2).with_timeout(timeout)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_embedder_reuses_the_process() {
+        // Responde com o número do pedido: o segundo vetor só muda se o processo for o mesmo
+        let embedder = shell_embedder(r#"n=0; while read -r line; do n=$((n+1)); echo "[$n, 1]"; done"#, Duration::from_secs(5));
+        let first = embedder.embed("first document").unwrap();
+        let second = embedder.embed("second\ndocument").unwrap();
+        assert!((first[0] - first[1]).abs() < 1e-6);
+        assert!(second[0] > second[1]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_embedder_timeout_kills_the_process() {
+        let embedder = shell_embedder("exec sleep 30", Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        assert!(embedder.embed("never answered").is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(embedder.process.lock().unwrap().is_none());
+
+        let embedder = shell_embedder("read -r line; echo '[1, 2, 3]'", Duration::from_secs(5));
+        assert!(embedder.embed("wrong size").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Snapshots do índice para copiar para outra máquina
+//!
+//! O snapshot é um arquivo tar com `manifest.json` primeiro (versão do
+//! formato, pastas raiz, escopos e contagens) e depois os arquivos Tantivy de
+//! cada escopo em `index/<escopo>/`. Na importação, `PathRemap` troca o
+//! começo dos caminhos gravados (por exemplo `/home/alice` → `/home/bob`).
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use crate::config::ScopeConfig;
+
+/// Versão do layout do arquivo; snapshots de outra versão são recusados
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const INDEX_DIR: &str = "index";
+
+/// Metadados gravados junto com o índice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    /// Momento da exportação (segundos Unix)
+    pub created_at: i64,
+    /// Pastas indexadas na máquina de origem
+    pub roots: Vec<PathBuf>,
+    pub scope_configs: Vec<ScopeConfig>,
+    pub scopes: Vec<SnapshotScope>,
+    /// Modelo dos embeddings gravados, se a busca semântica estava ligada
+    pub embedding_model: Option<String>,
+}
+
+/// Índice de um escopo dentro do snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotScope {
+    pub name: String,
+    pub doc_count: u64,
+}
+
+impl SnapshotManifest {
+    /// Pastas raiz da origem com os remapeamentos aplicados
+    pub fn remapped_roots(&self, remaps: &[PathRemap]) -> Vec<PathBuf> {
+        self.roots.iter().map(|root| PathRemap::apply_all(remaps, root)).collect()
+    }
+}
+
+/// Troca do prefixo `from` por `to` nos caminhos importados
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRemap {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl PathRemap {
+    pub fn new(from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self { from: from.into(), to: to.into() }
+    }
+
+    /// `path` com o prefixo trocado, se começar por `from` (comparando componentes)
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        let rest = path.strip_prefix(&self.from).ok()?;
+        Some(if rest.as_os_str().is_empty() { self.to.clone() } else { self.to.join(rest) })
+    }
+
+    /// Aplica o remapeamento de `from` mais longo que casar; sem nenhum, devolve `path`
+    pub fn apply_all(remaps: &[PathRemap], path: &Path) -> PathBuf {
+        remaps
+            .iter()
+            .filter(|remap| path.starts_with(&remap.from))
+            .max_by_key(|remap| remap.from.components().count())
+            .and_then(|remap| remap.apply(path))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+/// Formato `DE=PARA`, como em `--remap /home/alice=/home/bob`
+impl FromStr for PathRemap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(Self::new(from, to)),
+            _ => anyhow::bail!("Invalid path remap {:?}, expected FROM=TO", s),
+        }
+    }
+}
+
+impl fmt::Display for PathRemap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.from.display(), self.to.display())
+    }
+}
+
+/// Escreve o snapshot num arquivo temporário, renomeado só em `finish`
+pub(crate) struct SnapshotWriter {
+    /// `None` depois de `finish`
+    builder: Option<tar::Builder<BufWriter<File>>>,
+    partial_path: PathBuf,
+    archive_path: PathBuf,
+}
+
+impl SnapshotWriter {
+    pub(crate) fn create(archive_path: &Path, manifest: &SnapshotManifest) -> Result<Self> {
+        let mut partial_name = archive_path.file_name().unwrap_or_default().to_os_string();
+        partial_name.push(".partial");
+        let partial_path = archive_path.with_file_name(partial_name);
+
+        let mut writer = Self {
+            builder: Some(tar::Builder::new(BufWriter::new(File::create(&partial_path)?))),
+            partial_path,
+            archive_path: archive_path.to_path_buf(),
+        };
+        writer.append(Path::new(MANIFEST_NAME), &serde_json::to_vec_pretty(manifest)?)?;
+        Ok(writer)
+    }
+
+    pub(crate) fn add_file(&mut self, scope: &str, file: &Path, data: &[u8]) -> Result<()> {
+        self.append(&Path::new(INDEX_DIR).join(scope).join(file), data)
+    }
+
+    fn append(&mut self, path: &Path, data: &[u8]) -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        let builder = self.builder.as_mut().expect("snapshot already finished");
+        builder.append_data(&mut header, path, data)?;
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> Result<()> {
+        let builder = self.builder.take().expect("snapshot already finished");
+        let file = builder.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&self.partial_path, &self.archive_path)?;
+        Ok(())
+    }
+}
+
+impl Drop for SnapshotWriter {
+    fn drop(&mut self) {
+        // Exportação interrompida: não deixar um arquivo truncado para trás
+        if self.builder.take().is_some() {
+            let _ = std::fs::remove_file(&self.partial_path);
+        }
+    }
+}
+
+/// Extrai o snapshot em `dest`, com um subdiretório por escopo
+///
+/// Só aceita `manifest.json` seguido de arquivos em `index/<escopo>/`, para
+/// um snapshot adulterado não escrever fora de `dest`.
+pub(crate) fn extract_snapshot(archive_path: &Path, dest: &Path) -> Result<SnapshotManifest> {
+    let mut archive = tar::Archive::new(BufReader::new(File::open(archive_path)?));
+    let mut entries = archive.entries()?;
+
+    let manifest: SnapshotManifest = match entries.next() {
+        Some(entry) => {
+            let entry = entry?;
+            if entry.path()? != Path::new(MANIFEST_NAME) {
+                anyhow::bail!("Not a search index snapshot: {:?}", archive_path);
+            }
+            serde_json::from_reader(entry)?
+        }
+        None => anyhow::bail!("Search index snapshot is empty: {:?}", archive_path),
+    };
+    if manifest.format_version != SNAPSHOT_FORMAT_VERSION {
+        anyhow::bail!(
+            "Unsupported snapshot format version {} (expected {})",
+            manifest.format_version,
+            SNAPSHOT_FORMAT_VERSION
+        );
+    }
+    for scope in &manifest.scopes {
+        if !ScopeConfig::is_valid_name(&scope.name) {
+            anyhow::bail!("Invalid search scope name in snapshot: {:?}", scope.name);
+        }
+        std::fs::create_dir_all(dest.join(&scope.name))?;
+    }
+
+    for entry in entries {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let (scope, file) = match path.components().collect::<Vec<_>>().as_slice() {
+            [Component::Normal(dir), Component::Normal(scope), Component::Normal(file)] if *dir == INDEX_DIR => {
+                (scope.to_string_lossy().to_string(), PathBuf::from(file))
+            }
+            _ => anyhow::bail!("Unexpected entry in search index snapshot: {:?}", path),
+        };
+        if !entry.header().entry_type().is_file() || !manifest.scopes.iter().any(|s| s.name == scope) {
+            anyhow::bail!("Unexpected entry in search index snapshot: {:?}", path);
+        }
+        entry.unpack(dest.join(&scope).join(file))?;
+    }
+
+    for scope in &manifest.scopes {
+        if !dest.join(&scope.name).join("meta.json").exists() {
+            anyhow::bail!("Search index snapshot is missing scope '{}'", scope.name);
+        }
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_remap() {
+        let remap: PathRemap = "/home/alice=/home/bob".parse().unwrap();
+        assert_eq!(remap.to_string(), "/home/alice=/home/bob");
+        assert_eq!(remap.apply(Path::new("/home/alice/doc.md")), Some(PathBuf::from("/home/bob/doc.md")));
+        assert_eq!(remap.apply(Path::new("/home/alice")), Some(PathBuf::from("/home/bob")));
+        // Compara componentes, não texto
+        assert_eq!(remap.apply(Path::new("/home/alicia/doc.md")), None);
+        assert!("/home/alice".parse::<PathRemap>().is_err());
+        assert!("=/home/bob".parse::<PathRemap>().is_err());
+
+        let remaps = [remap, PathRemap::new("/home/alice/work", "/srv/work")];
+        assert_eq!(PathRemap::apply_all(&remaps, Path::new("/home/alice/work/a.md")), PathBuf::from("/srv/work/a.md"));
+        assert_eq!(PathRemap::apply_all(&remaps, Path::new("/home/alice/b.md")), PathBuf::from("/home/bob/b.md"));
+        assert_eq!(PathRemap::apply_all(&remaps, Path::new("/tmp/c.md")), PathBuf::from("/tmp/c.md"));
+    }
+
+    #[test]
+    fn test_extract_rejects_unexpected_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("snapshot.tar");
+        let manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            created_at: 0,
+            roots: Vec::new(),
+            scope_configs: Vec::new(),
+            scopes: vec![SnapshotScope { name: "default".to_string(), doc_count: 0 }],
+            embedding_model: None,
+        };
+
+        let mut writer = SnapshotWriter::create(&archive, &manifest).unwrap();
+        writer.add_file("default", Path::new("meta.json"), b"{}").unwrap();
+        writer.append(Path::new("index/other/meta.json"), b"{}").unwrap();
+        writer.finish().unwrap();
+        let error = extract_snapshot(&archive, &dir.path().join("out")).unwrap_err();
+        assert!(error.to_string().contains("Unexpected entry"), "{}", error);
+
+        let mut writer = SnapshotWriter::create(&archive, &manifest).unwrap();
+        writer.add_file("default", Path::new("meta.json"), b"{}").unwrap();
+        writer.finish().unwrap();
+        assert!(!dir.path().join("snapshot.tar.partial").exists());
+        let extracted = extract_snapshot(&archive, &dir.path().join("out")).unwrap();
+        assert_eq!(extracted.scopes.len(), 1);
+        assert!(dir.path().join("out/default/meta.json").exists());
+    }
+}
@@ -0,0 +1,57 @@
+//! Utilitários compartilhados pelos testes do crate
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tempfile::TempDir;
+
+use crate::config::SearchConfig;
+use crate::engine::SearchEngine;
+use crate::indexer::FileIndexer;
+
+/// Pasta temporária com os arquivos a indexar
+pub fn corpus_dir() -> TempDir {
+    tempfile::Builder::new().prefix("r5-corpus").tempdir().unwrap()
+}
+
+/// Pasta temporária para o índice
+pub fn index_dir() -> TempDir {
+    tempfile::Builder::new().prefix("r5-index").tempdir().unwrap()
+}
+
+/// Indexa só `corpus`; sem os `ignored_paths` padrão, que excluiriam pastas temporárias
+pub fn corpus_config(corpus: &Path) -> SearchConfig {
+    SearchConfig {
+        indexed_paths: vec![corpus.to_path_buf()],
+        ignored_paths: vec![],
+        ..SearchConfig::default()
+    }
+}
+
+/// Indexador de `config` com a varredura inicial já feita
+pub async fn scanned_indexer(engine: &Arc<SearchEngine>, config: SearchConfig) -> FileIndexer {
+    let indexer = FileIndexer::new(engine.clone(), config);
+    indexer.start_indexing().await.unwrap();
+    indexer
+}
+
+/// Cria `files` num corpus novo e os indexa num índice novo
+pub async fn index_corpus(files: &[(&str, &[u8])]) -> (Arc<SearchEngine>, TempDir, TempDir) {
+    index_corpus_with(files, |config| config).await
+}
+
+/// Como `index_corpus`, com a configuração ajustada por `configure`
+pub async fn index_corpus_with(
+    files: &[(&str, &[u8])],
+    configure: impl FnOnce(SearchConfig) -> SearchConfig,
+) -> (Arc<SearchEngine>, TempDir, TempDir) {
+    let corpus = corpus_dir();
+    for (name, content) in files {
+        std::fs::write(corpus.path().join(name), content).unwrap();
+    }
+
+    let index_dir = index_dir();
+    let engine = Arc::new(SearchEngine::open_in_dir(index_dir.path()).await.unwrap());
+    scanned_indexer(&engine, configure(corpus_config(corpus.path()))).await;
+    (engine, corpus, index_dir)
+}
@@ -0,0 +1,363 @@
+//! Indexação incremental baseada em eventos do sistema de arquivos
+//!
+//! O `FileWatcher` observa os diretórios indexados e aplica criações,
+//! modificações, renomeações e remoções ao índice Tantivy. Rajadas de eventos
+//! são agrupadas (debounce) e aplicadas em um único commit.
+
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::exclusion::is_ignore_file;
+use crate::indexer::FileIndexer;
+
+/// Silêncio necessário antes de aplicar um lote de eventos
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+/// Atraso máximo de um lote, mesmo sob fluxo contínuo de eventos
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(5);
+
+/// O que aconteceu com um caminho durante o lote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    /// Criado ou renomeado para dentro da árvore observada
+    Added,
+    /// Conteúdo ou metadados alterados
+    Changed,
+    /// Removido ou renomeado para fora da árvore observada
+    Removed,
+}
+
+/// Eventos acumulados e ainda não aplicados ao índice
+#[derive(Debug, Default)]
+struct PendingChanges {
+    paths: BTreeMap<PathBuf, ChangeKind>,
+    rescans: BTreeSet<PathBuf>,
+    rescan_all: bool,
+}
+
+impl PendingChanges {
+    fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.rescans.is_empty() && !self.rescan_all
+    }
+
+    fn push(&mut self, event: Event) {
+        if event.need_rescan() {
+            // Eventos foram perdidos (ex.: overflow da fila do inotify)
+            self.request_rescan(event.paths);
+            return;
+        }
+
+        match event.kind {
+            EventKind::Create(_) => self.record_all(event.paths, ChangeKind::Added),
+            EventKind::Remove(_) => self.record_all(event.paths, ChangeKind::Removed),
+            EventKind::Modify(ModifyKind::Name(mode)) => match mode {
+                RenameMode::From => self.record_all(event.paths, ChangeKind::Removed),
+                RenameMode::To => self.record_all(event.paths, ChangeKind::Added),
+                RenameMode::Both if event.paths.len() == 2 => {
+                    self.record(event.paths[0].clone(), ChangeKind::Removed);
+                    self.record(event.paths[1].clone(), ChangeKind::Added);
+                }
+                // Sem saber a direção, o estado é conferido no disco ao aplicar
+                _ => self.record_all(event.paths, ChangeKind::Added),
+            },
+            EventKind::Modify(_) => self.record_all(event.paths, ChangeKind::Changed),
+            EventKind::Access(_) => {}
+            EventKind::Any | EventKind::Other => self.record_all(event.paths, ChangeKind::Changed),
+        }
+    }
+
+    fn request_rescan(&mut self, paths: Vec<PathBuf>) {
+        if paths.is_empty() {
+            self.rescan_all = true;
+        } else {
+            self.rescans.extend(paths);
+        }
+    }
+
+    fn record_all(&mut self, paths: Vec<PathBuf>, kind: ChangeKind) {
+        for path in paths {
+            self.record(path, kind);
+        }
+    }
+
+    fn record(&mut self, path: PathBuf, kind: ChangeKind) {
+        let kind = match (self.paths.get(&path), kind) {
+            // Uma modificação não anula uma criação ainda não aplicada
+            (Some(ChangeKind::Added), ChangeKind::Changed) => ChangeKind::Added,
+            _ => kind,
+        };
+        self.paths.insert(path, kind);
+    }
+}
+
+/// Observa os diretórios indexados e mantém o índice atualizado
+pub struct FileWatcher {
+    indexer: FileIndexer,
+}
+
+/// Mantém o watcher ativo; ao ser descartado, a observação é encerrada
+pub struct WatcherHandle {
+    // Mutex só para tornar o handle `Sync`; o watcher nunca é acessado de novo
+    _watcher: std::sync::Mutex<Box<dyn Watcher + Send>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for WatcherHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl FileWatcher {
+    pub fn new(indexer: FileIndexer) -> Self {
+        Self { indexer }
+    }
+
+    /// Começa a observar as pastas de `SearchConfig::roots` (indexadas e dos escopos)
+    pub fn start(self) -> Result<WatcherHandle> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let roots = self.roots();
+
+        let watcher = match Self::create_watcher(RecommendedWatcher::new(event_sender(tx.clone()), Config::default()), &roots) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                // Ex.: limite de watches do inotify atingido; recorre ao polling
+                let interval = Duration::from_secs(self.indexer.config().index_update_interval.max(1));
+                warn!("Native file watcher unavailable ({}), polling every {:?}", e, interval);
+                let config = Config::default().with_poll_interval(interval);
+                Self::create_watcher(PollWatcher::new(event_sender(tx), config), &roots)?
+            }
+        };
+
+        info!("File watcher started for {} directories", roots.len());
+        let task = tokio::spawn(self.run(rx, roots));
+        Ok(WatcherHandle { _watcher: std::sync::Mutex::new(watcher), task })
+    }
+
+    fn roots(&self) -> Vec<PathBuf> {
+        self.indexer.config().roots().into_iter().filter(|path| path.is_dir()).collect()
+    }
+
+    fn create_watcher<W: Watcher + Send + 'static>(
+        watcher: notify::Result<W>,
+        roots: &[PathBuf],
+    ) -> notify::Result<Box<dyn Watcher + Send>> {
+        let mut watcher = watcher?;
+        for root in roots {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        }
+        Ok(Box::new(watcher))
+    }
+
+    async fn run(self, mut rx: mpsc::UnboundedReceiver<notify::Result<Event>>, roots: Vec<PathBuf>) {
+        while let Some(first) = rx.recv().await {
+            let mut pending = PendingChanges::default();
+            self.collect(&mut pending, first);
+
+            // Agrupa eventos até um período de silêncio ou o atraso máximo do lote
+            let deadline = Instant::now() + MAX_BATCH_DELAY;
+            while Instant::now() < deadline {
+                match tokio::time::timeout(DEBOUNCE_DELAY, rx.recv()).await {
+                    Ok(Some(event)) => self.collect(&mut pending, event),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+
+            if pending.is_empty() {
+                continue;
+            }
+            if let Err(e) = self.apply(pending, &roots).await {
+                error!("Failed to apply file system changes to the index: {}", e);
+            }
+        }
+        debug!("File watcher channel closed");
+    }
+
+    fn collect(&self, pending: &mut PendingChanges, event: notify::Result<Event>) {
+        match event {
+            Ok(mut event) => {
+                // Regras de exclusão mudaram: a pasta inteira precisa ser reavaliada
+                let rule_dirs: Vec<PathBuf> = event
+                    .paths
+                    .iter()
+                    .filter(|path| is_ignore_file(path))
+                    .filter_map(|path| path.parent())
+                    .filter(|dir| !self.is_ignored(dir))
+                    .map(Path::to_path_buf)
+                    .collect();
+                if !rule_dirs.is_empty() {
+                    for dir in &rule_dirs {
+                        self.indexer.exclusions().invalidate(dir);
+                    }
+                    pending.request_rescan(rule_dirs);
+                }
+
+                event.paths.retain(|path| !self.is_ignored(path));
+                if !event.paths.is_empty() || event.need_rescan() {
+                    pending.push(event);
+                }
+            }
+            Err(e) => {
+                warn!("File watcher error: {}", e);
+                pending.request_rescan(e.paths.clone());
+            }
+        }
+    }
+
+    /// Ignora o próprio índice e caminhos excluídos (inclusive por um diretório pai)
+    fn is_ignored(&self, path: &Path) -> bool {
+        if path.starts_with(self.indexer.search_engine().index_path()) {
+            return true;
+        }
+
+        self.indexer.exclusions().is_excluded_with_ancestors(path, path.is_dir())
+    }
+
+    async fn apply(&self, pending: PendingChanges, roots: &[PathBuf]) -> Result<()> {
+        let engine = self.indexer.search_engine();
+
+        let rescans: Vec<PathBuf> = if pending.rescan_all {
+            roots.to_vec()
+        } else {
+            pending.rescans.into_iter().collect()
+        };
+        // Um caminho com erro não impede que o resto do lote chegue ao índice
+        for dir in &rescans {
+            info!("Rescanning {:?} after lost file system events", dir);
+            if let Err(e) = self.refresh(dir, ChangeKind::Added).await {
+                warn!("Could not rescan '{}': {}", dir.display(), e);
+            }
+        }
+
+        for (path, kind) in pending.paths {
+            // Já coberto por um rescan deste lote
+            if rescans.iter().any(|dir| path.starts_with(dir)) {
+                continue;
+            }
+            if let Err(e) = self.refresh(&path, kind).await {
+                warn!("Could not update '{}' in the index: {}", path.display(), e);
+            }
+        }
+
+        engine.commit_changes().await?;
+        debug!("Applied file system changes to the index");
+        Ok(())
+    }
+
+    /// Sincroniza o índice com o estado atual de `path` no disco
+    async fn refresh(&self, path: &Path, kind: ChangeKind) -> Result<()> {
+        let engine = self.indexer.search_engine();
+        let path_str = path.to_string_lossy().to_string();
+
+        if path.is_file() {
+            if !self.indexer.should_index_file(path) {
+                engine.remove_document(&path_str).await?;
+            } else if let Err(e) = self.indexer.index_file(path).await {
+                warn!("Could not index file '{}': {}", path.display(), e);
+            }
+        } else if path.is_dir() {
+            // Metadados de diretórios mudam a todo momento; só uma pasta nova exige varredura
+            if kind == ChangeKind::Added {
+                self.indexer.index_folder(path.to_path_buf()).await?;
+                self.indexer.remove_missing_under(path).await?;
+            }
+        } else {
+            engine.remove_document(&path_str).await?;
+            engine.remove_documents_under(path).await?;
+        }
+        Ok(())
+    }
+}
+
+fn event_sender(tx: mpsc::UnboundedSender<notify::Result<Event>>) -> impl Fn(notify::Result<Event>) + Send + 'static {
+    move |event| {
+        let _ = tx.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{corpus_config, corpus_dir, index_dir, scanned_indexer};
+    use crate::engine::SearchEngine;
+    use notify::event::{CreateKind, DataChange, Flag, RemoveKind};
+    use std::sync::Arc;
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        let mut event = Event::new(kind);
+        event.paths = paths.iter().map(PathBuf::from).collect();
+        event
+    }
+
+    #[test]
+    fn test_pending_changes_coalesce() {
+        let mut pending = PendingChanges::default();
+        pending.push(event(EventKind::Create(CreateKind::File), &["/r/a.txt"]));
+        pending.push(event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &["/r/a.txt"]));
+        pending.push(event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &["/r/b.txt"]));
+        pending.push(event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &["/r/c.txt", "/r/d.txt"]));
+        pending.push(event(EventKind::Remove(RemoveKind::File), &["/r/b.txt"]));
+
+        assert_eq!(pending.paths.get(Path::new("/r/a.txt")), Some(&ChangeKind::Added));
+        assert_eq!(pending.paths.get(Path::new("/r/b.txt")), Some(&ChangeKind::Removed));
+        assert_eq!(pending.paths.get(Path::new("/r/c.txt")), Some(&ChangeKind::Removed));
+        assert_eq!(pending.paths.get(Path::new("/r/d.txt")), Some(&ChangeKind::Added));
+        assert!(!pending.rescan_all);
+    }
+
+    #[test]
+    fn test_overflow_requests_rescan() {
+        let mut pending = PendingChanges::default();
+        pending.push(Event::new(EventKind::Other).set_flag(Flag::Rescan));
+        assert!(pending.rescan_all);
+
+        let mut pending = PendingChanges::default();
+        pending.push(event(EventKind::Other, &["/r/sub"]).set_flag(Flag::Rescan));
+        assert!(!pending.rescan_all);
+        assert!(pending.rescans.contains(Path::new("/r/sub")));
+    }
+
+    async fn wait_for_hits(engine: &SearchEngine, query: &str, expected: usize) -> bool {
+        for _ in 0..50 {
+            if engine.search(query, 10).await.unwrap().len() == expected {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_watcher_applies_changes() {
+        let corpus = corpus_dir();
+        let index_dir = index_dir();
+        let engine = Arc::new(SearchEngine::open_in_dir(index_dir.path()).await.unwrap());
+        let indexer = scanned_indexer(&engine, corpus_config(corpus.path())).await;
+
+        let _handle = FileWatcher::new(indexer).start().unwrap();
+
+        let file = corpus.path().join("report.txt");
+        std::fs::write(&file, "zebra").unwrap();
+        assert!(wait_for_hits(&engine, "zebra", 1).await, "created file not indexed");
+
+        std::fs::write(&file, "giraffe").unwrap();
+        assert!(wait_for_hits(&engine, "giraffe", 1).await, "modified file not reindexed");
+        assert!(wait_for_hits(&engine, "zebra", 0).await, "stale content still indexed");
+
+        let renamed = corpus.path().join("summary.txt");
+        std::fs::rename(&file, &renamed).unwrap();
+        assert!(wait_for_hits(&engine, "summary", 1).await, "renamed file not indexed");
+        assert!(wait_for_hits(&engine, "report", 0).await, "old name still indexed");
+
+        std::fs::remove_file(&renamed).unwrap();
+        assert!(wait_for_hits(&engine, "giraffe", 0).await, "deleted file still indexed");
+    }
+}
@@ -39,6 +39,9 @@ pub struct UiConfig {
     pub max_results: usize,
     pub animation_speed: f64,
     pub auto_hide_delay: u64,
+    /// Overlay executable the daemon starts; `real-overlay` next to the daemon when unset
+    #[serde(default)]
+    pub overlay_path: Option<PathBuf>,
 }
 
 impl Default for R5Config {
@@ -89,6 +92,7 @@ impl Default for UiConfig {
             max_results: 10,
             animation_speed: 0.2,
             auto_hide_delay: 150,
+            overlay_path: None,
         }
     }
 }
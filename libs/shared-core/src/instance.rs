@@ -0,0 +1,176 @@
+// Single-instance lock
+// An advisory lock (`flock`/`LockFileEx`) on `<name>.pid`, held for the
+// lifetime of a process (daemon, overlay). The operating system releases it
+// when the process exits, even on a crash or SIGKILL, so there is no stale
+// lock to detect. The PID in the file only tells a second launch whom it is
+// forwarding to.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use fs4::FileExt;
+use log::debug;
+
+/// Lock names of the processes that must run only once per user
+pub const DAEMON_INSTANCE: &str = "r5-flowlight-daemon";
+pub const OVERLAY_INSTANCE: &str = "r5-flowlight-overlay";
+
+/// Outcome of `InstanceLock::acquire`
+#[derive(Debug)]
+pub enum InstanceStatus {
+    /// This process is now the running instance
+    Acquired(InstanceLock),
+    /// Another process holds the lock; its PID, or 0 if it couldn't be read
+    Running(u32),
+}
+
+/// Held while the process runs; the lock is released on drop or exit
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// `<runtime dir>/<name>.pid`, falling back to the data directory
+    pub fn default_path(name: &str) -> Option<PathBuf> {
+        dirs::runtime_dir()
+            .or_else(crate::utils::data_dir)
+            .map(|dir| dir.join(format!("{}.pid", name)))
+    }
+
+    pub fn acquire(path: &Path) -> anyhow::Result<InstanceStatus> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if file.try_lock_exclusive().is_err() {
+            return Ok(InstanceStatus::Running(holder_pid(&file).unwrap_or(0)));
+        }
+
+        let lock = Self { file, path: path.to_path_buf() };
+        lock.refresh()?;
+        debug!("🔒 Instance lock acquired: {:?}", path);
+        Ok(InstanceStatus::Acquired(lock))
+    }
+
+    /// PID of the process (other than this one) holding the lock at `path`
+    pub fn holder(path: &Path) -> Option<u32> {
+        let file = File::open(path).ok()?;
+        if file.try_lock_shared().is_ok() {
+            // Nobody holds it
+            let _ = file.unlock();
+            return None;
+        }
+        holder_pid(&file).filter(|&pid| pid != std::process::id())
+    }
+
+    /// Rewrite the PID, e.g. after forking into the background
+    pub fn refresh(&self) -> std::io::Result<()> {
+        let mut file = &self.file;
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// PID of the running instance called `name`, if any
+pub fn running_instance(name: &str) -> Option<u32> {
+    InstanceLock::default_path(name).and_then(|path| InstanceLock::holder(&path))
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The file stays: removing it could race with the next instance locking it
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// PID written by the holder of `file`'s lock; it is written right after
+/// locking, so an empty file is read again a few times
+fn holder_pid(file: &File) -> Option<u32> {
+    for _ in 0..10 {
+        if let Some(pid) = read_pid(file) {
+            return Some(pid);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    None
+}
+
+fn read_pid(mut file: &File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquired(path: &Path) -> InstanceLock {
+        match InstanceLock::acquire(path).unwrap() {
+            InstanceStatus::Acquired(lock) => lock,
+            InstanceStatus::Running(pid) => panic!("held by {}", pid),
+        }
+    }
+
+    fn file_pid(path: &Path) -> Option<u32> {
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    #[test]
+    fn test_acquire_and_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("test.pid");
+
+        let lock = acquired(&path);
+        assert_eq!(file_pid(&path), Some(std::process::id()));
+        // Our own PID never counts as another instance
+        assert_eq!(InstanceLock::holder(&path), None);
+
+        drop(lock);
+        assert_eq!(file_pid(&path), None);
+        assert_eq!(InstanceLock::holder(&path), None);
+        drop(acquired(&path));
+    }
+
+    #[test]
+    fn test_held_lock_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.pid");
+
+        // The lock belongs to the open file, so a second open conflicts even in this process
+        let _lock = acquired(&path);
+        assert!(matches!(
+            InstanceLock::acquire(&path).unwrap(),
+            InstanceStatus::Running(pid) if pid == std::process::id()
+        ));
+    }
+
+    #[test]
+    fn test_unlocked_pid_file_is_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.pid");
+        // Left by a crashed instance; PID 1 being alive doesn't matter
+        std::fs::write(&path, "1").unwrap();
+
+        assert_eq!(InstanceLock::holder(&path), None);
+        let _lock = acquired(&path);
+        assert_eq!(file_pid(&path), Some(std::process::id()));
+    }
+}
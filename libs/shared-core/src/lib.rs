@@ -3,7 +3,9 @@
 pub mod types;
 pub mod config;
 pub mod utils;
+pub mod instance;
 
 pub use types::*;
 pub use config::*;
-pub use utils::*;
\ No newline at end of file
+pub use utils::*;
+pub use instance::*;
\ No newline at end of file
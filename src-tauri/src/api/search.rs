@@ -4,6 +4,10 @@
 
 use serde::{Deserialize, Serialize};
 use log::info;
+use std::path::Path;
+use std::time::Duration;
+use ipc_communication::{IPCMessage, SearchResult as IpcSearchResult, TcpIPCClient};
+use crate::core::AppIndexer;
 
 /// Resultado de busca de arquivo
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,7 +31,13 @@ pub struct AppResult {
     pub bundle_id: Option<String>,
 }
 
+/// Tempo máximo de espera pela resposta do daemon
+const DAEMON_SEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// Handler para busca de arquivos
+///
+/// O índice pertence ao daemon (que mantém o lock dele aberto), então a busca
+/// vai por IPC e só os resultados do módulo `files` são devolvidos.
 pub async fn search_files_handler(query: String) -> Result<Vec<SearchResult>, String> {
     info!("API: Searching files for query: {}", query);
 
@@ -35,100 +45,100 @@ pub async fn search_files_handler(query: String) -> Result<Vec<SearchResult>, St
         return Ok(Vec::new());
     }
 
-    // Busca real de arquivos no sistema
-    use std::path::Path;
-    use walkdir::WalkDir;
-    use std::fs;
-
-    let mut results = Vec::new();
-    let home_dir = dirs::home_dir().unwrap_or_else(|| Path::new("/").to_path_buf());
-
-    // Diretórios comuns para buscar
-    let search_dirs = vec![
-        home_dir.join("Documents"),
-        home_dir.join("Desktop"),
-        home_dir.join("Downloads"),
-    ];
-
-    for search_dir in search_dirs {
-        if !search_dir.exists() {
-            continue;
-        }
-
-        let walker = WalkDir::new(&search_dir)
-            .max_depth(3)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-            .take(10); // Limite para performance
-
-        for entry in walker {
-            let file_name = entry.file_name().to_string_lossy().to_lowercase();
-            let query_lower = query.to_lowercase();
-
-            if file_name.contains(&query_lower) {
-                let path = entry.path();
-                let metadata = fs::metadata(path).ok();
-
-                let file_type = path.extension()
-                    .and_then(|ext| ext.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                let modified = metadata
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|t| chrono::DateTime::from(t).checked_sub_signed(chrono::Duration::seconds(0)))
-                    .unwrap_or_else(chrono::Utc::now);
-
-                // Calcular score baseado na similaridade
-                let score = if file_name == query_lower {
-                    1.0
-                } else if file_name.starts_with(&query_lower) {
-                    0.9
-                } else {
-                    0.7
-                };
-
-                results.push(SearchResult {
-                    title: path.file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_else(|| "Unknown".to_string()),
-                    path: path.to_string_lossy().to_string(),
-                    content_preview: format!("Arquivo {} encontrado", file_type.to_uppercase()),
-                    score,
-                    file_type,
-                    size,
-                    modified,
-                });
+    let results = tokio::time::timeout(DAEMON_SEARCH_TIMEOUT, search_daemon(query))
+        .await
+        .map_err(|_| "O daemon de busca não respondeu a tempo".to_string())??;
+
+    let files: Vec<IpcSearchResult> = results
+        .into_iter()
+        .filter(|result| result.metadata.get("module").map(String::as_str) == Some("files"))
+        .collect();
+    // O daemon já devolve os resultados ordenados pela pontuação
+    let count = files.len();
+    Ok(files
+        .into_iter()
+        .enumerate()
+        .map(|(rank, result)| to_file_result(result, 1.0 - rank as f32 / count as f32))
+        .collect())
+}
 
-                if results.len() >= 10 {
-                    break;
-                }
-            }
-        }
-
-        if results.len() >= 10 {
-            break;
+/// Envia a consulta ao daemon e espera os resultados desta sessão
+async fn search_daemon(query: String) -> Result<Vec<IpcSearchResult>, String> {
+    let mut client = TcpIPCClient::new()
+        .await
+        .map_err(|e| format!("Daemon de busca indisponível: {}", e))?;
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    client
+        .send(IPCMessage::SearchQuery { query, session_id: session_id.clone() })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // A conexão também recebe mensagens de broadcast, como o progresso da indexação
+    loop {
+        match client.receive().await.map_err(|e| e.to_string())? {
+            IPCMessage::SearchResults { results, session_id: id } if id == session_id => return Ok(results),
+            _ => continue,
         }
     }
+}
 
-    // Ordenar por score
-    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-
-    Ok(results)
+fn to_file_result(result: IpcSearchResult, score: f32) -> SearchResult {
+    let path = result.metadata.get("path").cloned().unwrap_or(result.id);
+    let file_type = Path::new(&path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let metadata = std::fs::metadata(&path).ok();
+
+    SearchResult {
+        title: result.title,
+        content_preview: result.metadata.get("snippet").cloned().unwrap_or(result.description),
+        score,
+        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+        modified: metadata
+            .and_then(|m| m.modified().ok())
+            .map(chrono::DateTime::from)
+            .unwrap_or_else(chrono::Utc::now),
+        file_type,
+        path,
+    }
 }
 
 /// Handler para busca de aplicativos
-pub async fn search_apps_handler(query: String) -> Result<Vec<AppResult>, String> {
+///
+/// `indexer` é o indexador mantido no estado do Tauri, indexado uma vez na inicialização
+pub async fn search_apps_handler(query: String, indexer: &AppIndexer) -> Result<Vec<AppResult>, String> {
     info!("API: Searching apps for query: {}", query);
 
     if query.trim().is_empty() {
         return Ok(Vec::new());
     }
 
-    // Busca real de aplicativos no macOS
-    use std::path::Path;
+    Ok(find_apps(&query, indexer).await)
+}
+
+/// Aplicativos das entradas `.desktop` do freedesktop
+#[cfg(target_os = "linux")]
+async fn find_apps(query: &str, indexer: &AppIndexer) -> Vec<AppResult> {
+    indexer
+        .search(query, 10)
+        .await
+        .into_iter()
+        .map(|app| AppResult {
+            name: app.name,
+            path: app.path.to_string_lossy().to_string(),
+            icon: app.icon,
+            version: None,
+            bundle_id: Some(app.id),
+        })
+        .collect()
+}
+
+/// Busca real de aplicativos no macOS
+#[cfg(not(target_os = "linux"))]
+async fn find_apps(query: &str, _indexer: &AppIndexer) -> Vec<AppResult> {
     use walkdir::WalkDir;
 
     let mut results = Vec::new();
@@ -199,5 +209,5 @@ pub async fn search_apps_handler(query: String) -> Result<Vec<AppResult>, String
     // Ordenar alfabeticamente
     results.sort_by(|a, b| a.name.cmp(&b.name));
 
-    Ok(results)
+    results
 }
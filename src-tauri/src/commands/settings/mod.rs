@@ -60,6 +60,25 @@ pub struct SearchSettings {
     pub content_types: Vec<String>,
 }
 
+impl SearchSettings {
+    /// Copia pastas e padrões de exclusão para a configuração usada pelo indexador
+    ///
+    /// Os padrões da interface se somam aos padrões de fábrica (`target`, `dist`...);
+    /// para reincluir um deles, use `!padrão`.
+    pub fn apply_to(&self, config: &mut crate::utils::config::SearchConfig) {
+        if !self.included_paths.is_empty() {
+            config.indexed_paths = self.included_paths.iter().map(PathBuf::from).collect();
+        }
+        let mut patterns = crate::utils::config::default_excluded_patterns();
+        for pattern in &self.excluded_patterns {
+            if !patterns.contains(pattern) {
+                patterns.push(pattern.clone());
+            }
+        }
+        config.excluded_patterns = patterns;
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppearanceSettings {
     pub theme: String,
@@ -106,6 +125,14 @@ pub struct PerformanceSettings {
     pub cache_size: i32,
 }
 
+impl PerformanceSettings {
+    /// Copia os limites de recursos para a indexação em segundo plano
+    pub fn apply_to(&self, config: &mut crate::utils::config::SearchConfig) {
+        config.indexing_workers = self.max_concurrent.max(1) as usize;
+        config.background_throttling = self.background_throttling;
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IntegrationSettings {
     pub github: bool,
@@ -273,6 +300,19 @@ pub fn save_settings<R: Runtime>(resolver: &PathResolver<R>, settings: &AppSetti
     fs::write(&settings_path, settings_json).map_err(|e| format!("Falha ao salvar configurações: {}", e))?;
 
     info!("Configurações salvas com sucesso em {:?}", settings_path);
+
+    // O indexador lê a configuração principal: manter exclusões e limites em um só lugar
+    match crate::utils::config::AppConfig::load() {
+        Ok(mut config) => {
+            settings.search.apply_to(&mut config.search);
+            settings.performance.apply_to(&mut config.search);
+            if let Err(e) = config.save() {
+                error!("Falha ao salvar as configurações do indexador: {}", e);
+            }
+        }
+        Err(e) => error!("Falha ao carregar a configuração principal: {}", e),
+    }
+
     Ok(())
 }
 
@@ -382,3 +422,21 @@ pub(crate) fn setup_autostart(enabled: bool) -> Result<(), Box<dyn std::error::E
     info!("Autostart at login {}", if enabled { "enabled" } else { "disabled" });
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::config::SearchConfig;
+
+    #[test]
+    fn test_search_settings_keep_default_exclusions() {
+        let mut config = SearchConfig::default();
+        AppSettings::default().search.apply_to(&mut config);
+
+        for pattern in ["node_modules", "target", "dist", "build", "*.log", "*.tmp"] {
+            assert!(config.excluded_patterns.iter().any(|p| p == pattern), "missing {}", pattern);
+        }
+        // Sem repetir o que já vem de fábrica
+        assert_eq!(config.excluded_patterns.iter().filter(|p| *p == "node_modules").count(), 1);
+    }
+}
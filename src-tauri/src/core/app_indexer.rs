@@ -1,32 +1,66 @@
 //! Application indexer implementation
-//! 
+//!
 //! Indexador de aplicativos para busca
 
 use anyhow::Result;
 use log::info;
+use ::modules::desktop_entry::DesktopEntry;
+use ::modules::{AppsModule, SearchModule};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
 
 /// Indexador de aplicativos
+///
+/// Mantém um `AppsModule` já indexado, para que as buscas usem a mesma
+/// pontuação do daemon sem reler as entradas `.desktop` a cada tecla.
 pub struct AppIndexer {
     pub enabled: bool,
+    module: RwLock<AppsModule>,
 }
 
 impl AppIndexer {
     /// Cria uma nova instância do indexador de apps
     pub fn new() -> Result<Self> {
         info!("App indexer initialized");
-        Ok(Self { enabled: true })
+        Ok(Self { enabled: true, module: RwLock::new(AppsModule::new()) })
     }
-    
+
     /// Inicia a indexação de aplicativos
+    ///
+    /// Lê as entradas `.desktop` de `~/.local/share/applications` e `XDG_DATA_DIRS`
     pub async fn start_indexing(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
         info!("Starting app indexing...");
-        // TODO: Implementar indexação real de aplicativos
+        let mut module = self.module.write().await;
+        module.initialize(HashMap::new()).await?;
+
+        info!("Indexed {} applications", module.entries().len());
         Ok(())
     }
+
+    /// Aplicativos indexados
+    pub async fn apps(&self) -> Vec<DesktopEntry> {
+        self.module.read().await.entries().to_vec()
+    }
+
+    /// Busca aplicativos pelo nome, nome genérico ou palavras-chave, com a
+    /// pontuação fuzzy do `AppsModule`
+    pub async fn search(&self, query: &str, limit: usize) -> Vec<DesktopEntry> {
+        self.module
+            .read()
+            .await
+            .ranked(query, limit)
+            .into_iter()
+            .map(|(app, _)| app.clone())
+            .collect()
+    }
 }
 
 impl Default for AppIndexer {
     fn default() -> Self {
-        Self::new().unwrap_or(Self { enabled: false })
+        Self::new().unwrap_or(Self { enabled: false, module: RwLock::new(AppsModule::new()) })
     }
-}
\ No newline at end of file
+}
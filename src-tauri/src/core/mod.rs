@@ -7,12 +7,11 @@
 //! - Gerenciamento de hotkeys globais
 
 pub mod search_engine;
-pub mod file_indexer;
 pub mod app_indexer;
 pub mod hotkey_manager;
 
 // ✅ CORREÇÃO: Re-exports ativos para facilitar o uso
-pub use search_engine::{SearchEngine, SearchResult, AppResult};
-pub use file_indexer::FileIndexer;
-// pub use app_indexer::AppIndexer; // Descomente quando implementado
+pub use search_engine::{SearchEngine, SearchResult, AppResult, FileFingerprint, IndexedDocument};
+pub use ::search_engine::{FileIndexer, FileWatcher, WatcherHandle};
+pub use app_indexer::AppIndexer;
 // pub use hotkey_manager::HotkeyManager; // Descomente quando implementado
@@ -60,8 +60,8 @@ async fn search_files(query: String) -> Result<Vec<api::search::SearchResult>, S
 }
 
 #[tauri::command]
-async fn search_apps(query: String) -> Result<Vec<api::search::AppResult>, String> {
-    api::search::search_apps_handler(query).await
+async fn search_apps(query: String, indexer: State<'_, core::AppIndexer>) -> Result<Vec<api::search::AppResult>, String> {
+    api::search::search_apps_handler(query, &indexer).await
 }
 
 #[tauri::command]
@@ -222,6 +222,7 @@ fn force_window_to_front<R: Runtime>(window: &tauri::WebviewWindow<R>) -> Result
 fn main() {
     tauri::Builder::default()
         .manage(AppState::default())
+        .manage(core::AppIndexer::default())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_os::init())
@@ -298,6 +299,12 @@ fn main() {
                 // let search_engine = SearchEngine::new().await.expect("Failed to initialize search engine");
                 // handle_clone.manage(search_engine);
                 info!("App principal inicializado - busca gerenciada pelo daemon/overlay");
+
+                // Indexa os aplicativos uma vez; as buscas reutilizam o índice
+                let indexer = handle_clone.state::<core::AppIndexer>();
+                if let Err(e) = indexer.start_indexing().await {
+                    warn!("Failed to index applications: {}", e);
+                }
             });
 
             // List all available windows to debug
@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use anyhow::Result;
 
+// A configuração de busca pertence ao crate de indexação
+pub use ::search_engine::SearchConfig;
+pub use ::search_engine::config::default_excluded_patterns;
+
 /// Configuração principal da aplicação
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -38,28 +42,6 @@ pub struct GeneralConfig {
     pub show_in_tray: bool,
 }
 
-/// Configurações de busca
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SearchConfig {
-    /// Diretórios para indexar
-    pub indexed_paths: Vec<PathBuf>,
-
-    /// Diretórios para ignorar
-    pub ignored_paths: Vec<PathBuf>,
-
-    /// Extensões de arquivo para indexar
-    pub indexed_extensions: Vec<String>,
-
-    /// Tamanho máximo de arquivo (MB)
-    pub max_file_size_mb: u64,
-
-    /// Intervalo de atualização do índice (segundos)
-    pub index_update_interval: u64,
-
-    /// Incluir arquivos ocultos na indexação
-    pub include_hidden: bool,
-}
-
 /// Configurações de IA
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIConfig {
@@ -101,30 +83,7 @@ impl Default for AppConfig {
                 start_with_system: false,
                 show_in_tray: true,
             },
-            search: SearchConfig {
-                indexed_paths: vec![
-                    dirs::home_dir().unwrap_or_default(),
-                    dirs::desktop_dir().unwrap_or_default(),
-                    dirs::document_dir().unwrap_or_default(),
-                ],
-                ignored_paths: vec![
-                    PathBuf::from(".git"),
-                    PathBuf::from("node_modules"),
-                    PathBuf::from(".DS_Store"),
-                ],
-                indexed_extensions: vec![
-                    "txt".to_string(), "md".to_string(), "pdf".to_string(),
-                    "doc".to_string(), "docx".to_string(), "xls".to_string(),
-                    "xlsx".to_string(), "ppt".to_string(), "pptx".to_string(),
-                    "js".to_string(), "ts".to_string(), "py".to_string(),
-                    "rs".to_string(), "go".to_string(), "java".to_string(),
-                    "c".to_string(), "cpp".to_string(), "h".to_string(),
-                    "html".to_string(), "css".to_string(), "json".to_string(),
-                ],
-                max_file_size_mb: 50,
-                index_update_interval: 300, // 5 minutos
-                include_hidden: false, // Não incluir arquivos ocultos por padrão
-            },
+            search: SearchConfig::default(),
             ai: AIConfig {
                 default_provider: "openai".to_string(),
                 openai_api_key: None,
@@ -186,12 +145,8 @@ impl AppConfig {
         Ok(())
     }
 
-    /// Retorna o caminho do arquivo de configuração
+    /// Retorna o caminho do arquivo de configuração; o daemon lê a seção `[search]` dele
     fn config_path() -> PathBuf {
-        if let Some(config_dir) = dirs::config_dir() {
-            config_dir.join("r5-flowlight").join("config.toml")
-        } else {
-            PathBuf::from("config.toml")
-        }
+        ::search_engine::app_config_path()
     }
 }
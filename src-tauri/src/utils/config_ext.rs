@@ -18,81 +18,22 @@ pub trait AppConfigExt {
     fn should_index_extension(&self, extension: &str) -> bool;
 }
 
+// As regras vivem em `SearchConfig`, no crate de indexação
 impl AppConfigExt for AppConfig {
     fn should_index_file(&self, path: &Path) -> bool {
-        // Verificar se o caminho está na lista de ignorados
-        if self.should_ignore_path(path) {
-            return false;
-        }
-        
-        // Verificar extensão do arquivo
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            if !self.should_index_extension(ext) {
-                return false;
-            }
-        }
-        
-        // Verificar tamanho máximo do arquivo (se aplicável)
-        if let Ok(metadata) = std::fs::metadata(path) {
-            let max_size_bytes = self.search.max_file_size_mb * 1024 * 1024;
-            if metadata.len() > max_size_bytes {
-                return false;
-            }
-        }
-        
-        true
+        self.search.should_index_file(path)
     }
     
     fn should_index_dir(&self, path: &Path) -> bool {
-        // Verificar se o caminho está na lista de ignorados
-        if self.should_ignore_path(path) {
-            return false;
-        }
-        
-        // Verificar se o diretório está na lista de caminhos indexados
-        for indexed_path in &self.search.indexed_paths {
-            if path.starts_with(indexed_path) {
-                return true;
-            }
-        }
-        
-        false
+        self.search.should_index_dir(path)
     }
     
     fn should_ignore_path(&self, path: &Path) -> bool {
-        // Verificar se o caminho está na lista de ignorados
-        for ignored_path in &self.search.ignored_paths {
-            if path.starts_with(ignored_path) {
-                return true;
-            }
-        }
-        
-        // Verificar se é um diretório oculto (começa com .)
-        if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-            if file_name.starts_with('.') && file_name != "." && !self.search.include_hidden {
-                return true;
-            }
-            
-            // Ignorar diretórios do sistema
-            let ignored_dirs = ["node_modules", "target", "dist", "build", ".git"];
-            if ignored_dirs.contains(&file_name) {
-                return true;
-            }
-        }
-        
-        false
+        self.search.should_ignore_path(path)
     }
     
     fn should_index_extension(&self, extension: &str) -> bool {
-        // Se não houver extensões especificadas, indexa todas
-        if self.search.indexed_extensions.is_empty() {
-            return true;
-        }
-        
-        // Verificar se a extensão está na lista de extensões indexadas
-        self.search.indexed_extensions
-            .iter()
-            .any(|ext| ext.eq_ignore_ascii_case(extension))
+        self.search.should_index_extension(extension)
     }
 }
 
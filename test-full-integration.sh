@@ -12,7 +12,7 @@ echo ""
 
 # Start daemon in background
 echo "🔥 Starting search daemon..."
-cargo run --bin simple-daemon -- --log-level info &
+cargo run -p search-daemon --no-default-features -- --log-level info &
 DAEMON_PID=$!
 
 # Wait for daemon to start